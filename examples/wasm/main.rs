@@ -1,8 +1,12 @@
+#[path = "../../src/icon.rs"]
+mod icon;
+
 use std::{
     collections::HashMap,
     io::{BufRead, BufReader, Read, Write},
-    net::{TcpListener, ToSocketAddrs},
+    net::{SocketAddr, TcpListener, TcpStream},
     process::Command,
+    time::Duration,
 };
 
 type CoreError = Box<dyn core::error::Error>;
@@ -13,13 +17,31 @@ const INDEX_LEN: usize = INDEX.len();
 const GLUE: &[u8] = include_bytes!("./miniquad_wasm_glue.js");
 const GLUE_LEN: usize = GLUE.len();
 
+type Routes = HashMap<String, Box<dyn Fn(String) -> Vec<u8> + Send>>;
+
 fn main() -> Result<(), CoreError> {
+    // `--check` serves on a free port, requests every route, and exits instead of serving forever
+    let check = std::env::args().any(|argument| argument == "--check");
+
     compile_wasm()?;
     let wasm = load_wasm()?;
+    let favicon = render_favicon()?;
+
+    if check {
+        return check_routes(&wasm, &favicon);
+    }
+
+    let server = TcpListener::bind("127.0.0.1:7878")?;
+    println!("Serving on \nhttp://localhost:7878");
+
+    serve(server, routes(wasm, favicon))
+}
+
+fn routes(wasm: Vec<u8>, favicon: Vec<u8>) -> Routes {
     let wasm_len = wasm.len();
+    let favicon_len = favicon.len();
 
-    let host_address = "127.0.0.1:7878";
-    let routes = HashMap::from([
+    HashMap::from([
         (
             "GET /miniquad_wasm_glue.js HTTP/1.1".to_owned(),
             Box::new(|_request| {
@@ -43,17 +65,127 @@ fn main() -> Result<(), CoreError> {
             }) as _,
         ),
         (
-            "GET /index.html HTTP/1.1".to_owned(),
-            Box::new(|_request| {
-                format!("HTTP/1.1 200 OK\r\nContent-Length: {INDEX_LEN}\r\n\r\n{INDEX}")
-                    .into_bytes()
+            "GET /favicon.png HTTP/1.1".to_owned(),
+            Box::new(move |_request| {
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {favicon_len}\r\nContent-Type: image/png\r\n\r\n"
+                );
+                let mut response = header.into_bytes();
+                response.extend_from_slice(&favicon);
+                response
             }) as _,
         ),
-    ]);
+        (
+            "GET /index.html HTTP/1.1".to_owned(),
+            Box::new(|_request| index_response()) as _,
+        ),
+    ])
+}
 
-    println!("Serving on \nhttp://localhost:7878");
+fn index_response() -> Vec<u8> {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {INDEX_LEN}\r\nContent-Type: text/html\r\n\r\n{INDEX}"
+    )
+    .into_bytes()
+}
+
+/// Serve on an ephemeral port and request every route, checking the status, content type, and body of each response
+fn check_routes(wasm: &[u8], favicon: &[u8]) -> Result<(), CoreError> {
+    let server = TcpListener::bind("127.0.0.1:0")?;
+    let address = server.local_addr()?;
+    let routes = routes(wasm.to_vec(), favicon.to_vec());
+    std::thread::spawn(move || {
+        if let Err(error) = serve(server, routes) {
+            eprintln!("Server stopped: {error}");
+        }
+    });
+    println!("Checking routes on http://{address}");
 
-    serve(host_address, routes)
+    let expected_responses: [(&str, &str, &[u8]); 5] = [
+        ("/", "text/html", INDEX.as_bytes()),
+        ("/index.html", "text/html", INDEX.as_bytes()),
+        ("/miniquad_wasm_glue.js", "application/javascript", GLUE),
+        ("/mandelbrot.wasm", "application/wasm", wasm),
+        ("/favicon.png", "image/png", favicon),
+    ];
+
+    let mut failure_count = 0;
+    for (path, content_type, body) in expected_responses {
+        match check_route(address, path, content_type, body) {
+            Ok(()) => println!("ok   GET {path}"),
+            Err(error) => {
+                println!("FAIL GET {path}: {error}");
+                failure_count += 1;
+            }
+        }
+    }
+
+    if failure_count > 0 {
+        return Err(format!("{failure_count} route(s) failed").into());
+    }
+    println!("All routes ok");
+    Ok(())
+}
+
+fn check_route(
+    address: SocketAddr,
+    path: &str,
+    expected_content_type: &str,
+    expected_body: &[u8],
+) -> Result<(), CoreError> {
+    let mut client = TcpStream::connect(address)?;
+    client.set_read_timeout(Some(Duration::from_secs(10)))?;
+    write!(client, "GET {path} HTTP/1.1\r\nHost: {address}\r\n\r\n")?;
+
+    // the server closes the connection after each response
+    let mut response = Vec::new();
+    client.read_to_end(&mut response)?;
+
+    let header_end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or("response has no end of headers")?;
+    let header = String::from_utf8_lossy(&response[..header_end]);
+    let body = &response[header_end + 4..];
+
+    let mut lines = header.lines();
+    let status_line = lines.next().ok_or("response has no status line")?;
+    if status_line != "HTTP/1.1 200 OK" {
+        return Err(format!("unexpected status {status_line:?}").into());
+    }
+    let headers = lines
+        .filter_map(|line| line.split_once(": "))
+        .map(|(name, value)| (name.to_ascii_lowercase(), value))
+        .collect::<HashMap<_, _>>();
+
+    let content_type = headers.get("content-type").copied();
+    if content_type != Some(expected_content_type) {
+        return Err(format!(
+            "expected content type {expected_content_type:?}, got {content_type:?}"
+        )
+        .into());
+    }
+    let content_length = headers
+        .get("content-length")
+        .ok_or("missing content length")?
+        .parse::<usize>()?;
+    if content_length != body.len() {
+        return Err(format!(
+            "content length is {content_length} but the body is {} bytes",
+            body.len()
+        )
+        .into());
+    }
+    if body != expected_body {
+        return Err(format!(
+            "body is {} bytes but {} were expected, or the bytes differ",
+            body.len(),
+            expected_body.len()
+        )
+        .into());
+    }
+
+    Ok(())
 }
 
 fn compile_wasm() -> Result<(), CoreError> {
@@ -75,6 +207,14 @@ fn compile_wasm() -> Result<(), CoreError> {
         .args(&["build", "--release", "--target", "wasm32-unknown-unknown"])
         .output()
         .map_err(|e| format!("Failed to build wasm binary.: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to build wasm binary.: {}\nstderr\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
     println!(
         "{}\n{}\nstdout\n{}\nstderr\n{}",
         "wasm binary built!",
@@ -94,6 +234,20 @@ fn compile_wasm() -> Result<(), CoreError> {
     Ok(())
 }
 
+fn render_favicon() -> Result<Vec<u8>, CoreError> {
+    println!("Rendering favicon...");
+    // the same image as the native window icon
+    let icon = icon::window_icon();
+    let mut favicon = Vec::new();
+    let mut encoder = png::Encoder::new(&mut favicon, 64, 64);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&icon.big)?;
+    writer.finish()?;
+    Ok(favicon)
+}
+
 fn load_wasm() -> Result<Vec<u8>, CoreError> {
     let mut output = Vec::new();
     std::fs::File::options()
@@ -103,12 +257,7 @@ fn load_wasm() -> Result<Vec<u8>, CoreError> {
     Ok(output)
 }
 
-fn serve(
-    host_address: impl ToSocketAddrs,
-    routes: HashMap<String, Box<dyn Fn(String) -> Vec<u8>>>,
-) -> Result<(), CoreError> {
-    let server = TcpListener::bind(host_address)?;
-
+fn serve(server: TcpListener, routes: Routes) -> Result<(), CoreError> {
     for possible_stream in server.incoming() {
         let mut client = match possible_stream {
             Ok(stream) => stream,
@@ -128,8 +277,7 @@ fn serve(
         let request_line = request.lines().next().ok_or("Request line missing")?;
         let response = match routes.get(request_line) {
             Some(request_handler) => request_handler(request),
-            None => format!("HTTP/1.1 200 OK\r\nContent-Length: {INDEX_LEN}\r\n\r\n{INDEX}")
-                .into_bytes(),
+            None => index_response(),
         };
 
         client.write_all(&response)?;
@@ -0,0 +1,62 @@
+use crate::PixelRect;
+use core::fmt::{self, Display, Formatter};
+
+/// Why a [`RenderJob`](crate::RenderJob) didn't produce a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// the job was cancelled before it finished
+    Cancelled,
+    /// the tile asked for isn't inside the job's image
+    TileOutOfBounds {
+        rect: PixelRect,
+        width: usize,
+        height: usize,
+    },
+    /// the grid to continue isn't the job's resolution
+    GridSizeMismatch {
+        grid: (usize, usize),
+        job: (usize, usize),
+    },
+    /// the grid to continue was iterated further than the job's `iteration_max`
+    IterationMaxDecreased { grid: usize, job: usize },
+    /// the grid to reuse was rendered with a different `iteration_max` than the job
+    IterationMaxMismatch { grid: usize, job: usize },
+    /// the rows to append to a grid aren't the grid's width, iteration limit, or stored buffers
+    RowsMismatch,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cancelled => write!(f, "The render job was cancelled"),
+            Self::TileOutOfBounds {
+                rect,
+                width,
+                height,
+            } => write!(
+                f,
+                "The {}x{} tile at row {}, column {} isn't inside the {width}x{height} image",
+                rect.width, rect.height, rect.row, rect.column
+            ),
+            Self::GridSizeMismatch { grid, job } => write!(
+                f,
+                "The {}x{} grid can't be continued by a {}x{} job",
+                grid.0, grid.1, job.0, job.1
+            ),
+            Self::IterationMaxDecreased { grid, job } => write!(
+                f,
+                "The grid was iterated {grid} times, more than the job's {job}"
+            ),
+            Self::IterationMaxMismatch { grid, job } => write!(
+                f,
+                "The grid was iterated {grid} times, but the job iterates {job} times"
+            ),
+            Self::RowsMismatch => write!(
+                f,
+                "The rows have a different width, iteration limit, or stored buffers than the grid"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,151 @@
+#[cfg(feature = "std")]
+use crate::EscapeData;
+use crate::{OrbitIterator, Scalar};
+use num::Complex;
+
+/// An iterated function `zₙ₊₁ = f(zₙ, c)` and when to stop iterating it.
+/// Implement this to render a new fractal without changing the iteration loop
+pub trait FractalFormula<T: Scalar>: Sync {
+    /// The first `z` of the orbit for the pixel at `pixel`
+    fn init(&self, pixel: &Complex<T>) -> Complex<T>;
+
+    /// Calculate `zₙ₊₁` from `zₙ`
+    fn step(&self, z: Complex<T>, pixel: &Complex<T>) -> Complex<T>;
+
+    /// Whether the orbit is certain to diverge once it reaches `z`. Defaults to `|z| > 2`
+    fn has_escaped(&self, z: &Complex<T>) -> bool {
+        norm_sqr(z) > T::from_f64(4.0)
+    }
+}
+
+/// Lets a formula be borrowed by an `OrbitIterator` instead of moved into it
+impl<T: Scalar, F: FractalFormula<T> + ?Sized> FractalFormula<T> for &F {
+    fn init(&self, pixel: &Complex<T>) -> Complex<T> {
+        (**self).init(pixel)
+    }
+
+    fn step(&self, z: Complex<T>, pixel: &Complex<T>) -> Complex<T> {
+        (**self).step(z, pixel)
+    }
+
+    fn has_escaped(&self, z: &Complex<T>) -> bool {
+        (**self).has_escaped(z)
+    }
+}
+
+/// `|z|²` without requiring `T: num::Num`
+pub fn norm_sqr<T: Scalar>(z: &Complex<T>) -> T {
+    z.re.clone() * z.re.clone() + z.im.clone() * z.im.clone()
+}
+
+/// `z²` without requiring `T: num::Num`
+pub fn square<T: Scalar>(z: Complex<T>) -> Complex<T> {
+    let Complex { re, im } = z;
+    // (re + i im)² = re² - im² + 2 re im i
+    Complex::new(
+        re.clone() * re.clone() - im.clone() * im.clone(),
+        T::from_f64(2.0) * re * im,
+    )
+}
+
+fn add<T: Scalar>(a: Complex<T>, b: &Complex<T>) -> Complex<T> {
+    Complex::new(a.re + b.re.clone(), a.im + b.im.clone())
+}
+
+fn zero<T: Scalar>() -> Complex<T> {
+    Complex::new(T::from_f64(0.0), T::from_f64(0.0))
+}
+
+/// `z² + c` starting at `z₀ = 0`, where `c` is the pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Mandelbrot;
+
+impl<T: Scalar> FractalFormula<T> for Mandelbrot {
+    fn init(&self, _pixel: &Complex<T>) -> Complex<T> {
+        zero()
+    }
+
+    fn step(&self, z: Complex<T>, pixel: &Complex<T>) -> Complex<T> {
+        add(square(z), pixel)
+    }
+}
+
+/// `conj(z)² + c` starting at `z₀ = 0`, where `c` is the pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tricorn;
+
+impl<T: Scalar> FractalFormula<T> for Tricorn {
+    fn init(&self, _pixel: &Complex<T>) -> Complex<T> {
+        zero()
+    }
+
+    fn step(&self, z: Complex<T>, pixel: &Complex<T>) -> Complex<T> {
+        add(square(Complex::new(z.re, -z.im)), pixel)
+    }
+}
+
+/// `(|re(z)| + i|im(z)|)² + c` starting at `z₀ = 0`, where `c` is the pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BurningShip;
+
+impl<T: Scalar> FractalFormula<T> for BurningShip {
+    fn init(&self, _pixel: &Complex<T>) -> Complex<T> {
+        zero()
+    }
+
+    fn step(&self, z: Complex<T>, pixel: &Complex<T>) -> Complex<T> {
+        add(square(Complex::new(z.re.abs(), z.im.abs())), pixel)
+    }
+}
+
+/// `z² + c` for a fixed `c`, starting at `z₀` = the pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Julia<T> {
+    pub c: Complex<T>,
+}
+
+impl<T: Scalar> FractalFormula<T> for Julia<T> {
+    fn init(&self, pixel: &Complex<T>) -> Complex<T> {
+        pixel.clone()
+    }
+
+    fn step(&self, z: Complex<T>, _pixel: &Complex<T>) -> Complex<T> {
+        add(square(z), &self.c)
+    }
+}
+
+/// Iterate `formula` for one pixel in the precision of `T`, storing the path as `f32`
+#[cfg(feature = "std")]
+pub(crate) fn escape_path<T: Scalar>(
+    formula: &impl FractalFormula<T>,
+    pixel: Complex<T>,
+    iteration_max: usize,
+) -> EscapeData {
+    let mut orbit = OrbitIterator::new(formula, pixel);
+    let path = orbit
+        .by_ref()
+        .take(iteration_max + 1)
+        .map(|z| Complex::new(z.re.to_f32(), z.im.to_f32()))
+        .collect();
+
+    (orbit.escape_time(), path)
+}
+
+/// Iterate `formula` for one pixel, keeping only the escape time and the last `z`
+pub(crate) fn escape_time<T: Scalar>(
+    formula: &impl FractalFormula<T>,
+    pixel: Complex<T>,
+    iteration_max: usize,
+) -> (Option<usize>, Complex<f32>) {
+    let mut orbit = OrbitIterator::new(formula, pixel);
+    let last_z = orbit
+        .by_ref()
+        .take(iteration_max + 1)
+        .last()
+        .expect("every orbit has a z₀");
+
+    (
+        orbit.escape_time(),
+        Complex::new(last_z.re.to_f32(), last_z.im.to_f32()),
+    )
+}
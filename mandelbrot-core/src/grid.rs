@@ -0,0 +1,421 @@
+use crate::{Error, OrbitStatistics};
+use num::Complex;
+
+/// The escape time of one pixel (`None` if it never escaped) and every `z` its orbit visited, starting at `z₀`
+pub type EscapeData = (Option<usize>, Vec<Complex<f32>>);
+
+/// Stored in [`EscapeGrid::escape_times`] for pixels that never escaped
+pub const NEVER_ESCAPED: u32 = u32::MAX;
+
+/// The result of a [`RenderJob`](crate::RenderJob), stored as one buffer per field so each buffer can be read
+/// (or exported) on its own. Pixels are in row major order, and row 0 is the bottom of the view, where the imaginary part is smallest.
+/// With the `serde` feature the buffers are serialized as they are, and checked for consistent lengths when deserialized
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedEscapeGrid"))]
+pub struct EscapeGrid {
+    width: usize,
+    height: usize,
+    /// the iteration limit the pixels were iterated to
+    iteration_max: usize,
+    /// the escape time of each pixel, or `NEVER_ESCAPED`
+    escape_times: Vec<u32>,
+    /// the last `z` of each pixel's orbit
+    last_z_values: Vec<Complex<f32>>,
+    /// `None` if the job didn't [store paths](crate::RenderJob::store_paths)
+    paths: Option<PathStorage>,
+    /// `None` if the job didn't gather [orbit statistics](crate::RenderJob::orbit_statistics)
+    statistics: Option<StatisticsStorage>,
+}
+
+/// Every pixel's escape path, one after another
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct PathStorage {
+    z_values: Vec<Complex<f32>>,
+    /// pixel `i`'s path is `z_values[starts[i]..starts[i + 1]]`
+    starts: Vec<usize>,
+}
+
+/// Each field of every pixel's `OrbitStatistics`
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct StatisticsStorage {
+    min_norms: Vec<f32>,
+    mean_norms: Vec<f32>,
+    last_arguments: Vec<f32>,
+    cross_trap_distances: Vec<f32>,
+    point_trap_distances: Vec<f32>,
+}
+
+impl StatisticsStorage {
+    fn push(&mut self, statistics: OrbitStatistics) {
+        self.min_norms.push(statistics.min_norm);
+        self.mean_norms.push(statistics.mean_norm);
+        self.last_arguments.push(statistics.last_argument);
+        self.cross_trap_distances
+            .push(statistics.cross_trap_distance);
+        self.point_trap_distances
+            .push(statistics.point_trap_distance);
+    }
+
+    /// Whether every buffer has `length` values
+    #[cfg(feature = "serde")]
+    fn has_length(&self, length: usize) -> bool {
+        [
+            &self.min_norms,
+            &self.mean_norms,
+            &self.last_arguments,
+            &self.cross_trap_distances,
+            &self.point_trap_distances,
+        ]
+        .iter()
+        .all(|buffer| buffer.len() == length)
+    }
+}
+
+/// The part of a grid calculated by one thread
+#[derive(Debug, Default)]
+pub(crate) struct GridRow {
+    escape_times: Vec<u32>,
+    last_z_values: Vec<Complex<f32>>,
+    path_z_values: Vec<Complex<f32>>,
+    path_lengths: Vec<usize>,
+    statistics: Vec<OrbitStatistics>,
+}
+
+impl GridRow {
+    /// `path` and `statistics` are stored when given, and `path` must end with `last_z`
+    pub(crate) fn push(
+        &mut self,
+        escape_time: Option<usize>,
+        last_z: Complex<f32>,
+        path: Option<&[Complex<f32>]>,
+        statistics: Option<OrbitStatistics>,
+    ) {
+        self.escape_times
+            .push(escape_time.map_or(NEVER_ESCAPED, |escape_time| escape_time as u32));
+        self.last_z_values.push(last_z);
+        if let Some(path) = path {
+            self.path_z_values.extend_from_slice(path);
+            self.path_lengths.push(path.len());
+        }
+        if let Some(statistics) = statistics {
+            self.statistics.push(statistics);
+        }
+    }
+}
+
+impl EscapeGrid {
+    /// Join rows calculated separately into one grid
+    pub(crate) fn from_rows(
+        width: usize,
+        height: usize,
+        iteration_max: usize,
+        rows: Vec<GridRow>,
+        store_paths: bool,
+        store_statistics: bool,
+    ) -> Self {
+        let pixel_count = width * height;
+        let mut escape_times = Vec::with_capacity(pixel_count);
+        let mut last_z_values = Vec::with_capacity(pixel_count);
+        let mut paths = store_paths.then(|| PathStorage {
+            z_values: Vec::new(),
+            starts: vec![0],
+        });
+        let mut statistics = store_statistics.then(StatisticsStorage::default);
+
+        for row in rows {
+            escape_times.extend(row.escape_times);
+            last_z_values.extend(row.last_z_values);
+            if let Some(paths) = &mut paths {
+                paths.z_values.extend(row.path_z_values);
+                for path_length in row.path_lengths {
+                    let start = paths.starts[paths.starts.len() - 1];
+                    paths.starts.push(start + path_length);
+                }
+            }
+            if let Some(statistics) = &mut statistics {
+                for pixel_statistics in row.statistics {
+                    statistics.push(pixel_statistics);
+                }
+            }
+        }
+        debug_assert_eq!(escape_times.len(), pixel_count);
+
+        Self {
+            width,
+            height,
+            iteration_max,
+            escape_times,
+            last_z_values,
+            paths,
+            statistics,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The iteration limit the grid was rendered with. Each pixel that never escaped stopped at this iteration,
+    /// with its [last `z`](Self::last_z) being `z` number `iteration_max`
+    pub fn iteration_max(&self) -> usize {
+        self.iteration_max
+    }
+
+    /// The number of pixels
+    pub fn len(&self) -> usize {
+        self.escape_times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.escape_times.is_empty()
+    }
+
+    /// The index of a pixel in each buffer
+    pub fn pixel_index(&self, row_index: usize, column_index: usize) -> usize {
+        row_index * self.width + column_index
+    }
+
+    /// The escape time of one pixel, `None` if it never escaped. Panics if `pixel_index` is out of bounds
+    pub fn escape_time(&self, pixel_index: usize) -> Option<usize> {
+        match self.escape_times[pixel_index] {
+            NEVER_ESCAPED => None,
+            escape_time => Some(escape_time as usize),
+        }
+    }
+
+    /// The last `z` of one pixel's orbit. Panics if `pixel_index` is out of bounds
+    pub fn last_z(&self, pixel_index: usize) -> Complex<f32> {
+        self.last_z_values[pixel_index]
+    }
+
+    /// Every `z` one pixel's orbit visited, `None` if paths weren't stored. Panics if `pixel_index` is out of bounds
+    pub fn path(&self, pixel_index: usize) -> Option<&[Complex<f32>]> {
+        let paths = self.paths.as_ref()?;
+        Some(&paths.z_values[paths.starts[pixel_index]..paths.starts[pixel_index + 1]])
+    }
+
+    pub fn has_paths(&self) -> bool {
+        self.paths.is_some()
+    }
+
+    /// The statistics of one pixel's orbit, `None` if they weren't gathered. Panics if `pixel_index` is out of bounds
+    pub fn statistics(&self, pixel_index: usize) -> Option<OrbitStatistics> {
+        let statistics = self.statistics.as_ref()?;
+        Some(OrbitStatistics {
+            min_norm: statistics.min_norms[pixel_index],
+            mean_norm: statistics.mean_norms[pixel_index],
+            last_argument: statistics.last_arguments[pixel_index],
+            cross_trap_distance: statistics.cross_trap_distances[pixel_index],
+            point_trap_distance: statistics.point_trap_distances[pixel_index],
+        })
+    }
+
+    pub fn has_statistics(&self) -> bool {
+        self.statistics.is_some()
+    }
+
+    /// A 64-bit FNV-1a hash of the size, iteration limit, and every stored buffer, bit for bit.
+    /// Rendering is deterministic, so the same job always gives the same checksum on every platform and thread count,
+    /// and comparing checksums against known good renders catches changes to the results
+    pub fn checksum(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        let mut hash = OFFSET_BASIS;
+        let mut write = |bytes: &[u8]| {
+            for &byte in bytes {
+                hash = (hash ^ byte as u64).wrapping_mul(PRIME);
+            }
+        };
+        for value in [self.width, self.height, self.iteration_max] {
+            write(&(value as u64).to_le_bytes());
+        }
+        for escape_time in &self.escape_times {
+            write(&escape_time.to_le_bytes());
+        }
+        let path_z_values = self.paths.iter().flat_map(|paths| &paths.z_values);
+        for z in self.last_z_values.iter().chain(path_z_values) {
+            write(&z.re.to_bits().to_le_bytes());
+            write(&z.im.to_bits().to_le_bytes());
+        }
+        if let Some(paths) = &self.paths {
+            for start in &paths.starts {
+                write(&(*start as u64).to_le_bytes());
+            }
+        }
+        if let Some(statistics) = &self.statistics {
+            for buffer in [
+                &statistics.min_norms,
+                &statistics.mean_norms,
+                &statistics.last_arguments,
+                &statistics.cross_trap_distances,
+                &statistics.point_trap_distances,
+            ] {
+                for value in buffer {
+                    write(&value.to_bits().to_le_bytes());
+                }
+            }
+        }
+        hash
+    }
+
+    /// Add the rows of `rows` after the last row of this grid, such as the next band of a render
+    /// calculated a few rows at a time with [`run_tile`](crate::RenderJob::run_tile).
+    /// Returns [`Error::RowsMismatch`] unless both grids have the same width, iteration limit, and stored buffers
+    ///
+    /// ```
+    /// use mandelbrot_core::{PixelRect, RenderJob};
+    ///
+    /// let job = RenderJob::<f32>::new().resolution(40, 30);
+    /// let mut grid = job.run_tile(PixelRect { row: 0, column: 0, width: 40, height: 10 }).unwrap();
+    /// grid.append_rows(job.run_tile(PixelRect { row: 10, column: 0, width: 40, height: 20 }).unwrap())
+    ///     .unwrap();
+    /// assert_eq!(grid, job.run());
+    /// ```
+    pub fn append_rows(&mut self, rows: EscapeGrid) -> Result<(), Error> {
+        if rows.width != self.width
+            || rows.iteration_max != self.iteration_max
+            || rows.paths.is_some() != self.paths.is_some()
+            || rows.statistics.is_some() != self.statistics.is_some()
+        {
+            return Err(Error::RowsMismatch);
+        }
+
+        self.height += rows.height;
+        self.escape_times.extend(rows.escape_times);
+        self.last_z_values.extend(rows.last_z_values);
+        if let (Some(paths), Some(rows_paths)) = (&mut self.paths, rows.paths) {
+            let offset = paths.z_values.len();
+            paths.z_values.extend(rows_paths.z_values);
+            paths
+                .starts
+                .extend(rows_paths.starts[1..].iter().map(|start| start + offset));
+        }
+        if let (Some(statistics), Some(rows_statistics)) = (&mut self.statistics, rows.statistics) {
+            statistics.min_norms.extend(rows_statistics.min_norms);
+            statistics.mean_norms.extend(rows_statistics.mean_norms);
+            statistics
+                .last_arguments
+                .extend(rows_statistics.last_arguments);
+            statistics
+                .cross_trap_distances
+                .extend(rows_statistics.cross_trap_distances);
+            statistics
+                .point_trap_distances
+                .extend(rows_statistics.point_trap_distances);
+        }
+        Ok(())
+    }
+
+    /// The number of bytes the stored buffers take up, not counting spare capacity
+    ///
+    /// ```
+    /// use mandelbrot_core::RenderJob;
+    ///
+    /// let grid = RenderJob::<f32>::new().resolution(40, 30).store_paths(false).run();
+    /// // a 4 byte escape time and an 8 byte last z for each pixel
+    /// assert_eq!(grid.memory_size(), 40 * 30 * (4 + 8));
+    /// ```
+    pub fn memory_size(&self) -> usize {
+        let paths_size = self.paths.as_ref().map_or(0, |paths| {
+            size_of_val(paths.z_values.as_slice()) + size_of_val(paths.starts.as_slice())
+        });
+        let statistics_size = self.statistics.as_ref().map_or(0, |statistics| {
+            [
+                &statistics.min_norms,
+                &statistics.mean_norms,
+                &statistics.last_arguments,
+                &statistics.cross_trap_distances,
+                &statistics.point_trap_distances,
+            ]
+            .iter()
+            .map(|buffer| size_of_val(buffer.as_slice()))
+            .sum()
+        });
+        size_of_val(self.escape_times.as_slice())
+            + size_of_val(self.last_z_values.as_slice())
+            + paths_size
+            + statistics_size
+    }
+
+    /// The escape time of every pixel, with `NEVER_ESCAPED` for pixels in the set
+    pub fn escape_times(&self) -> &[u32] {
+        &self.escape_times
+    }
+
+    /// The last `z` of every pixel's orbit
+    pub fn last_z_values(&self) -> &[Complex<f32>] {
+        &self.last_z_values
+    }
+}
+
+/// An `EscapeGrid` as it was deserialized, before its buffers are checked
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedEscapeGrid {
+    width: usize,
+    height: usize,
+    iteration_max: usize,
+    escape_times: Vec<u32>,
+    last_z_values: Vec<Complex<f32>>,
+    paths: Option<PathStorage>,
+    statistics: Option<StatisticsStorage>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedEscapeGrid> for EscapeGrid {
+    type Error = String;
+
+    fn try_from(grid: UncheckedEscapeGrid) -> Result<Self, Self::Error> {
+        let pixel_count = grid
+            .width
+            .checked_mul(grid.height)
+            .ok_or("the grid is too big")?;
+        if grid.escape_times.len() != pixel_count || grid.last_z_values.len() != pixel_count {
+            return Err(format!(
+                "a {}x{} grid needs {pixel_count} escape times and last z values, not {} and {}",
+                grid.width,
+                grid.height,
+                grid.escape_times.len(),
+                grid.last_z_values.len()
+            ));
+        }
+        if let Some(paths) = &grid.paths {
+            let starts_are_valid = paths.starts.len() == pixel_count + 1
+                && paths.starts.first() == Some(&0)
+                && paths.starts.last() == Some(&paths.z_values.len())
+                && paths.starts.is_sorted();
+            if !starts_are_valid {
+                return Err(String::from(
+                    "the path starts don't divide the path z values between the pixels",
+                ));
+            }
+        }
+
+        if let Some(statistics) = &grid.statistics
+            && !statistics.has_length(pixel_count)
+        {
+            return Err(format!(
+                "a {}x{} grid needs {pixel_count} of each orbit statistic",
+                grid.width, grid.height
+            ));
+        }
+
+        Ok(Self {
+            width: grid.width,
+            height: grid.height,
+            iteration_max: grid.iteration_max,
+            escape_times: grid.escape_times,
+            last_z_values: grid.last_z_values,
+            paths: grid.paths,
+            statistics: grid.statistics,
+        })
+    }
+}
@@ -0,0 +1,571 @@
+use crate::{FractalFormula, Mandelbrot, OrbitIterator, Scalar, formula::escape_time};
+use num::Complex;
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+#[cfg(feature = "std")]
+use {
+    crate::{
+        Error, EscapeData, EscapeGrid, formula::escape_path, grid::GridRow,
+        statistics::StatisticsAccumulator,
+    },
+    core::sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+/// A description of which part of the complex plane to sample and how.
+/// Start with [`RenderJob::new`], chain the settings that differ from the defaults, then [`run`](Self::run) it.
+/// Orbits of `F` are iterated with `T`. Paths are always stored as `f32`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderJob<T: Scalar, F: FractalFormula<T> = Mandelbrot> {
+    width: usize,
+    height: usize,
+    center: Complex<T>,
+    dimensions: Complex<T>,
+    /// radians counterclockwise around `center`
+    rotation: f64,
+    iteration_max: usize,
+    formula: F,
+    store_paths: bool,
+    orbit_statistics: bool,
+    trap_point: Complex<f32>,
+}
+
+impl<T: Scalar, F: FractalFormula<T> + Default> Default for RenderJob<T, F> {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            center: Complex::new(T::from_f64(0.0), T::from_f64(0.0)),
+            dimensions: Complex::new(T::from_f64(4.0), T::from_f64(3.0)),
+            rotation: 0.0,
+            iteration_max: 500,
+            formula: F::default(),
+            store_paths: true,
+            orbit_statistics: false,
+            trap_point: Complex::new(0.0, 0.0),
+        }
+    }
+}
+
+impl<T: Scalar> RenderJob<T> {
+    /// A job for the Mandelbrot set. Use [`formula`](Self::formula) to render something else
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: Scalar, F: FractalFormula<T>> RenderJob<T, F> {
+    /// The number of pixels across and down
+    pub fn resolution(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// The complex number in the middle of the view
+    pub fn center(mut self, center: Complex<T>) -> Self {
+        self.center = center;
+        self
+    }
+
+    /// The width (real part) and height (imaginary part) of the view on the complex plane
+    pub fn dimensions(mut self, dimensions: Complex<T>) -> Self {
+        self.dimensions = dimensions;
+        self
+    }
+
+    /// How far the view is turned around its center, in radians counterclockwise. Defaults to none
+    pub fn rotation(mut self, rotation: f64) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// How many iterations to try before deciding a point is inside the set
+    pub fn iteration_max(mut self, iteration_max: usize) -> Self {
+        self.iteration_max = iteration_max;
+        self
+    }
+
+    /// The fractal to render
+    pub fn formula<G: FractalFormula<T>>(self, formula: G) -> RenderJob<T, G> {
+        RenderJob {
+            width: self.width,
+            height: self.height,
+            center: self.center,
+            dimensions: self.dimensions,
+            rotation: self.rotation,
+            iteration_max: self.iteration_max,
+            formula,
+            store_paths: self.store_paths,
+            orbit_statistics: self.orbit_statistics,
+            trap_point: self.trap_point,
+        }
+    }
+
+    /// Whether each pixel keeps its whole escape path (the default) or only its last `z`.
+    /// Paths take `iteration_max` times more memory, and the last `z` is all smooth coloring needs.
+    /// Any pixel's path can be recalculated with [`escape_path`](Self::escape_path)
+    pub fn store_paths(mut self, store_paths: bool) -> Self {
+        self.store_paths = store_paths;
+        self
+    }
+
+    /// Whether each pixel keeps [`OrbitStatistics`](crate::OrbitStatistics) of its orbit, off by default.
+    /// They cost a few numbers per pixel, where paths cost `iteration_max` of them
+    pub fn orbit_statistics(mut self, orbit_statistics: bool) -> Self {
+        self.orbit_statistics = orbit_statistics;
+        self
+    }
+
+    /// Where the point orbit trap of the [statistics](Self::orbit_statistics) is. Defaults to the origin
+    pub fn trap_point(mut self, trap_point: Complex<f32>) -> Self {
+        self.trap_point = trap_point;
+        self
+    }
+
+    /// The complex number sampled for a pixel. Row 0 is the bottom of the view
+    pub fn pixel_coordinate(&self, row_index: usize, column_index: usize) -> Complex<T> {
+        self.rotated(self.pixel_offset(row_index, column_index))
+    }
+
+    /// How far a pixel is from the center before the view is rotated
+    fn pixel_offset(&self, row_index: usize, column_index: usize) -> Complex<T> {
+        let half = T::from_f64(0.5);
+        let x_percent = T::from_f64(column_index as f64 / self.width as f64);
+        let y_percent = T::from_f64(row_index as f64 / self.height as f64);
+
+        Complex::new(
+            (x_percent - half.clone()) * self.dimensions.re.clone(),
+            (y_percent - half) * self.dimensions.im.clone(),
+        )
+    }
+
+    /// The point `offset` away from the center once the view is rotated
+    fn rotated(&self, offset: Complex<T>) -> Complex<T> {
+        // unrotated views skip the multiplications, so they sample exactly the points they always have
+        if self.rotation == 0.0 {
+            return Complex::new(
+                self.center.re.clone() + offset.re,
+                self.center.im.clone() + offset.im,
+            );
+        }
+        let cos = T::from_f64(libm::cos(self.rotation));
+        let sin = T::from_f64(libm::sin(self.rotation));
+        Complex::new(
+            self.center.re.clone() + offset.re.clone() * cos.clone()
+                - offset.im.clone() * sin.clone(),
+            self.center.im.clone() + offset.re * sin + offset.im * cos,
+        )
+    }
+
+    /// Lazily iterate a single pixel with this job's formula. Not limited by `iteration_max`
+    pub fn orbit(&self, pixel: Complex<T>) -> OrbitIterator<T, &F> {
+        OrbitIterator::new(&self.formula, pixel)
+    }
+
+    /// Iterate a single pixel with this job's formula and iteration limit, keeping only the escape time and the last `z`
+    pub fn escape_time(&self, pixel: Complex<T>) -> (Option<usize>, Complex<f32>) {
+        escape_time(&self.formula, pixel, self.iteration_max)
+    }
+
+    /// The pixels of the whole image
+    pub fn full_rect(&self) -> PixelRect {
+        PixelRect {
+            row: 0,
+            column: 0,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// The (center, dimensions) of the part of the complex plane covered by `rect`.
+    /// A job with this center, these dimensions, this job's rotation, and `rect`'s resolution samples the same points as the tile
+    pub fn tile_view(&self, rect: PixelRect) -> (Complex<T>, Complex<T>) {
+        let half = T::from_f64(0.5);
+        let corner = self.pixel_offset(rect.row, rect.column);
+        let dimensions = Complex::new(
+            T::from_f64(rect.width as f64 / self.width as f64) * self.dimensions.re.clone(),
+            T::from_f64(rect.height as f64 / self.height as f64) * self.dimensions.im.clone(),
+        );
+        let center = self.rotated(Complex::new(
+            corner.re + half.clone() * dimensions.re.clone(),
+            corner.im + half * dimensions.im.clone(),
+        ));
+        (center, dimensions)
+    }
+}
+
+/// Storing paths and rendering grids need `std`
+#[cfg(feature = "std")]
+impl<T: Scalar, F: FractalFormula<T>> RenderJob<T, F> {
+    /// Iterate a single pixel with this job's formula and iteration limit
+    pub fn escape_path(&self, pixel: Complex<T>) -> EscapeData {
+        escape_path(&self.formula, pixel, self.iteration_max)
+    }
+
+    /// Iterate every pixel, in parallel with the `rayon` feature
+    pub fn run(&self) -> EscapeGrid {
+        self.run_with_progress(|_, _| {}, &AtomicBool::new(false))
+            .expect("the whole image is in bounds and nothing can cancel the job")
+    }
+
+    /// Iterate every pixel one row at a time, in parallel with the `rayon` feature.
+    /// `progress` is called with (rows completed, total rows) after each row, from whichever thread finished it.
+    /// Storing `true` in `cancel` stops the job before its next row with [`Error::Cancelled`]
+    pub fn run_with_progress(
+        &self,
+        progress: impl Fn(usize, usize) + Sync,
+        cancel: &AtomicBool,
+    ) -> Result<EscapeGrid, Error> {
+        self.run_tile_with_progress(self.full_rect(), progress, cancel)
+    }
+
+    /// Iterate the pixels in `rect`, in parallel with the `rayon` feature. The grid is `rect`'s size, and its row 0 is `rect.row` of the image.
+    /// Returns [`Error::TileOutOfBounds`] if `rect` isn't inside the image
+    pub fn run_tile(&self, rect: PixelRect) -> Result<EscapeGrid, Error> {
+        self.run_tile_with_progress(rect, |_, _| {}, &AtomicBool::new(false))
+    }
+
+    /// [`run_tile`](Self::run_tile) with the progress reporting and cancellation of [`run_with_progress`](Self::run_with_progress).
+    /// Progress counts the rows of the tile
+    pub fn run_tile_with_progress(
+        &self,
+        rect: PixelRect,
+        progress: impl Fn(usize, usize) + Sync,
+        cancel: &AtomicBool,
+    ) -> Result<EscapeGrid, Error> {
+        if rect.row + rect.height > self.height || rect.column + rect.width > self.width {
+            return Err(Error::TileOutOfBounds {
+                rect,
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        let rows_completed = AtomicUsize::new(0);
+        let row_indices = rect.row..rect.row + rect.height;
+        #[cfg(feature = "rayon")]
+        let row_indices = row_indices.into_par_iter();
+        let rows = row_indices
+            .map(|row_index| {
+                if cancel.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let mut row = GridRow::default();
+                for column_index in rect.column..rect.column + rect.width {
+                    self.push_pixel(&mut row, row_index, column_index);
+                }
+                progress(
+                    rows_completed.fetch_add(1, Ordering::Relaxed) + 1,
+                    rect.height,
+                );
+                Some(row)
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::Cancelled)?;
+
+        Ok(EscapeGrid::from_rows(
+            rect.width,
+            rect.height,
+            self.iteration_max,
+            rows,
+            self.store_paths,
+            self.orbit_statistics,
+        ))
+    }
+
+    /// Iterate one pixel into `row`
+    fn push_pixel(&self, row: &mut GridRow, row_index: usize, column_index: usize) {
+        let pixel = self.pixel_coordinate(row_index, column_index);
+        if !self.orbit_statistics {
+            if self.store_paths {
+                let (escape_time, path) = self.escape_path(pixel);
+                let last_z = *path.last().expect("every orbit has a z₀");
+                row.push(escape_time, last_z, Some(&path), None);
+            } else {
+                let (escape_time, last_z) = escape_time(&self.formula, pixel, self.iteration_max);
+                row.push(escape_time, last_z, None, None);
+            }
+            return;
+        }
+
+        let mut statistics = StatisticsAccumulator::new(self.trap_point);
+        let mut orbit = OrbitIterator::new(&self.formula, pixel);
+        let z_values = orbit
+            .by_ref()
+            .take(self.iteration_max + 1)
+            .map(|z| Complex::new(z.re.to_f32(), z.im.to_f32()))
+            .inspect(|&z| statistics.add(z));
+        if self.store_paths {
+            let path = z_values.collect::<Vec<_>>();
+            let last_z = *path.last().expect("every orbit has a z₀");
+            row.push(
+                orbit.escape_time(),
+                last_z,
+                Some(&path),
+                Some(statistics.finish()),
+            );
+        } else {
+            let last_z = z_values.last().expect("every orbit has a z₀");
+            row.push(orbit.escape_time(), last_z, None, Some(statistics.finish()));
+        }
+    }
+
+    /// Render the whole image, copying the pixels it shares with `grid` instead of iterating them again.
+    /// `grid` must have been rendered by this job with its center `row_shift` rows and `column_shift` columns away,
+    /// so that this job's pixel `(row, column)` is `grid`'s pixel `(row + row_shift, column + column_shift)`.
+    /// Only the strips the shift exposes are iterated, unless this job stores paths or statistics and `grid` didn't.
+    /// Returns [`Error::GridSizeMismatch`] if `grid` isn't this job's resolution,
+    /// and [`Error::IterationMaxMismatch`] if it was rendered with a different iteration limit
+    pub fn run_shifted(
+        &self,
+        grid: &EscapeGrid,
+        row_shift: isize,
+        column_shift: isize,
+    ) -> Result<EscapeGrid, Error> {
+        self.run_shifted_with_progress(
+            grid,
+            row_shift,
+            column_shift,
+            |_, _| {},
+            &AtomicBool::new(false),
+        )
+    }
+
+    /// [`run_shifted`](Self::run_shifted) with the progress reporting and cancellation of [`run_with_progress`](Self::run_with_progress)
+    pub fn run_shifted_with_progress(
+        &self,
+        grid: &EscapeGrid,
+        row_shift: isize,
+        column_shift: isize,
+        progress: impl Fn(usize, usize) + Sync,
+        cancel: &AtomicBool,
+    ) -> Result<EscapeGrid, Error> {
+        if (grid.width(), grid.height()) != (self.width, self.height) {
+            return Err(Error::GridSizeMismatch {
+                grid: (grid.width(), grid.height()),
+                job: (self.width, self.height),
+            });
+        }
+        if grid.iteration_max() != self.iteration_max {
+            return Err(Error::IterationMaxMismatch {
+                grid: grid.iteration_max(),
+                job: self.iteration_max,
+            });
+        }
+
+        let reusable = (grid.has_paths() || !self.store_paths)
+            && (grid.has_statistics() || !self.orbit_statistics);
+        let shifted = |index: usize, shift: isize, length: usize| {
+            index
+                .checked_add_signed(shift)
+                .filter(|&index| index < length && reusable)
+        };
+        let rows_completed = AtomicUsize::new(0);
+        let row_indices = 0..self.height;
+        #[cfg(feature = "rayon")]
+        let row_indices = row_indices.into_par_iter();
+        let rows = row_indices
+            .map(|row_index| {
+                if cancel.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let mut row = GridRow::default();
+                for column_index in 0..self.width {
+                    let old_row_index = shifted(row_index, row_shift, self.height);
+                    let old_column_index = shifted(column_index, column_shift, self.width);
+                    let (Some(old_row_index), Some(old_column_index)) =
+                        (old_row_index, old_column_index)
+                    else {
+                        self.push_pixel(&mut row, row_index, column_index);
+                        continue;
+                    };
+
+                    let pixel_index = grid.pixel_index(old_row_index, old_column_index);
+                    row.push(
+                        grid.escape_time(pixel_index),
+                        grid.last_z(pixel_index),
+                        grid.path(pixel_index).filter(|_| self.store_paths),
+                        grid.statistics(pixel_index)
+                            .filter(|_| self.orbit_statistics),
+                    );
+                }
+                progress(
+                    rows_completed.fetch_add(1, Ordering::Relaxed) + 1,
+                    self.height,
+                );
+                Some(row)
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::Cancelled)?;
+
+        Ok(EscapeGrid::from_rows(
+            self.width,
+            self.height,
+            self.iteration_max,
+            rows,
+            self.store_paths,
+            self.orbit_statistics,
+        ))
+    }
+
+    /// Raise the iteration limit of a `grid` this job rendered with a lower `iteration_max`,
+    /// iterating only the pixels that hadn't escaped from where they stopped, instead of rendering everything again.
+    /// The paths and statistics are extended if `grid` stored them, with this job's trap point. The last `z` each pixel resumes from is stored as `f32`,
+    /// so the result matches [`run`](Self::run) exactly for `f32` jobs and closely for wider scalars.
+    /// Returns [`Error::GridSizeMismatch`] if `grid` isn't this job's resolution,
+    /// and [`Error::IterationMaxDecreased`] if `grid` was iterated further than this job would be
+    pub fn continue_iterations(&self, grid: &EscapeGrid) -> Result<EscapeGrid, Error> {
+        self.continue_iterations_with_progress(grid, |_, _| {}, &AtomicBool::new(false))
+    }
+
+    /// [`continue_iterations`](Self::continue_iterations) with the progress reporting and cancellation of [`run_with_progress`](Self::run_with_progress)
+    pub fn continue_iterations_with_progress(
+        &self,
+        grid: &EscapeGrid,
+        progress: impl Fn(usize, usize) + Sync,
+        cancel: &AtomicBool,
+    ) -> Result<EscapeGrid, Error> {
+        if (grid.width(), grid.height()) != (self.width, self.height) {
+            return Err(Error::GridSizeMismatch {
+                grid: (grid.width(), grid.height()),
+                job: (self.width, self.height),
+            });
+        }
+        if grid.iteration_max() > self.iteration_max {
+            return Err(Error::IterationMaxDecreased {
+                grid: grid.iteration_max(),
+                job: self.iteration_max,
+            });
+        }
+
+        let new_iterations = self.iteration_max - grid.iteration_max();
+        let rows_completed = AtomicUsize::new(0);
+        let row_indices = 0..self.height;
+        #[cfg(feature = "rayon")]
+        let row_indices = row_indices.into_par_iter();
+        let rows = row_indices
+            .map(|row_index| {
+                if cancel.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let mut row = GridRow::default();
+                for column_index in 0..self.width {
+                    let pixel_index = grid.pixel_index(row_index, column_index);
+                    let last_z = grid.last_z(pixel_index);
+                    let path = grid.path(pixel_index);
+                    let statistics = grid.statistics(pixel_index);
+                    if let Some(escape_time) = grid.escape_time(pixel_index) {
+                        row.push(Some(escape_time), last_z, path, statistics);
+                        continue;
+                    }
+
+                    // an orbit that hasn't escaped has every z up to z number `iteration_max`
+                    let mut statistics = statistics.map(|statistics| {
+                        StatisticsAccumulator::resume(
+                            statistics,
+                            grid.iteration_max(),
+                            self.trap_point,
+                        )
+                    });
+                    let mut orbit = OrbitIterator::resume(
+                        &self.formula,
+                        self.pixel_coordinate(row_index, column_index),
+                        Complex::new(T::from_f64(last_z.re as f64), T::from_f64(last_z.im as f64)),
+                        grid.iteration_max(),
+                    );
+                    let new_z_values = orbit
+                        .by_ref()
+                        .take(new_iterations)
+                        .map(|z| Complex::new(z.re.to_f32(), z.im.to_f32()))
+                        .inspect(|&z| {
+                            if let Some(statistics) = &mut statistics {
+                                statistics.add(z);
+                            }
+                        });
+                    match path {
+                        Some(path) => {
+                            let path = path.iter().copied().chain(new_z_values).collect::<Vec<_>>();
+                            let last_z = *path.last().expect("every orbit has a z₀");
+                            row.push(
+                                orbit.escape_time(),
+                                last_z,
+                                Some(&path),
+                                statistics.map(StatisticsAccumulator::finish),
+                            );
+                        }
+                        None => {
+                            let last_z = new_z_values.last().unwrap_or(last_z);
+                            row.push(
+                                orbit.escape_time(),
+                                last_z,
+                                None,
+                                statistics.map(StatisticsAccumulator::finish),
+                            );
+                        }
+                    }
+                }
+                progress(
+                    rows_completed.fetch_add(1, Ordering::Relaxed) + 1,
+                    self.height,
+                );
+                Some(row)
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::Cancelled)?;
+
+        Ok(EscapeGrid::from_rows(
+            self.width,
+            self.height,
+            self.iteration_max,
+            rows,
+            grid.has_paths(),
+            grid.has_statistics(),
+        ))
+    }
+}
+
+/// A rectangle of pixels in a [`RenderJob`]'s image, for rendering part of it with [`RenderJob::run_tile`].
+/// Rows count up from the bottom of the view, like [`RenderJob::pixel_coordinate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PixelRect {
+    pub row: usize,
+    pub column: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl PixelRect {
+    /// Split `width` by `height` pixels into tiles of at most `tile_size` by `tile_size`, in row major order
+    pub fn tiles(width: usize, height: usize, tile_size: usize) -> impl Iterator<Item = Self> {
+        let tile_size = tile_size.max(1);
+        (0..height).step_by(tile_size).flat_map(move |row| {
+            (0..width).step_by(tile_size).map(move |column| Self {
+                row,
+                column,
+                width: tile_size.min(width - column),
+                height: tile_size.min(height - row),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenderJob;
+    use num::Complex;
+
+    #[test]
+    fn rotation_turns_the_view_around_its_center() {
+        let job = RenderJob::<f64>::new()
+            .resolution(4, 4)
+            .center(Complex::new(-0.5, 0.0))
+            .dimensions(Complex::new(4.0, 4.0))
+            .rotation(core::f64::consts::FRAC_PI_2);
+        // a quarter turn counterclockwise moves the middle of the left edge to the bottom
+        let left_middle = job.pixel_coordinate(2, 0);
+        assert!((left_middle - Complex::new(-0.5, -2.0)).norm() < 1e-12);
+        // the center stays put
+        assert_eq!(job.pixel_coordinate(2, 2), Complex::new(-0.5, 0.0));
+    }
+}
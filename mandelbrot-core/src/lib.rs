@@ -0,0 +1,103 @@
+//! The math behind the Mandelbrot escape path demo, without any rendering.
+//!
+//! A [`RenderJob`] describes which part of the complex plane to sample and how,
+//! and [`RenderJob::run`] iterates every pixel into an [`EscapeGrid`], in parallel when the default `rayon` feature is on.
+//! [`RenderJob::run_tile`] does the same for any [`PixelRect`] of the image, for tiled, partial, or distributed rendering.
+//! Single orbits can be followed lazily with an [`OrbitIterator`].
+//! The iteration is generic over the [`FractalFormula`] being rendered, and over the [`Scalar`] it is done with,
+//! so deeper zooms can trade speed for precision by switching from `f32` to `f64`, [`DoubleDouble`], or an arbitrary precision type,
+//! and targets without fast floats can iterate with the integer only [`Fixed`].
+//!
+//! ```
+//! use mandelbrot_core::{Julia, RenderJob};
+//! use num::Complex;
+//!
+//! let grid = RenderJob::<f64>::new()
+//!     .resolution(80, 60)
+//!     .center(Complex::new(-0.4, 0.0))
+//!     .dimensions(Complex::new(4.0, 3.0))
+//!     .iteration_max(100)
+//!     .run();
+//!
+//! // the middle of the view is inside the set
+//! let middle = grid.pixel_index(30, 40);
+//! assert_eq!(grid.escape_time(middle), None);
+//! assert!(grid.path(middle).is_some());
+//!
+//! // any `FractalFormula` can be rendered
+//! let julia = RenderJob::<f64>::new()
+//!     .formula(Julia { c: Complex::new(-0.8, 0.156) })
+//!     .run();
+//! assert_eq!(julia.width(), 800);
+//! ```
+//!
+//! Without the default `std` feature the crate is `no_std`: formulas, scalars, [`OrbitIterator`],
+//! and single pixels with [`RenderJob::escape_time`] still work, using `libm` for float math,
+//! but storing paths and rendering whole grids are left out.
+//! Without the default `rayon` feature grids are rendered one row after another on the calling thread,
+//! for single threaded and wasm builds.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod error;
+mod formula;
+#[cfg(feature = "std")]
+mod grid;
+mod job;
+mod orbit;
+mod scalar;
+#[cfg(feature = "std")]
+mod statistics;
+
+pub use error::Error;
+pub use formula::{BurningShip, FractalFormula, Julia, Mandelbrot, Tricorn, norm_sqr, square};
+#[cfg(feature = "std")]
+pub use grid::{EscapeData, EscapeGrid, NEVER_ESCAPED};
+pub use job::{PixelRect, RenderJob};
+pub use orbit::OrbitIterator;
+pub use scalar::{DoubleDouble, Fixed, Scalar};
+#[cfg(feature = "std")]
+pub use statistics::OrbitStatistics;
+
+#[cfg(feature = "std")]
+use num::Complex;
+
+/// Iterate `zₙ₊₁ = zₙ² + c` starting at `z₀ = 0`.
+/// Returns the escape time (if `|z| > 2` within `iteration_max` steps) and every `z` visited,
+/// the same way [`RenderJob::escape_path`] does. This is [`compute_orbit`] with the path rounded to `f32`
+#[cfg(feature = "std")]
+pub fn calculate_escape_path<T: Scalar>(c: Complex<T>, iteration_max: usize) -> EscapeData {
+    let (escape_time, z_values) = compute_orbit(c, iteration_max);
+    let path = z_values
+        .into_iter()
+        .map(|z| Complex::new(z.re.to_f32(), z.im.to_f32()))
+        .collect();
+    (escape_time, path)
+}
+
+/// Iterate `zₙ₊₁ = zₙ² + c` starting at `z₀ = 0` for a single point, without rendering or storing anything else.
+/// Returns the escape time (if `|z| > 2` within `iteration_max` steps) and every `z` visited, kept in `T`'s full precision.
+/// Use [`RenderJob::orbit`] for other formulas, or to stop early
+///
+/// ```
+/// use mandelbrot_core::compute_orbit;
+/// use num::Complex;
+///
+/// let (escape_time, z_values) = compute_orbit(Complex::new(1.0_f64, 0.0), 100);
+/// assert_eq!(escape_time, Some(3));
+/// assert_eq!(z_values, [0.0, 1.0, 2.0, 5.0].map(|re| Complex::new(re, 0.0)));
+///
+/// // points inside the set run until the limit
+/// let (escape_time, z_values) = compute_orbit(Complex::new(-1.0_f64, 0.0), 100);
+/// assert_eq!(escape_time, None);
+/// assert_eq!(z_values.len(), 101);
+/// ```
+#[cfg(feature = "std")]
+pub fn compute_orbit<T: Scalar>(
+    c: Complex<T>,
+    iteration_max: usize,
+) -> (Option<usize>, Vec<Complex<T>>) {
+    let mut orbit = OrbitIterator::new(Mandelbrot, c);
+    let z_values = orbit.by_ref().take(iteration_max + 1).collect();
+    (orbit.escape_time(), z_values)
+}
@@ -0,0 +1,80 @@
+use crate::{FractalFormula, Scalar};
+use num::Complex;
+
+/// Lazily iterates a formula for one pixel, yielding `z₀, z₁, z₂, …` in full precision.
+/// Ends after the first `z` that has escaped, so orbits inside the set never end on their own.
+/// Use [`take`](Iterator::take) to limit them
+///
+/// ```
+/// use mandelbrot_core::{Mandelbrot, OrbitIterator};
+/// use num::Complex;
+///
+/// let mut orbit = OrbitIterator::new(Mandelbrot, Complex::new(1.0, 0.0));
+/// let z_values = orbit.by_ref().take(100).collect::<Vec<Complex<f64>>>();
+/// assert_eq!(z_values, [0.0, 1.0, 2.0, 5.0].map(|re| Complex::new(re, 0.0)));
+/// assert_eq!(orbit.escape_time(), Some(3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrbitIterator<T: Scalar, F: FractalFormula<T>> {
+    formula: F,
+    pixel: Complex<T>,
+    /// the last z yielded. `None` before the first call to `next`
+    z: Option<Complex<T>>,
+    /// the index of the next z
+    iteration: usize,
+    escape_time: Option<usize>,
+}
+
+impl<T: Scalar, F: FractalFormula<T>> OrbitIterator<T, F> {
+    pub fn new(formula: F, pixel: Complex<T>) -> Self {
+        Self {
+            formula,
+            pixel,
+            z: None,
+            iteration: 0,
+            escape_time: None,
+        }
+    }
+
+    /// Pick up an orbit that stopped at `z`, the `iteration`th `z` of the orbit, which hadn't escaped.
+    /// The first `z` yielded is the one after it
+    pub fn resume(formula: F, pixel: Complex<T>, z: Complex<T>, iteration: usize) -> Self {
+        Self {
+            formula,
+            pixel,
+            z: Some(z),
+            iteration: iteration + 1,
+            escape_time: None,
+        }
+    }
+
+    /// The index of the first `z` that escaped, once it has been yielded
+    pub fn escape_time(&self) -> Option<usize> {
+        self.escape_time
+    }
+}
+
+impl<T: Scalar, F: FractalFormula<T>> Iterator for OrbitIterator<T, F> {
+    type Item = Complex<T>;
+
+    fn next(&mut self) -> Option<Complex<T>> {
+        if self.escape_time.is_some() {
+            return None;
+        }
+
+        let z = match self.z.take() {
+            None => self.formula.init(&self.pixel),
+            Some(z) => {
+                let z = self.formula.step(z, &self.pixel);
+                if self.formula.has_escaped(&z) {
+                    self.escape_time = Some(self.iteration);
+                }
+                z
+            }
+        };
+        self.iteration += 1;
+        self.z = Some(z.clone());
+
+        Some(z)
+    }
+}
@@ -0,0 +1,114 @@
+use num::Complex;
+
+/// Summaries of one pixel's orbit, gathered while iterating so colorings that need them don't have to store whole paths.
+/// `z₀` is left out, because most formulas start every orbit at the same `z₀`, unless it's the only `z` of the orbit
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrbitStatistics {
+    /// the smallest `|z|`
+    pub min_norm: f32,
+    /// the average `|z|`
+    pub mean_norm: f32,
+    /// the argument of the last `z`, in `-π..=π`
+    pub last_argument: f32,
+    /// the smallest distance from `z` to either axis, for a cross shaped orbit trap
+    pub cross_trap_distance: f32,
+    /// the smallest distance from `z` to the job's [trap point](crate::RenderJob::trap_point)
+    pub point_trap_distance: f32,
+}
+
+impl OrbitStatistics {
+    /// The statistics of an orbit's `z` values, such as a stored path, with the point trap at `trap_point`
+    pub fn from_z_values(
+        z_values: impl IntoIterator<Item = Complex<f32>>,
+        trap_point: Complex<f32>,
+    ) -> Self {
+        let mut accumulator = StatisticsAccumulator::new(trap_point);
+        for z in z_values {
+            accumulator.add(z);
+        }
+        accumulator.finish()
+    }
+}
+
+/// Gathers `OrbitStatistics` one `z` at a time
+#[derive(Debug, Clone)]
+pub(crate) struct StatisticsAccumulator {
+    trap_point: Complex<f32>,
+    /// `None` until the first `z`, which is only counted if no other `z` follows
+    z_0: Option<Complex<f32>>,
+    /// how many `z` after `z₀` have been counted
+    count: usize,
+    norm_sum: f32,
+    min_norm: f32,
+    last_argument: f32,
+    cross_trap_distance: f32,
+    point_trap_distance: f32,
+}
+
+impl StatisticsAccumulator {
+    pub(crate) fn new(trap_point: Complex<f32>) -> Self {
+        Self {
+            trap_point,
+            z_0: None,
+            count: 0,
+            norm_sum: 0.0,
+            min_norm: f32::INFINITY,
+            last_argument: 0.0,
+            cross_trap_distance: f32::INFINITY,
+            point_trap_distance: f32::INFINITY,
+        }
+    }
+
+    /// Carry on from the `statistics` of the first `count` values after `z₀` of an orbit
+    pub(crate) fn resume(
+        statistics: OrbitStatistics,
+        count: usize,
+        trap_point: Complex<f32>,
+    ) -> Self {
+        Self {
+            trap_point,
+            z_0: Some(Complex::new(0.0, 0.0)),
+            count,
+            norm_sum: statistics.mean_norm * count as f32,
+            min_norm: statistics.min_norm,
+            last_argument: statistics.last_argument,
+            cross_trap_distance: statistics.cross_trap_distance,
+            point_trap_distance: statistics.point_trap_distance,
+        }
+    }
+
+    pub(crate) fn add(&mut self, z: Complex<f32>) {
+        if self.z_0.is_none() {
+            self.z_0 = Some(z);
+        } else {
+            self.count_z(z);
+        }
+    }
+
+    fn count_z(&mut self, z: Complex<f32>) {
+        let norm = z.norm();
+        self.count += 1;
+        self.norm_sum += norm;
+        self.min_norm = self.min_norm.min(norm);
+        self.last_argument = z.arg();
+        self.cross_trap_distance = self.cross_trap_distance.min(z.re.abs().min(z.im.abs()));
+        self.point_trap_distance = self.point_trap_distance.min((z - self.trap_point).norm());
+    }
+
+    pub(crate) fn finish(mut self) -> OrbitStatistics {
+        if self.count == 0
+            && let Some(z_0) = self.z_0
+        {
+            self.count_z(z_0);
+        }
+
+        OrbitStatistics {
+            min_norm: self.min_norm,
+            mean_norm: self.norm_sum / self.count.max(1) as f32,
+            last_argument: self.last_argument,
+            cross_trap_distance: self.cross_trap_distance,
+            point_trap_distance: self.point_trap_distance,
+        }
+    }
+}
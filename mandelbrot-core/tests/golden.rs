@@ -0,0 +1,168 @@
+//! Known good renders of reference locations, compared by checksum.
+//! Any change to the iteration, the precision, or the formulas that changes a single stored value fails here.
+//! If the change is intended, check the new renders look right and replace the checksums with the ones the failure lists.
+
+use mandelbrot_core::{
+    BurningShip, DoubleDouble, EscapeGrid, Fixed, Julia, RenderJob, Scalar, Tricorn,
+};
+use num::Complex;
+
+/// A render and the checksum it had when it was known to be right
+struct Reference {
+    name: &'static str,
+    render: fn() -> EscapeGrid,
+    checksum: u64,
+}
+
+fn complex<T: Scalar>(re: f64, im: f64) -> Complex<T> {
+    Complex::new(T::from_f64(re), T::from_f64(im))
+}
+
+/// The whole Mandelbrot set at a small size, iterated with `T`
+fn whole_set<T: Scalar>() -> EscapeGrid {
+    RenderJob::<T>::new()
+        .resolution(64, 48)
+        .center(complex(-0.5, 0.0))
+        .dimensions(complex(3.0, 2.25))
+        .iteration_max(200)
+        .run()
+}
+
+const REFERENCES: &[Reference] = &[
+    Reference {
+        name: "whole set, f32, with paths",
+        render: whole_set::<f32>,
+        checksum: 0x8f36_d7c8_f2db_3fbf,
+    },
+    Reference {
+        name: "whole set, fixed point, with paths",
+        render: whole_set::<Fixed>,
+        checksum: 0xfd33_9543_94f5_fd5a,
+    },
+    Reference {
+        name: "seahorse valley, f64, escape times only",
+        render: || {
+            RenderJob::<f64>::new()
+                .resolution(80, 60)
+                .center(complex(-0.745, 0.113))
+                .dimensions(complex(0.01, 0.0075))
+                .iteration_max(1000)
+                .store_paths(false)
+                .run()
+        },
+        checksum: 0x0445_3eaf_ddd7_1b07,
+    },
+    Reference {
+        name: "deep zoom, double double, escape times only",
+        render: || {
+            RenderJob::<DoubleDouble>::new()
+                .resolution(48, 36)
+                .center(complex(-0.743_643_887_037_151, 0.131_825_904_205_330))
+                .dimensions(complex(4e-12, 3e-12))
+                .iteration_max(3000)
+                .store_paths(false)
+                .run()
+        },
+        checksum: 0xb180_4ff0_8e82_f925,
+    },
+    Reference {
+        name: "julia, f32, orbit statistics",
+        render: || {
+            RenderJob::<f32>::new()
+                .formula(Julia {
+                    c: complex(-0.8, 0.156),
+                })
+                .resolution(64, 48)
+                .dimensions(complex(3.2, 2.4))
+                .iteration_max(300)
+                .store_paths(false)
+                .orbit_statistics(true)
+                .trap_point(complex(0.25, 0.0))
+                .run()
+        },
+        checksum: 0xfa4f_d568_75aa_9748,
+    },
+    Reference {
+        name: "burning ship, f64, escape times only",
+        render: || {
+            RenderJob::<f64>::new()
+                .formula(BurningShip)
+                .resolution(64, 48)
+                .center(complex(-1.762, -0.028))
+                .dimensions(complex(0.1, 0.075))
+                .iteration_max(500)
+                .store_paths(false)
+                .run()
+        },
+        checksum: 0xc8e3_35fb_ff6f_7c06,
+    },
+    Reference {
+        name: "tricorn, f32, with paths",
+        render: || {
+            RenderJob::<f32>::new()
+                .formula(Tricorn)
+                .resolution(48, 36)
+                .center(complex(-0.3, 0.0))
+                .dimensions(complex(4.0, 3.0))
+                .iteration_max(100)
+                .run()
+        },
+        checksum: 0x77df_a26a_9a64_23ce,
+    },
+];
+
+#[test]
+fn references_match() {
+    let mismatches = REFERENCES
+        .iter()
+        .filter_map(|reference| {
+            let checksum = (reference.render)().checksum();
+            (checksum != reference.checksum).then(|| {
+                format!(
+                    "{}: expected {:#018x}, got {checksum:#018x}",
+                    reference.name, reference.checksum
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    assert!(
+        mismatches.is_empty(),
+        "renders changed:\n{}",
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn rendering_is_deterministic() {
+    for reference in REFERENCES {
+        assert_eq!(
+            (reference.render)(),
+            (reference.render)(),
+            "{} rendered differently twice",
+            reference.name
+        );
+    }
+}
+
+#[test]
+fn tiles_match_the_whole_image() {
+    let job = RenderJob::<f32>::new()
+        .resolution(64, 48)
+        .center(complex(-0.5, 0.0))
+        .dimensions(complex(3.0, 2.25))
+        .iteration_max(200);
+    let whole = job.run();
+    for rect in mandelbrot_core::PixelRect::tiles(64, 48, 20) {
+        let tile = job.run_tile(rect).unwrap();
+        for row_index in 0..rect.height {
+            for column_index in 0..rect.width {
+                let tile_index = tile.pixel_index(row_index, column_index);
+                let whole_index =
+                    whole.pixel_index(rect.row + row_index, rect.column + column_index);
+                assert_eq!(tile.escape_time(tile_index), whole.escape_time(whole_index));
+                assert_eq!(tile.path(tile_index), whole.path(whole_index));
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Wall clock timing for animations, so they run at the same speed on any refresh rate.
+//! Animations advance by the time since the last frame instead of counting frames.
+
+use macroquad::prelude::*;
+
+/// Longer frames (a render, a dragged window) count as this long, so animations slow down instead of jumping
+const MAX_FRAME_TIME: f32 = 0.1;
+
+/// Seconds since the last frame
+pub fn frame_delta() -> f32 {
+    get_frame_time().min(MAX_FRAME_TIME)
+}
+
+/// Seconds an animation has been running. Advanced once per frame
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AnimationClock {
+    elapsed: f32,
+}
+
+impl AnimationClock {
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    /// Call once per frame while the animation is running
+    pub fn advance(&mut self) {
+        self.elapsed += frame_delta();
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+}
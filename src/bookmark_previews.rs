@@ -0,0 +1,184 @@
+//! Previews of bookmarks, rendered one at a time on another thread while nothing else is being calculated.
+//! Each render is colored and averaged down into a small thumbnail, so every thumbnail pixel is a supersample of many.
+//! The full render of the bookmark picked in the list is kept too, so going to it shows it straight away.
+//! The browser has no threads to render on, so there are no previews there.
+
+use crate::{
+    coloring::create_mandelbrot_image,
+    view::{ViewJob, ViewParams},
+};
+use macroquad::prelude::*;
+use mandelbrot_core::EscapeGrid;
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, TryRecvError},
+    },
+};
+
+/// Pixels across a thumbnail
+const THUMBNAIL_WIDTH: usize = 120;
+
+const PREVIEWS_SUPPORTED: bool = cfg!(not(target_arch = "wasm32"));
+
+/// A bookmark being rendered on another thread
+struct PreviewWorker {
+    name: String,
+    view: ViewParams,
+    job: ViewJob,
+    cancel: Arc<AtomicBool>,
+    result: Receiver<Result<EscapeGrid, mandelbrot_core::Error>>,
+}
+
+impl PreviewWorker {
+    fn spawn(name: &str, view: &ViewParams, job: ViewJob) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, result) = std::sync::mpsc::channel();
+        {
+            let (job, cancel) = (job.clone(), Arc::clone(&cancel));
+            // sending fails once the worker is dropped, and then nobody wants the render
+            std::thread::spawn(move || sender.send(job.run_with_progress(|_, _| {}, &cancel)));
+        }
+        Self {
+            name: name.to_owned(),
+            view: view.clone(),
+            job,
+            cancel,
+            result,
+        }
+    }
+}
+
+impl Drop for PreviewWorker {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The thumbnails rendered so far, and the bookmark being rendered
+#[derive(Default)]
+pub struct BookmarkPreviews {
+    /// render previews while the app is idle
+    pub enabled: bool,
+    /// the view each thumbnail was rendered from, and the thumbnail. `None` if rendering it failed
+    thumbnails: HashMap<String, (ViewParams, Option<Texture2D>)>,
+    /// the full render of the selected bookmark
+    rendered: Option<(ViewJob, EscapeGrid)>,
+    worker: Option<PreviewWorker>,
+}
+
+impl BookmarkPreviews {
+    /// Render the next preview while `idle`, and stop rendering when it isn't. Call every frame.
+    /// `bookmarks` are (name, view) in the order they're rendered, after `selected`,
+    /// and `bookmark_job` is how the view would render each of them
+    pub fn update<'a>(
+        &mut self,
+        idle: bool,
+        bookmarks: impl Iterator<Item = (&'a str, &'a ViewParams)> + Clone,
+        selected: Option<&str>,
+        bookmark_job: impl Fn(&ViewParams) -> ViewJob,
+    ) {
+        if !(self.enabled && idle && PREVIEWS_SUPPORTED) {
+            // dropping the worker cancels it
+            self.worker = None;
+            return;
+        }
+
+        if let Some(worker) = &self.worker {
+            let result = match worker.result.try_recv() {
+                Ok(result) => result.ok(),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => None,
+            };
+            let worker = self.worker.take().expect("checked above");
+            let thumbnail = result
+                .as_ref()
+                .and_then(|data| thumbnail(data, &worker.view));
+            self.thumbnails
+                .insert(worker.name.clone(), (worker.view.clone(), thumbnail));
+            if let Some(data) = result
+                && selected == Some(worker.name.as_str())
+            {
+                self.rendered = Some((worker.job.clone(), data));
+            }
+        }
+
+        let tried = |name: &str, view: &ViewParams| {
+            self.thumbnails
+                .get(name)
+                .is_some_and(|(thumbnail_view, _)| thumbnail_view == view)
+        };
+        let selected_bookmark = bookmarks
+            .clone()
+            .find(|&(name, _)| Some(name) == selected)
+            .filter(|&(name, view)| {
+                let rendered_job = self.rendered.as_ref().map(|(job, _)| job);
+                let failed =
+                    self.thumbnails
+                        .get(name)
+                        .is_some_and(|(thumbnail_view, thumbnail)| {
+                            thumbnail_view == view && thumbnail.is_none()
+                        });
+                rendered_job != Some(&bookmark_job(view)) && !failed
+            });
+        let next = selected_bookmark
+            .or_else(|| bookmarks.clone().find(|&(name, view)| !tried(name, view)));
+        if let Some((name, view)) = next {
+            self.worker = Some(PreviewWorker::spawn(name, view, bookmark_job(view)));
+        }
+    }
+
+    /// The thumbnail of the bookmark `name`, if it's been rendered since the bookmark was last changed
+    pub fn thumbnail(&self, name: &str, view: &ViewParams) -> Option<&Texture2D> {
+        match self.thumbnails.get(name)? {
+            (thumbnail_view, Some(thumbnail)) if thumbnail_view == view => Some(thumbnail),
+            _ => None,
+        }
+    }
+
+    /// The escape data of `job`, if the selected bookmark was rendered with it
+    pub fn rendered(&self, job: &ViewJob) -> Option<EscapeGrid> {
+        self.rendered
+            .as_ref()
+            .filter(|(rendered_job, _)| rendered_job == job)
+            .map(|(_, data)| data.clone())
+    }
+}
+
+/// `data` colored like `view`, averaged down to `THUMBNAIL_WIDTH` pixels across
+fn thumbnail(data: &EscapeGrid, view: &ViewParams) -> Option<Texture2D> {
+    let image = create_mandelbrot_image(data, data.iteration_max(), &view.coloring).ok()?;
+    let (source_width, source_height) = (image.width as usize, image.height as usize);
+    let width = THUMBNAIL_WIDTH.min(source_width).max(1);
+    let height = (source_height * width / source_width.max(1)).max(1);
+
+    // the source pixels a thumbnail pixel covers
+    let span = |index: usize, size: usize, source_size: usize| {
+        let start = index * source_size / size;
+        start..((index + 1) * source_size / size).max(start + 1)
+    };
+    let mut bytes = Vec::with_capacity(width * height * 4);
+    for row_index in 0..height {
+        for column_index in 0..width {
+            let mut sum = [0_u32; 4];
+            let mut count = 0;
+            for source_row in span(row_index, height, source_height) {
+                for source_column in span(column_index, width, source_width) {
+                    let start = (source_row * source_width + source_column) * 4;
+                    for (channel, &value) in image.bytes[start..start + 4].iter().enumerate() {
+                        sum[channel] += value as u32;
+                    }
+                    count += 1;
+                }
+            }
+            bytes.extend(sum.map(|channel| (channel / count) as u8));
+        }
+    }
+    Some(Texture2D::from_image(&Image {
+        bytes,
+        width: width as u16,
+        height: height as u16,
+    }))
+}
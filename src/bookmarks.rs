@@ -0,0 +1,253 @@
+//! Named locations the user wants to come back to.
+//! Bookmarks are kept in a JSON file next to the app, so they're still there in the next session.
+//! A single view can also be saved to and loaded from a file of its own, to share it with other people or machines.
+//! Loading a Fractint `.par` file adds each of its entries as a bookmark, see `par`.
+//! The location can also be copied and pasted as text, with the buttons or ctrl+c and ctrl+v, see `clipboard`.
+//! While the app is idle, bookmarks can be rendered in the background to show a preview of each, see `bookmark_previews`.
+
+use crate::{
+    bookmark_previews::BookmarkPreviews,
+    clipboard::{copy_location, paste_location},
+    navigation::KeyboardCommand,
+    par::parse_par,
+    view::{ViewJob, ViewParams},
+};
+use macroquad::{
+    prelude::*,
+    ui::{hash, root_ui, widgets::Window},
+};
+use mandelbrot_core::EscapeGrid;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const BOOKMARKS_PATH: &str = "mandelbrot_bookmarks.json";
+const DEFAULT_VIEW_PATH: &str = "view.json";
+
+/// there is no file system to save to in the browser, so bookmarks only last until the tab is closed
+const BOOKMARKS_SAVED: bool = cfg!(not(target_arch = "wasm32"));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Bookmark {
+    name: String,
+    view: ViewParams,
+}
+
+/// What the bookmarks window shows between frames
+pub struct BookmarksState {
+    bookmarks: Vec<Bookmark>,
+    /// the name the next bookmark is saved under
+    name: String,
+    /// index into `bookmarks` of the one picked in the list
+    selected: usize,
+    /// the file `Save View` and `Load View` use
+    view_path: String,
+    /// the result of the last load or save
+    status: String,
+    previews: BookmarkPreviews,
+}
+
+impl Default for BookmarksState {
+    fn default() -> Self {
+        let (bookmarks, status) = match load_bookmarks() {
+            Ok(bookmarks) => (bookmarks, String::new()),
+            Err(error) => (Vec::new(), format!("Failed to load bookmarks: {error}")),
+        };
+        Self {
+            bookmarks,
+            name: String::new(),
+            selected: 0,
+            view_path: String::from(DEFAULT_VIEW_PATH),
+            status,
+            previews: BookmarkPreviews::default(),
+        }
+    }
+}
+
+impl BookmarksState {
+    /// Render the next bookmark preview while `idle`. Call every frame.
+    /// `bookmark_job` is how the view would render a bookmark
+    pub fn update_previews(&mut self, idle: bool, bookmark_job: impl Fn(&ViewParams) -> ViewJob) {
+        let bookmarks = self
+            .bookmarks
+            .iter()
+            .map(|bookmark| (bookmark.name.as_str(), &bookmark.view));
+        let selected = self
+            .bookmarks
+            .get(self.selected)
+            .map(|bookmark| bookmark.name.as_str());
+        self.previews
+            .update(idle, bookmarks, selected, bookmark_job);
+    }
+
+    /// The escape data of `job`, if it's the selected bookmark's and it was rendered in the background
+    pub fn prerendered(&self, job: &ViewJob) -> Option<EscapeGrid> {
+        self.previews.rendered(job)
+    }
+}
+
+/// The bookmarks saved by earlier sessions, or none if nothing has been saved yet
+fn load_bookmarks() -> Result<Vec<Bookmark>, String> {
+    if !BOOKMARKS_SAVED {
+        return Ok(Vec::new());
+    }
+    match std::fs::read_to_string(BOOKMARKS_PATH) {
+        Ok(json) => serde_json::from_str(&json).map_err(|error| error.to_string()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+fn save_bookmarks(bookmarks: &[Bookmark]) -> Result<(), String> {
+    if !BOOKMARKS_SAVED {
+        return Ok(());
+    }
+    let json = serde_json::to_string_pretty(bookmarks).map_err(|error| error.to_string())?;
+    std::fs::write(BOOKMARKS_PATH, json).map_err(|error| error.to_string())
+}
+
+/// Add `bookmark`, replacing the one with the same name if there is one
+fn add_bookmark(bookmarks: &mut Vec<Bookmark>, bookmark: Bookmark) {
+    match bookmarks
+        .iter()
+        .position(|existing| existing.name == bookmark.name)
+    {
+        Some(index) => bookmarks[index] = bookmark,
+        None => bookmarks.push(bookmark),
+    }
+}
+
+/// Add the entries of the `.par` file at `path` as bookmarks, and describe what was imported
+fn import_par(
+    bookmarks: &mut Vec<Bookmark>,
+    path: &str,
+    view_aspect: f32,
+    view: &ViewParams,
+) -> Result<String, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|error| format!("Failed to read {path:?}: {error}"))?;
+    let (imported, skipped) = parse_par(&text, view_aspect, view);
+    for skipped in &skipped {
+        eprintln!("Skipped {} in {path:?}: {}", skipped.name, skipped.reason);
+    }
+    let imported_count = imported.len();
+    for (name, view) in imported {
+        add_bookmark(bookmarks, Bookmark { name, view });
+    }
+    save_bookmarks(bookmarks).map_err(|error| format!("Failed to save bookmarks: {error}"))?;
+    Ok(format!(
+        "Bookmarked {imported_count} entries, skipped {}",
+        skipped.len()
+    ))
+}
+
+/// Save `view` under a name, and list the saved bookmarks.
+/// Returns the view of a bookmark when the user goes to it, or of a view file or pasted location when it's loaded.
+/// `view_aspect` is the width of the view over its height
+pub fn bookmarks_window(
+    state: &mut BookmarksState,
+    view: impl Fn() -> ViewParams,
+    view_aspect: f32,
+    command: Option<KeyboardCommand>,
+) -> Option<ViewParams> {
+    let mut recalled = None;
+    Window::new(hash!(), vec2(260.0, 0.0), vec2(250.0, 350.0))
+        .label("bookmarks")
+        .titlebar(true)
+        .ui(&mut *root_ui(), |ui| {
+            ui.input_text(hash!(), "name", &mut state.name);
+            if ui.button(None, "Bookmark View") {
+                let name = state.name.trim().to_owned();
+                if name.is_empty() {
+                    state.status = String::from("Name the bookmark first");
+                } else {
+                    // saving under an existing name replaces that bookmark
+                    add_bookmark(&mut state.bookmarks, Bookmark { name, view: view() });
+                    state.status = match save_bookmarks(&state.bookmarks) {
+                        Ok(()) => format!("Bookmarked {}", state.name.trim()),
+                        Err(error) => format!("Failed to save bookmarks: {error}"),
+                    };
+                }
+            }
+
+            if state.bookmarks.is_empty() {
+                ui.label(None, "no bookmarks yet");
+            } else {
+                let names = state
+                    .bookmarks
+                    .iter()
+                    .map(|bookmark| bookmark.name.as_str())
+                    .collect::<Vec<_>>();
+                state.selected = state.selected.min(names.len() - 1);
+                ui.combo_box(hash!(), "bookmark", &names, &mut state.selected);
+                let selected = &state.bookmarks[state.selected];
+                match state.previews.thumbnail(&selected.name, &selected.view) {
+                    Some(thumbnail) => {
+                        ui.texture(thumbnail.clone(), thumbnail.width(), thumbnail.height());
+                    }
+                    None if state.previews.enabled => ui.label(None, "rendering preview..."),
+                    None => {}
+                }
+                if ui.button(None, "Go") {
+                    recalled = Some(state.bookmarks[state.selected].view.clone());
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "Delete") {
+                    let removed = state.bookmarks.remove(state.selected);
+                    state.status = match save_bookmarks(&state.bookmarks) {
+                        Ok(()) => format!("Deleted {}", removed.name),
+                        Err(error) => format!("Failed to save bookmarks: {error}"),
+                    };
+                }
+            }
+
+            ui.checkbox(hash!(), "idle previews", &mut state.previews.enabled);
+
+            ui.separator();
+            ui.input_text(hash!(), "view file", &mut state.view_path);
+            if ui.button(None, "Save View") {
+                state.status = match view().save(&state.view_path, view_aspect) {
+                    Ok(()) => format!("Saved {}", state.view_path),
+                    Err(error) => error,
+                };
+            }
+            ui.same_line(0.0);
+            if ui.button(None, "Load View") {
+                let is_par = Path::new(&state.view_path)
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("par"));
+                if is_par {
+                    state.status =
+                        import_par(&mut state.bookmarks, &state.view_path, view_aspect, &view())
+                            .unwrap_or_else(|error| error);
+                } else {
+                    match ViewParams::load(&state.view_path, view_aspect, view()) {
+                        Ok(view) => {
+                            state.status = format!("Loaded {}", state.view_path);
+                            recalled = Some(view);
+                        }
+                        Err(error) => state.status = error,
+                    }
+                }
+            }
+
+            let copy = ui.button(None, "Copy Location");
+            ui.same_line(0.0);
+            let paste = ui.button(None, "Paste Location");
+            if copy || command == Some(KeyboardCommand::CopyLocation) {
+                state.status = copy_location(&view());
+            }
+            if paste || command == Some(KeyboardCommand::PasteLocation) {
+                match paste_location(view()) {
+                    Ok(view) => {
+                        state.status = String::from("Went to the pasted location");
+                        recalled = Some(view);
+                    }
+                    Err(error) => state.status = error,
+                }
+            }
+
+            ui.label(None, &state.status);
+        });
+
+    recalled
+}
@@ -0,0 +1,68 @@
+//! Copy the location of the view to the clipboard as a line of text, and go to a location pasted from it,
+//! so locations can be shared in chat without a file. The text is `key=value` pairs, e.g.
+//! `re=-0.7436439 im=0.1318259 zoom=120000 iterations=500 rotation=30`. `iterations` and `rotation` can be left out,
+//! and the formula and coloring are left as they were.
+
+use crate::view::ViewParams;
+use macroquad::miniquad::window::{clipboard_get, clipboard_set};
+use num::Complex;
+
+/// The location of `view` as a line of text
+pub fn location_text(view: &ViewParams) -> String {
+    format!(
+        "re={} im={} zoom={} iterations={} rotation={}",
+        view.center.re, view.center.im, view.scale, view.iteration_max, view.rotation
+    )
+}
+
+/// `view` at the location in `text`, as written by `location_text`
+pub fn parse_location(text: &str, view: ViewParams) -> Result<ViewParams, String> {
+    let value = |key: &str| {
+        text.split(|character: char| character.is_whitespace() || character == ',')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(pair_key, _)| pair_key.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    };
+    let number = |key: &str| -> Result<f32, String> {
+        let value = value(key).ok_or_else(|| format!("The location has no {key}"))?;
+        match value.parse::<f32>() {
+            Ok(number) if number.is_finite() => Ok(number),
+            _ => Err(format!("{key} {value:?} isn't a number")),
+        }
+    };
+
+    let scale = number("zoom")?;
+    if scale <= 0.0 {
+        return Err(String::from("zoom must be positive"));
+    }
+    let iteration_max = match value("iterations") {
+        Some(iterations) => iterations
+            .parse()
+            .map_err(|error| format!("iterations {iterations:?} isn't a number: {error}"))?,
+        None => view.iteration_max,
+    };
+    let rotation = match value("rotation") {
+        Some(_) => number("rotation")?,
+        None => 0.0,
+    };
+    Ok(ViewParams {
+        center: Complex::new(number("re")?, number("im")?),
+        scale,
+        iteration_max,
+        rotation,
+        ..view
+    })
+}
+
+/// Put the location of `view` on the clipboard, and describe what was copied
+pub fn copy_location(view: &ViewParams) -> String {
+    let text = location_text(view);
+    clipboard_set(&text);
+    format!("Copied {text}")
+}
+
+/// `view` at the location on the clipboard
+pub fn paste_location(view: ViewParams) -> Result<ViewParams, String> {
+    let text = clipboard_get().ok_or("The clipboard has no text")?;
+    parse_location(text.trim(), view)
+}
@@ -0,0 +1,117 @@
+use crate::palette::Palette;
+use crate::{
+    colorizer::{COLORIZERS, FrameStatistics, PixelResult},
+    error::{RenderError, image_size},
+    shading::{light_direction, shade},
+};
+use macroquad::prelude::*;
+use mandelbrot_core::EscapeGrid;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+pub fn rgba_to_array(color: Color) -> [u8; 4] {
+    [
+        (color.r * 255.0) as _,
+        (color.g * 255.0) as _,
+        (color.b * 255.0) as _,
+        (color.a * 255.0) as _,
+    ]
+}
+
+/// Options that change how escape data becomes colors. Changing these doesn't require recalculating the data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Coloring {
+    /// name of the registered `Colorizer` to use
+    pub colorizer: String,
+    /// the exponent used by the `power` colorizer
+    pub exponent: f32,
+    /// the colors escape times are mapped to
+    pub palette: Palette,
+    /// how many times the palette repeats across the escape times
+    pub palette_density: f32,
+    /// where in the palette the lowest escape times start. `0.0..1.0`
+    pub palette_offset: f32,
+    /// light the image as if the potential were a 3D surface
+    pub slope_shading: bool,
+    /// degrees around the complex plane the light comes from
+    pub light_azimuth: f32,
+    /// degrees above the complex plane the light comes from
+    pub light_elevation: f32,
+}
+
+impl Default for Coloring {
+    fn default() -> Self {
+        Self {
+            colorizer: String::from("smooth"),
+            exponent: 0.25,
+            palette: Palette::default(),
+            palette_density: 1.0,
+            palette_offset: 0.0,
+            slope_shading: false,
+            light_azimuth: 45.0,
+            light_elevation: 45.0,
+        }
+    }
+}
+
+impl Coloring {
+    /// Maps `0.0..=1.0` to a color of the palette, repeated and shifted by the density and offset
+    pub fn palette_color(&self, normalized: f32) -> Color {
+        self.palette
+            .color(normalized, self.palette_density, self.palette_offset)
+    }
+}
+
+pub fn create_mandelbrot_image(
+    mandelbrot_data: &EscapeGrid,
+    iteration_max: usize,
+    coloring: &Coloring,
+) -> Result<Image, RenderError> {
+    let frame = FrameStatistics::new(mandelbrot_data, iteration_max);
+    color_with_frame(mandelbrot_data, &frame, coloring)
+}
+
+/// Color `mandelbrot_data` with the statistics of a frame it's part of, so a tile of a bigger image colors like the rest of it
+pub fn color_with_frame(
+    mandelbrot_data: &EscapeGrid,
+    frame: &FrameStatistics,
+    coloring: &Coloring,
+) -> Result<Image, RenderError> {
+    let (width, height) = image_size(mandelbrot_data.width(), mandelbrot_data.height())?;
+    let light = coloring
+        .slope_shading
+        .then(|| light_direction(coloring.light_azimuth, coloring.light_elevation));
+    let colorizers = COLORIZERS
+        .read()
+        .expect("a colorizer panicked while being registered");
+    let colorizer = colorizers.get(&coloring.colorizer);
+
+    // start with a blank image
+    let mut image = Image::gen_image_color(width, height, BLACK);
+
+    // update each pixel color in parallel
+    image
+        .get_image_data_mut() // we need the image pixel data to change
+        .par_iter_mut() // we want to edit all pixels at once
+        .enumerate() // the index of each pixel color is the index of it's mandelbrot data
+        .for_each(|(pixel_index, pixel_color)| {
+            let pixel = PixelResult {
+                escape_time: mandelbrot_data.escape_time(pixel_index),
+                last_z: mandelbrot_data.last_z(pixel_index),
+                escape_path: mandelbrot_data.path(pixel_index),
+                statistics: mandelbrot_data.statistics(pixel_index),
+            };
+            let color = colorizer.color(pixel, frame, coloring);
+
+            // only the outside of the set has a potential to light, and lighting needs the whole path
+            let color = match (light, pixel.escape_time, pixel.escape_path) {
+                (Some(light), Some(_), Some(escape_path)) => shade(color, escape_path, light),
+                _ => color,
+            };
+
+            *pixel_color = rgba_to_array(color);
+        });
+
+    Ok(image)
+}
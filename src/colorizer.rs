@@ -0,0 +1,118 @@
+//! Coloring algorithms.
+//! Each algorithm is a `Colorizer` and the app picks one by name from `COLORIZERS`,
+//! so a new algorithm only has to implement the trait and be registered.
+
+mod builtin;
+
+use crate::coloring::Coloring;
+use macroquad::prelude::*;
+use mandelbrot_core::{EscapeGrid, OrbitStatistics};
+use num::Complex;
+use std::sync::{LazyLock, RwLock};
+
+/// The colorizers the app can choose from. Starts with the built in colorizers
+pub static COLORIZERS: LazyLock<RwLock<ColorizerRegistry>> = LazyLock::new(Default::default);
+
+/// The calculation result for one pixel
+#[derive(Debug, Clone, Copy)]
+pub struct PixelResult<'a> {
+    pub escape_time: Option<usize>,
+    /// the last z value visited
+    pub last_z: Complex<f32>,
+    /// every z value visited, starting with `z₀ = 0`. `None` if only escape times were stored
+    pub escape_path: Option<&'a [Complex<f32>]>,
+    /// summaries of the orbit. `None` unless they were gathered instead of storing the path
+    pub statistics: Option<OrbitStatistics>,
+}
+
+/// Information about the whole frame, calculated once before any pixel is colored
+pub struct FrameStatistics {
+    pub iteration_max: usize,
+    /// the cumulative histogram of escape times.
+    /// `cdf[n]` is the fraction of escaped pixels that escaped within `n` iterations
+    pub cdf: Vec<f32>,
+}
+
+impl FrameStatistics {
+    pub fn new(mandelbrot_data: &EscapeGrid, iteration_max: usize) -> Self {
+        let mut histogram = vec![0_usize; iteration_max + 1];
+        for pixel_index in 0..mandelbrot_data.len() {
+            if let Some(escape_time) = mandelbrot_data.escape_time(pixel_index) {
+                histogram[escape_time.min(iteration_max)] += 1;
+            }
+        }
+
+        let escaped_count = histogram.iter().sum::<usize>().max(1) as f32;
+        let cdf = histogram
+            .iter()
+            .scan(0, |running_count, &count| {
+                *running_count += count;
+                Some(*running_count as f32 / escaped_count)
+            })
+            .collect();
+
+        Self { iteration_max, cdf }
+    }
+}
+
+/// The palette color of an iteration, as the `banded` colorizer colors pixels that escape at it.
+/// Orbits are colored with this so each z value matches the pixels that escape after as many iterations
+pub fn iteration_color(iteration: usize, iteration_max: usize, coloring: &Coloring) -> Color {
+    let normalized = (iteration as f32 / iteration_max.max(1) as f32).min(1.0);
+    coloring.palette_color(normalized)
+}
+
+/// Turns the result of one pixel into a color.
+/// Colorizers are shared between the threads that color the image in parallel
+pub trait Colorizer: Send + Sync {
+    /// Shown in the UI and used to select the colorizer
+    fn name(&self) -> &'static str;
+
+    fn color(&self, pixel: PixelResult, frame: &FrameStatistics, coloring: &Coloring) -> Color;
+}
+
+pub struct ColorizerRegistry {
+    colorizers: Vec<Box<dyn Colorizer>>,
+}
+
+impl Default for ColorizerRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            colorizers: Vec::new(),
+        };
+        builtin::register(&mut registry);
+        registry
+    }
+}
+
+impl ColorizerRegistry {
+    /// Add a colorizer, replacing any colorizer with the same name
+    pub fn register(&mut self, colorizer: impl Colorizer + 'static) {
+        self.colorizers
+            .retain(|registered| registered.name() != colorizer.name());
+        self.colorizers.push(Box::new(colorizer));
+    }
+
+    /// The colorizer called `name`, or the first one registered if there isn't one
+    pub fn get(&self, name: &str) -> &dyn Colorizer {
+        self.colorizers
+            .iter()
+            .find(|colorizer| colorizer.name() == name)
+            .or(self.colorizers.first())
+            .expect("the built in colorizers are always registered")
+            .as_ref()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.colorizers
+            .iter()
+            .any(|colorizer| colorizer.name() == name)
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.colorizers
+            .iter()
+            .map(|colorizer| colorizer.name())
+            .collect()
+    }
+}
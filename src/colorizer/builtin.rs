@@ -0,0 +1,112 @@
+use super::{Colorizer, ColorizerRegistry, FrameStatistics, PixelResult};
+use crate::{coloring::Coloring, contour::smooth_iteration};
+use macroquad::prelude::*;
+use mandelbrot_core::OrbitStatistics;
+use num::Complex;
+
+pub fn register(registry: &mut ColorizerRegistry) {
+    registry.register(Mapped {
+        name: "smooth",
+        map: |escape_time, last_z, frame, _| {
+            smooth_iteration(escape_time, last_z) / frame.iteration_max as f32
+        },
+    });
+    // raw integer escape times give distinct bands
+    registry.register(Mapped {
+        name: "banded",
+        map: |escape_time, _, frame, _| escape_time as f32 / frame.iteration_max as f32,
+    });
+    // spread out the low escape times
+    registry.register(Mapped {
+        name: "square root",
+        map: |escape_time, last_z, frame, _| {
+            smooth_iteration(escape_time, last_z).max(0.0).sqrt()
+                / (frame.iteration_max as f32).sqrt()
+        },
+    });
+    // spread out the low escape times even more
+    registry.register(Mapped {
+        name: "logarithmic",
+        map: |escape_time, last_z, frame, _| {
+            smooth_iteration(escape_time, last_z).max(0.0).ln_1p()
+                / (frame.iteration_max as f32).ln_1p()
+        },
+    });
+    // small exponents keep deep zooms with huge iteration limits from crowding into one end of the palette
+    registry.register(Mapped {
+        name: "power",
+        map: |escape_time, last_z, frame, coloring| {
+            (smooth_iteration(escape_time, last_z).max(0.0) / frame.iteration_max as f32)
+                .powf(coloring.exponent)
+        },
+    });
+    // every color is used by roughly the same number of pixels
+    registry.register(Mapped {
+        name: "histogram",
+        map: |escape_time, last_z, frame, _| {
+            equalize(&frame.cdf, smooth_iteration(escape_time, last_z))
+        },
+    });
+    registry.register(OrbitTrap);
+}
+
+/// Colors points in the set black, and escaped points by mapping their escape time to `0.0..=1.0` and looking it up in the palette
+struct Mapped {
+    name: &'static str,
+    /// (escape time, last z, frame, coloring) -> `0.0..=1.0`
+    map: fn(usize, Complex<f32>, &FrameStatistics, &Coloring) -> f32,
+}
+
+impl Colorizer for Mapped {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn color(&self, pixel: PixelResult, frame: &FrameStatistics, coloring: &Coloring) -> Color {
+        match pixel.escape_time {
+            Some(escape_time) => {
+                let last_z = pixel.last_z;
+                coloring.palette_color((self.map)(escape_time, last_z, frame, coloring))
+            }
+            None => BLACK,
+        }
+    }
+}
+
+/// Colors every pixel, inside the set too, by how close its orbit came to either axis (a cross shaped orbit trap)
+struct OrbitTrap;
+
+impl Colorizer for OrbitTrap {
+    fn name(&self) -> &'static str {
+        "orbit trap"
+    }
+
+    fn color(&self, pixel: PixelResult, _frame: &FrameStatistics, coloring: &Coloring) -> Color {
+        let trap_distance = pixel
+            .statistics
+            .or_else(|| {
+                let escape_path = pixel.escape_path?;
+                Some(OrbitStatistics::from_z_values(
+                    escape_path.iter().copied(),
+                    Complex::new(0.0, 0.0),
+                ))
+            })
+            .map(|statistics| statistics.cross_trap_distance)
+            // without the orbit, the last z is the only point to go by
+            .unwrap_or_else(|| pixel.last_z.re.abs().min(pixel.last_z.im.abs()));
+
+        // distances from 1 down to 0.0001 cover the palette
+        let normalized = (-trap_distance.max(f32::MIN_POSITIVE).log10() / 4.0).clamp(0.0, 1.0);
+        coloring.palette_color(normalized)
+    }
+}
+
+/// Look up a smoothed escape time in the cumulative distribution,
+/// interpolating between whole iterations so the smoothing isn't lost
+fn equalize(cdf: &[f32], smoothed_iteration: f32) -> f32 {
+    let cdf_at = |iteration: usize| cdf[iteration.min(cdf.len() - 1)];
+    let smoothed_iteration = smoothed_iteration.max(0.0);
+    let iteration = smoothed_iteration.floor() as usize;
+
+    cdf_at(iteration) + (cdf_at(iteration + 1) - cdf_at(iteration)) * smoothed_iteration.fract()
+}
@@ -0,0 +1,73 @@
+use crate::{
+    coloring::rgba_to_array,
+    error::{RenderError, image_size},
+    serialize_index,
+};
+use macroquad::prelude::*;
+use mandelbrot_core::EscapeGrid;
+use num::Complex;
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
+
+/// The continuous (smoothed) escape time of a point that escaped at `escape_time` with final value `last_z`.
+/// Its level sets are the equipotential lines of the Mandelbrot set,
+/// because it only differs from `log2` of the potential `ln|zₙ| / 2ⁿ` by a sign and a constant.
+pub fn smooth_iteration(escape_time: usize, last_z: Complex<f32>) -> f32 {
+    escape_time as f32 + 1.0 - last_z.norm().log2().log2()
+}
+
+/// Create a transparent image with a line wherever neighboring pixels fall in different bands of `spacing` smoothed iterations
+pub fn create_contour_image(
+    mandelbrot_data: &EscapeGrid,
+    spacing: f32,
+    color: Color,
+) -> Result<Image, RenderError> {
+    let width = mandelbrot_data.width();
+    let height = mandelbrot_data.height();
+    let (image_width, image_height) = image_size(width, height)?;
+
+    // the band each pixel's potential falls in. points inside the set have no potential
+    let levels = (0..mandelbrot_data.len())
+        .into_par_iter()
+        .map(|pixel_index| {
+            mandelbrot_data.escape_time(pixel_index).map(|escape_time| {
+                let last_z = mandelbrot_data.last_z(pixel_index);
+                (smooth_iteration(escape_time, last_z) / spacing).floor() as i64
+            })
+        })
+        .collect::<Vec<_>>();
+    let level_at = |row_index: usize, column_index: usize| {
+        levels
+            .get(serialize_index(row_index, column_index, width))
+            .copied()
+            .flatten()
+    };
+
+    let color = rgba_to_array(color);
+    let mut image = Image::gen_image_color(image_width, image_height, BLANK);
+    image
+        .get_image_data_mut()
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(pixel_index, pixel_color)| {
+            let row_index = pixel_index / width;
+            let column_index = pixel_index % width;
+            let Some(level) = level_at(row_index, column_index) else {
+                return;
+            };
+
+            // only look right and down so each line is one pixel wide
+            let is_on_contour = [(row_index, column_index + 1), (row_index + 1, column_index)]
+                .into_iter()
+                .filter(|&(row_index, column_index)| row_index < height && column_index < width)
+                .any(|(row_index, column_index)| {
+                    level_at(row_index, column_index).is_some_and(|neighbor| neighbor != level)
+                });
+            if is_on_contour {
+                *pixel_color = color;
+            }
+        });
+
+    Ok(image)
+}
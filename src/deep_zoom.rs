@@ -0,0 +1,142 @@
+//! Export a region as a Deep Zoom Image (DZI) tile pyramid, so it can be explored smoothly in a web viewer like OpenSeadragon.
+//! `name.dzi` describes the image, and `name_files/<level>/<column>_<row>.png` are its tiles.
+//! Level 0 is a single pixel, and each level doubles the size of the one before until the last is the full size.
+//! Every level is calculated at its own resolution rather than scaled down, and colored with the statistics of
+//! a preview of the whole image, so the levels match each other and there are no seams between tiles.
+
+use crate::{
+    coloring::color_with_frame,
+    png_metadata::write_png,
+    poster::{PosterJob, preview_statistics},
+    view::{ViewParams, view_job},
+};
+use mandelbrot_core::PixelRect;
+use std::{
+    path::Path,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+/// The side of the square tiles of every level
+const DZI_TILE_SIZE: usize = 256;
+
+/// The index of the full size level. Level `max_level` is `width` by `height`, and level 0 is 1 by 1
+fn max_level(width: usize, height: usize) -> u32 {
+    width
+        .max(height)
+        .max(1)
+        .next_power_of_two()
+        .trailing_zeros()
+}
+
+/// The width and height of `level`, halving the full size, rounded up, for each level below the top
+fn level_size(width: usize, height: usize, level: u32) -> (usize, usize) {
+    let divisor = 1 << (max_level(width, height) - level);
+    (width.div_ceil(divisor), height.div_ceil(divisor))
+}
+
+/// Tiles in every level of the pyramid
+fn tile_count(width: usize, height: usize) -> usize {
+    (0..=max_level(width, height))
+        .map(|level| {
+            let (level_width, level_height) = level_size(width, height, level);
+            level_width.div_ceil(DZI_TILE_SIZE) * level_height.div_ceil(DZI_TILE_SIZE)
+        })
+        .sum()
+}
+
+/// The folder the tiles of `path` go in: `name.dzi` keeps them in `name_files`
+fn tiles_folder(path: &str) -> String {
+    let path = Path::new(path);
+    path.with_file_name(format!(
+        "{}_files",
+        path.file_stem().unwrap_or_default().to_string_lossy()
+    ))
+    .to_string_lossy()
+    .into_owned()
+}
+
+/// The `.dzi` description of a `width` by `height` image
+fn dzi_xml(width: usize, height: usize) -> String {
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<Image xmlns=\"http://schemas.microsoft.com/deepzoom/2008\" Format=\"png\" Overlap=\"0\" TileSize=\"{}\">\n",
+            "  <Size Width=\"{}\" Height=\"{}\"/>\n",
+            "</Image>\n",
+        ),
+        DZI_TILE_SIZE, width, height
+    )
+}
+
+/// Render `view` as a `width` by `height` pyramid described by `path`.
+/// `tiles_done` counts the tiles written so far, and storing `true` in `cancel` stops before the next tile
+pub fn render_deep_zoom(
+    view: &ViewParams,
+    width: usize,
+    height: usize,
+    path: &str,
+    tiles_done: &AtomicUsize,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    if width == 0 || height == 0 {
+        return Err(String::from(
+            "The image must be at least 1 pixel on each side",
+        ));
+    }
+    let frame = preview_statistics(&view_job(view, width, height), view.iteration_max);
+    let folder = tiles_folder(path);
+
+    let mut done = 0;
+    for level in 0..=max_level(width, height) {
+        let (level_width, level_height) = level_size(width, height, level);
+        let level_folder = format!("{folder}/{level}");
+        std::fs::create_dir_all(&level_folder)
+            .map_err(|error| format!("Failed to create {level_folder:?}: {error}"))?;
+        let job = view_job(view, level_width, level_height);
+        for rect in PixelRect::tiles(level_width, level_height, DZI_TILE_SIZE) {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(String::from("Cancelled"));
+            }
+            let tile = job.run_tile(rect).map_err(|error| error.to_string())?;
+            let image = color_with_frame(&tile, &frame, &view.coloring)
+                .map_err(|error| error.to_string())?;
+            let tile_path = format!(
+                "{level_folder}/{}_{}.png",
+                rect.column / DZI_TILE_SIZE,
+                rect.row / DZI_TILE_SIZE
+            );
+            let rgba = png::ColorType::Rgba;
+            write_png(
+                &tile_path,
+                &image.bytes,
+                rect.width,
+                rect.height,
+                rgba,
+                None,
+            )?;
+            done += 1;
+            tiles_done.store(done, Ordering::Relaxed);
+        }
+    }
+
+    // written last, so a viewer never opens a pyramid that's missing tiles
+    std::fs::write(path, dzi_xml(width, height))
+        .map_err(|error| format!("Failed to write {path:?}: {error}"))
+}
+
+/// Start rendering `view` as a `width` by `height` pyramid described by `path` on another thread
+pub fn start_deep_zoom(
+    view: ViewParams,
+    width: usize,
+    height: usize,
+    path: &str,
+) -> Result<PosterJob, String> {
+    let dzi_path = path.to_owned();
+    PosterJob::spawn(
+        tile_count(width, height),
+        path,
+        move |tiles_done, cancel| {
+            render_deep_zoom(&view, width, height, &dzi_path, tiles_done, cancel)
+        },
+    )
+}
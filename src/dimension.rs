@@ -0,0 +1,148 @@
+use crate::serialize_index;
+use macroquad::prelude::*;
+use mandelbrot_core::{EscapeGrid, NEVER_ESCAPED};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+/// The result of box counting the boundary of the set in one render
+pub struct DimensionEstimate {
+    /// (log₂(1 / box size), log₂(number of boxes touching the boundary)) for each box size
+    pub samples: Vec<Vec2>,
+    /// slope of the least squares line through `samples`
+    pub dimension: f32,
+    /// where the least squares line crosses x = 0
+    pub intercept: f32,
+}
+
+/// Count how many boxes of each power of two size touch the boundary of the set,
+/// and fit a line to the log-log plot of box count against box size.
+/// Returns `None` if there isn't enough boundary in view to fit a line
+pub fn estimate_box_counting_dimension(mandelbrot_data: &EscapeGrid) -> Option<DimensionEstimate> {
+    let width = mandelbrot_data.width();
+    let height = mandelbrot_data.height();
+    let is_inside = |row_index: usize, column_index: usize| {
+        mandelbrot_data
+            .escape_times()
+            .get(serialize_index(row_index, column_index, width))
+            .is_some_and(|&escape_time| escape_time == NEVER_ESCAPED)
+    };
+
+    // a pixel is on the boundary if it is inside the set and one of its neighbors isn't
+    let is_boundary = (0..width * height)
+        .into_par_iter()
+        .map(|pixel_index| {
+            let row_index = pixel_index / width;
+            let column_index = pixel_index % width;
+            is_inside(row_index, column_index)
+                && [
+                    (row_index.wrapping_sub(1), column_index),
+                    (row_index + 1, column_index),
+                    (row_index, column_index.wrapping_sub(1)),
+                    (row_index, column_index + 1),
+                ]
+                .into_iter()
+                .filter(|&(row_index, column_index)| row_index < height && column_index < width)
+                .any(|(row_index, column_index)| !is_inside(row_index, column_index))
+        })
+        .collect::<Vec<_>>();
+
+    let mut samples = Vec::new();
+    let mut box_size = 1;
+    // stop while there are still enough boxes to count
+    while box_size * 4 <= width.min(height) {
+        let box_rows = height.div_ceil(box_size);
+        let box_columns = width.div_ceil(box_size);
+        let box_count = (0..box_rows * box_columns)
+            .into_par_iter()
+            .filter(|box_index| {
+                let row_start = box_index / box_columns * box_size;
+                let column_start = box_index % box_columns * box_size;
+                (row_start..(row_start + box_size).min(height)).any(|row_index| {
+                    (column_start..(column_start + box_size).min(width)).any(|column_index| {
+                        is_boundary[serialize_index(row_index, column_index, width)]
+                    })
+                })
+            })
+            .count();
+
+        if box_count > 0 {
+            samples.push(vec2(-(box_size as f32).log2(), (box_count as f32).log2()));
+        }
+        box_size *= 2;
+    }
+
+    if samples.len() < 2 {
+        return None;
+    }
+
+    // least squares fit
+    let mean = samples.iter().sum::<Vec2>() / samples.len() as f32;
+    let covariance = samples
+        .iter()
+        .map(|sample| (sample.x - mean.x) * (sample.y - mean.y))
+        .sum::<f32>();
+    let variance = samples
+        .iter()
+        .map(|sample| (sample.x - mean.x).powi(2))
+        .sum::<f32>();
+    let dimension = covariance / variance;
+
+    Some(DimensionEstimate {
+        dimension,
+        intercept: mean.y - dimension * mean.x,
+        samples,
+    })
+}
+
+/// Draw the log-log plot of an estimate and its fitted line inside `area`
+pub fn draw_dimension_plot(estimate: &DimensionEstimate, area: Rect) {
+    const PADDING: f32 = 24.0;
+    const FONT_SIZE: f32 = 16.0;
+
+    draw_rectangle(area.x, area.y, area.w, area.h, BLACK.with_alpha(0.7));
+    draw_text(
+        &format!("box-counting dimension: {:.3}", estimate.dimension),
+        area.x + 4.0,
+        area.y + FONT_SIZE,
+        FONT_SIZE,
+        WHITE,
+    );
+    draw_text(
+        "log2(1 / box size)",
+        area.x + PADDING,
+        area.bottom() - 4.0,
+        FONT_SIZE,
+        LIGHTGRAY,
+    );
+
+    let plot = Rect::new(
+        area.x + PADDING,
+        area.y + PADDING,
+        area.w - PADDING * 2.0,
+        area.h - PADDING * 2.0,
+    );
+    let min = estimate
+        .samples
+        .iter()
+        .fold(Vec2::INFINITY, |min, &sample| min.min(sample));
+    let max = estimate
+        .samples
+        .iter()
+        .fold(Vec2::NEG_INFINITY, |max, &sample| max.max(sample));
+    let range = (max - min).max(Vec2::splat(f32::EPSILON));
+    let to_screen = |point: Vec2| {
+        let percent = (point - min) / range;
+        vec2(
+            plot.x + percent.x * plot.w,
+            plot.bottom() - percent.y * plot.h,
+        )
+    };
+
+    let fit_start = to_screen(vec2(min.x, estimate.intercept + estimate.dimension * min.x));
+    let fit_end = to_screen(vec2(max.x, estimate.intercept + estimate.dimension * max.x));
+    draw_line(fit_start.x, fit_start.y, fit_end.x, fit_end.y, 1.0, SKYBLUE);
+
+    for &sample in &estimate.samples {
+        let point = to_screen(sample);
+        draw_circle(point.x, point.y, 3.0, ORANGE);
+    }
+}
@@ -0,0 +1,44 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Why escape data couldn't be turned into an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderError {
+    /// macroquad images are at most `u16::MAX` pixels on each side
+    ImageTooLarge { width: usize, height: usize },
+    /// an image and the escape data it should be made from have different sizes
+    SizeMismatch {
+        /// (width, height) of the escape data
+        expected: (usize, usize),
+        /// (width, height) of the image
+        found: (usize, usize),
+    },
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ImageTooLarge { width, height } => write!(
+                f,
+                "A {width}x{height} image is too large, images can be at most {} pixels on each side",
+                u16::MAX
+            ),
+            Self::SizeMismatch {
+                expected: (expected_width, expected_height),
+                found: (found_width, found_height),
+            } => write!(
+                f,
+                "The image is {found_width}x{found_height} but the escape data is {expected_width}x{expected_height}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+/// The size of an image for `width` by `height` pixels of escape data
+pub fn image_size(width: usize, height: usize) -> Result<(u16, u16), RenderError> {
+    match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(width), Ok(height)) => Ok((width, height)),
+        _ => Err(RenderError::ImageTooLarge { width, height }),
+    }
+}
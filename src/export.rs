@@ -0,0 +1,553 @@
+//! Save the render as a PNG, optionally with the inside of the set cut out so it can be composited over another background.
+//! Where escape paths were stored the edge of the set is antialiased with the distance estimate,
+//! so the cut out doesn't have jagged pixel edges.
+//! "Export 4x" calculates the view again at a multiple of the window's resolution, a tile at a time so only one tile's escape data is in memory.
+//! Exported PNGs carry the view they show in their text chunks, and `Open PNG` goes back to it, see `png_metadata`.
+//! The raw escape times can be exported for coloring elsewhere, see `raw_export`, huge posters rendered, see `poster`,
+//! and tile pyramids for web viewers, see `deep_zoom`.
+//! The export window can also save the escape times of a selected region as a table, see `table`.
+
+use crate::{
+    MandelbrotRender,
+    coloring::{color_with_frame, create_mandelbrot_image},
+    colorizer::FrameStatistics,
+    deep_zoom::start_deep_zoom,
+    error::RenderError,
+    png_metadata::{read_view, write_png},
+    poster::PosterJob,
+    raw_export::{RawFormat, export_raw},
+    shading::distance_estimate,
+    table::{TableFormat, region_table},
+    view::ViewParams,
+};
+use macroquad::{
+    prelude::*,
+    ui::{hash, root_ui, widgets::Window},
+};
+use mandelbrot_core::{EscapeGrid, PixelRect};
+use std::path::Path;
+
+/// How many times the window's width and height "Export 4x" renders
+const HIGH_RESOLUTION_FACTOR: usize = 4;
+/// The side of the square tiles a high resolution export is calculated in
+const EXPORT_TILE_SIZE: usize = 256;
+/// The widths posters can be rendered at. Their height keeps the aspect ratio of the view
+const POSTER_WIDTHS: [usize; 4] = [4096, 8192, 16384, 32768];
+const POSTER_WIDTH_NAMES: [&str; 4] = ["4096", "8192", "16384", "32768"];
+
+/// What happens to the inside of the set when exporting
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Interior {
+    /// colored like the screen
+    #[default]
+    Opaque,
+    /// fully transparent
+    Transparent,
+    /// opaque, with a separate grayscale mask next to the image. White is outside the set
+    Mask,
+}
+
+impl Interior {
+    pub const ALL: [Self; 3] = [Self::Opaque, Self::Transparent, Self::Mask];
+    pub const NAMES: [&'static str; 3] = ["opaque", "transparent", "mask"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .position(|&interior_name| interior_name == name)
+            .map(|index| Self::ALL[index])
+    }
+}
+
+/// The opacity of each pixel: 0 inside the set and 255 outside it.
+/// Escaped pixels whose distance estimate is under a pixel from the set are partly transparent
+pub fn exterior_alpha(mandelbrot_data: &EscapeGrid, pixel_size: f32) -> Vec<u8> {
+    (0..mandelbrot_data.len())
+        .map(|pixel_index| {
+            if mandelbrot_data.escape_time(pixel_index).is_none() {
+                return 0;
+            }
+            let Some(escape_path) = mandelbrot_data.path(pixel_index) else {
+                return u8::MAX;
+            };
+
+            let Some(distance) = distance_estimate(escape_path) else {
+                return u8::MAX;
+            };
+            // a pixel right on the boundary is about half covered by the set
+            let coverage = (0.5 + distance / pixel_size).clamp(0.0, 1.0);
+            (coverage * u8::MAX as f32).round() as u8
+        })
+        .collect()
+}
+
+/// `path` with `_mask` added to the file name, e.g. `render.png` becomes `render_mask.png`
+pub fn mask_path(path: &str) -> String {
+    let path = Path::new(path);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("png");
+    path.with_file_name(format!("{stem}_mask.{extension}"))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Write `image`, colored from `mandelbrot_data`, to `path` as a PNG with `view` in its text chunks.
+/// Returns the path of the mask when one was written. `pixel_size` is the width of a pixel on the complex plane
+pub fn export_png(
+    image: &Image,
+    mandelbrot_data: &EscapeGrid,
+    pixel_size: f32,
+    interior: Interior,
+    path: &str,
+    view: &ViewParams,
+) -> Result<Option<String>, String> {
+    let image_size = (image.width as usize, image.height as usize);
+    let data_size = (mandelbrot_data.width(), mandelbrot_data.height());
+    if image_size != data_size {
+        return Err(RenderError::SizeMismatch {
+            expected: data_size,
+            found: image_size,
+        }
+        .to_string());
+    }
+
+    let (width, height) = image_size;
+
+    let rgba = png::ColorType::Rgba;
+    match interior {
+        Interior::Opaque => {
+            write_png(path, &image.bytes, width, height, rgba, Some(view)).map(|_| None)
+        }
+        Interior::Transparent => {
+            let alpha = exterior_alpha(mandelbrot_data, pixel_size);
+            let mut bytes = image.bytes.clone();
+            for (pixel, alpha) in bytes.chunks_exact_mut(4).zip(alpha) {
+                pixel[3] = alpha;
+            }
+            write_png(path, &bytes, width, height, rgba, Some(view)).map(|_| None)
+        }
+        Interior::Mask => {
+            write_png(path, &image.bytes, width, height, rgba, Some(view))?;
+            let mask_path = mask_path(path);
+            let alpha = exterior_alpha(mandelbrot_data, pixel_size);
+            write_png(
+                &mask_path,
+                &alpha,
+                width,
+                height,
+                png::ColorType::Grayscale,
+                None,
+            )?;
+            Ok(Some(mask_path))
+        }
+    }
+}
+
+/// Calculate the view of `render` again at `factor` times its resolution and write it to `path` as a PNG,
+/// without going through a texture. The view is calculated a tile at a time, and each tile is colored with the
+/// statistics of the screen's frame so the colors match the screen and there are no seams between tiles.
+/// Returns the path of the mask when one was written
+pub fn export_high_resolution(
+    render: &MandelbrotRender,
+    factor: usize,
+    interior: Interior,
+    path: &str,
+    view: &ViewParams,
+) -> Result<Option<String>, String> {
+    let (width, height) = (render.data.width() * factor, render.data.height() * factor);
+    let job = render.job.clone().resolution(width, height);
+    let frame = FrameStatistics::new(&render.data, render.iteration_max);
+    let pixel_size = render.dimensions.re / width as f32;
+
+    let mut bytes = vec![0; width * height * 4];
+    // only needed when the inside of the set is cut out
+    let mut alpha = vec![
+        u8::MAX;
+        if interior == Interior::Opaque {
+            0
+        } else {
+            width * height
+        }
+    ];
+    for rect in PixelRect::tiles(width, height, EXPORT_TILE_SIZE) {
+        let tile = job.run_tile(rect).map_err(|error| error.to_string())?;
+        let image =
+            color_with_frame(&tile, &frame, &render.coloring).map_err(|error| error.to_string())?;
+        let tile_alpha = (interior != Interior::Opaque).then(|| exterior_alpha(&tile, pixel_size));
+        for row_index in 0..rect.height {
+            let start = (rect.row + row_index) * width + rect.column;
+            let tile_start = row_index * rect.width;
+            bytes[start * 4..(start + rect.width) * 4]
+                .copy_from_slice(&image.bytes[tile_start * 4..(tile_start + rect.width) * 4]);
+            if let Some(tile_alpha) = &tile_alpha {
+                alpha[start..start + rect.width]
+                    .copy_from_slice(&tile_alpha[tile_start..tile_start + rect.width]);
+            }
+        }
+    }
+
+    let rgba = png::ColorType::Rgba;
+    match interior {
+        Interior::Opaque => write_png(path, &bytes, width, height, rgba, Some(view)).map(|_| None),
+        Interior::Transparent => {
+            for (pixel, alpha) in bytes.chunks_exact_mut(4).zip(alpha) {
+                pixel[3] = alpha;
+            }
+            write_png(path, &bytes, width, height, rgba, Some(view)).map(|_| None)
+        }
+        Interior::Mask => {
+            write_png(path, &bytes, width, height, rgba, Some(view))?;
+            let mask_path = mask_path(path);
+            write_png(
+                &mask_path,
+                &alpha,
+                width,
+                height,
+                png::ColorType::Grayscale,
+                None,
+            )?;
+            Ok(Some(mask_path))
+        }
+    }
+}
+
+/// What the export window shows between frames
+pub struct ExportState {
+    path: String,
+    interior: Interior,
+    /// the result of the last export
+    status: String,
+    /// whether dragging over the image selects the region to export as a table
+    select_region: bool,
+    /// where the current drag started, in screen coordinates
+    drag_start: Option<Vec2>,
+    /// the pixels to export as a table
+    region: Option<PixelRect>,
+    table_path: String,
+    table_format: TableFormat,
+    /// also export the smooth (continuous) escape times
+    include_smooth: bool,
+    raw_format: RawFormat,
+    /// also export the distance estimates with the raw escape times
+    include_distance: bool,
+    /// index into `POSTER_WIDTHS`
+    poster_width: usize,
+    poster_path: String,
+    /// the `.dzi` file a tile pyramid is described by. It's rendered at the poster width
+    deep_zoom_path: String,
+    /// the poster or tile pyramid being rendered
+    poster: Option<PosterJob>,
+}
+
+impl Default for ExportState {
+    fn default() -> Self {
+        Self {
+            path: String::from("mandelbrot.png"),
+            interior: Interior::default(),
+            status: String::new(),
+            select_region: false,
+            drag_start: None,
+            region: None,
+            table_path: String::from("escape_times.csv"),
+            table_format: TableFormat::default(),
+            include_smooth: false,
+            raw_format: RawFormat::default(),
+            include_distance: false,
+            poster_width: 2,
+            poster_path: String::from("poster.png"),
+            deep_zoom_path: String::from("deep_zoom.dzi"),
+            poster: None,
+        }
+    }
+}
+
+impl ExportState {
+    /// Whether dragging over the image selects a region, so it shouldn't do anything else
+    pub fn is_selecting_region(&self) -> bool {
+        self.select_region
+    }
+
+    /// Save the rendered image, without any overlays, to a new timestamped file, and show where in the export window
+    pub fn save_screenshot(&mut self, render: &MandelbrotRender, view: &ViewParams) {
+        let pixel_size = render.dimensions.re / render.data.width() as f32;
+        let saved = screenshot_path().and_then(|path| {
+            export_png(
+                &render.image,
+                &render.data,
+                pixel_size,
+                Interior::Opaque,
+                &path,
+                view,
+            )
+            .map(|_| path)
+        });
+        self.status = match saved {
+            Ok(path) => format!("Saved {path}"),
+            Err(error) => error,
+        };
+    }
+}
+
+/// A file name that's new for every screenshot, e.g. `mandelbrot_1700000000123.png`
+#[cfg(not(target_arch = "wasm32"))]
+fn screenshot_path() -> Result<String, String> {
+    let since_epoch = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|error| format!("The clock is before 1970: {error}"))?;
+    Ok(format!("mandelbrot_{}.png", since_epoch.as_millis()))
+}
+
+/// The browser can't save files
+#[cfg(target_arch = "wasm32")]
+fn screenshot_path() -> Result<String, String> {
+    Err(String::from("Screenshots can't be saved in the browser"))
+}
+
+/// The pixels of `mandelbrot_data` in the rectangle with corners `start` and `end` on screen, clamped to the data
+fn selected_region(mandelbrot_data: &EscapeGrid, start: Vec2, end: Vec2) -> Option<PixelRect> {
+    if mandelbrot_data.is_empty() {
+        return None;
+    }
+    let last_pixel = vec2(
+        (mandelbrot_data.width() - 1) as f32,
+        (mandelbrot_data.height() - 1) as f32,
+    );
+    let low = start.min(end).clamp(Vec2::ZERO, last_pixel);
+    let high = start.max(end).clamp(Vec2::ZERO, last_pixel);
+    Some(PixelRect {
+        row: low.y as usize,
+        column: low.x as usize,
+        width: high.x as usize - low.x as usize + 1,
+        height: high.y as usize - low.y as usize + 1,
+    })
+}
+
+/// Select a region by dragging over the image, and outline the selection
+fn update_region_selection(state: &mut ExportState, mandelbrot_data: &EscapeGrid) {
+    let mouse_position = Vec2::from(mouse_position());
+    if state.select_region {
+        // dragging a window doesn't select anything
+        if is_mouse_button_pressed(MouseButton::Left) && !root_ui().is_mouse_over(mouse_position) {
+            state.drag_start = Some(mouse_position);
+        }
+        if let Some(drag_start) = state.drag_start {
+            state.region = selected_region(mandelbrot_data, drag_start, mouse_position);
+            if is_mouse_button_released(MouseButton::Left) {
+                state.drag_start = None;
+            }
+        }
+    }
+
+    if let Some(region) = state.region {
+        draw_rectangle_lines(
+            region.column as f32,
+            region.row as f32,
+            region.width as f32,
+            region.height as f32,
+            2.0,
+            YELLOW,
+        );
+    }
+}
+
+/// Export the render as it was colored on screen, or the escape times of a selected region as a table.
+/// `view` is written into exported PNGs. Returns the view stored in the file when `Open PNG` is clicked
+pub fn export_window(
+    state: &mut ExportState,
+    render: &MandelbrotRender,
+    view: impl Fn() -> ViewParams,
+) -> Option<ViewParams> {
+    let mut opened = None;
+    update_region_selection(state, &render.data);
+    if let Some(poster) = state.poster.take_if(|poster| poster.is_finished()) {
+        state.status = poster.join();
+    }
+
+    let window_size = vec2(250.0, 440.0);
+    Window::new(
+        hash!(),
+        vec2(screen_width() - window_size.x, 270.0),
+        window_size,
+    )
+    .label("export")
+    .titlebar(true)
+    .ui(&mut *root_ui(), |ui| {
+        ui.input_text(hash!(), "file", &mut state.path);
+        let mut interior_index = state.interior as usize;
+        ui.combo_box(hash!(), "interior", &Interior::NAMES, &mut interior_index);
+        state.interior = Interior::ALL[interior_index];
+
+        if ui.button(None, "Export PNG") {
+            let pixel_size = render.dimensions.re / render.data.width() as f32;
+            let exported =
+                create_mandelbrot_image(&render.data, render.iteration_max, &render.coloring)
+                    .map_err(|error| error.to_string())
+                    .and_then(|image| {
+                        export_png(
+                            &image,
+                            &render.data,
+                            pixel_size,
+                            state.interior,
+                            &state.path,
+                            &view(),
+                        )
+                    });
+            state.status = match exported {
+                Ok(Some(mask_path)) => format!("Saved {} and {mask_path}", state.path),
+                Ok(None) => format!("Saved {}", state.path),
+                Err(error) => error,
+            };
+        }
+        ui.same_line(0.0);
+        if ui.button(None, format!("Export {HIGH_RESOLUTION_FACTOR}x").as_str()) {
+            let exported = export_high_resolution(
+                render,
+                HIGH_RESOLUTION_FACTOR,
+                state.interior,
+                &state.path,
+                &view(),
+            );
+            state.status = match exported {
+                Ok(Some(mask_path)) => format!("Saved {} and {mask_path}", state.path),
+                Ok(None) => format!("Saved {}", state.path),
+                Err(error) => error,
+            };
+        }
+        if ui.button(None, "Open PNG") {
+            match read_view(&state.path) {
+                Ok(view) => {
+                    state.status = format!("Opened the view in {}", state.path);
+                    opened = Some(view);
+                }
+                Err(error) => state.status = error,
+            }
+        }
+
+        ui.separator();
+        let mut raw_format_index = state.raw_format as usize;
+        ui.combo_box(
+            hash!(),
+            "raw format",
+            &RawFormat::NAMES,
+            &mut raw_format_index,
+        );
+        state.raw_format = RawFormat::ALL[raw_format_index];
+        if state.raw_format.has_distance() {
+            ui.checkbox(hash!(), "distance estimates", &mut state.include_distance);
+        }
+        if ui.button(None, "Export Raw") {
+            let exported = export_raw(
+                render,
+                state.raw_format,
+                state.include_distance,
+                &state.path,
+                &view(),
+            );
+            state.status = match exported {
+                Ok(paths) => format!("Saved {}", paths.join(" and ")),
+                Err(error) => error,
+            };
+        }
+
+        ui.separator();
+        ui.checkbox(hash!(), "select region", &mut state.select_region);
+        match state.region {
+            Some(region) => ui.label(
+                None,
+                &format!("region: {}x{} pixels", region.width, region.height),
+            ),
+            None => ui.label(None, "drag over the image to select"),
+        }
+        ui.input_text(hash!(), "table file", &mut state.table_path);
+        let mut table_format_index = state.table_format as usize;
+        ui.combo_box(
+            hash!(),
+            "table format",
+            &TableFormat::NAMES,
+            &mut table_format_index,
+        );
+        if TableFormat::ALL[table_format_index] != state.table_format {
+            state.table_format = TableFormat::ALL[table_format_index];
+            state.table_path = Path::new(&state.table_path)
+                .with_extension(state.table_format.extension())
+                .to_string_lossy()
+                .into_owned();
+        }
+        ui.checkbox(hash!(), "smooth values", &mut state.include_smooth);
+
+        if let Some(region) = state.region
+            && ui.button(None, "Export Table")
+        {
+            let exported = region_table(
+                &render.data,
+                &render.job,
+                region,
+                state.include_smooth,
+                state.table_format,
+            )
+            .and_then(|table| {
+                std::fs::write(&state.table_path, table)
+                    .map_err(|error| format!("Failed to write {:?}: {error}", state.table_path))
+            });
+            state.status = match exported {
+                Ok(()) => format!("Saved {}", state.table_path),
+                Err(error) => error,
+            };
+        }
+
+        ui.separator();
+        ui.combo_box(
+            hash!(),
+            "poster width",
+            &POSTER_WIDTH_NAMES,
+            &mut state.poster_width,
+        );
+        ui.input_text(hash!(), "poster file", &mut state.poster_path);
+        ui.input_text(hash!(), "deep zoom file", &mut state.deep_zoom_path);
+        match &state.poster {
+            Some(poster) => {
+                ui.label(
+                    None,
+                    &format!("rendering {:.0}% done", poster.progress() * 100.0),
+                );
+                if ui.button(None, "Cancel Render") {
+                    poster.cancel();
+                }
+            }
+            None => {
+                let width = POSTER_WIDTHS[state.poster_width];
+                let height = width * render.data.height() / render.data.width().max(1);
+                let render_poster = ui.button(None, "Render Poster");
+                ui.same_line(0.0);
+                let render_deep_zoom = ui.button(None, "Render Deep Zoom");
+                let started = if render_poster {
+                    Some(PosterJob::start(view(), width, height, &state.poster_path))
+                } else if render_deep_zoom {
+                    Some(start_deep_zoom(
+                        view(),
+                        width,
+                        height,
+                        &state.deep_zoom_path,
+                    ))
+                } else {
+                    None
+                };
+                match started {
+                    Some(Ok(poster)) => state.poster = Some(poster),
+                    Some(Err(error)) => state.status = error,
+                    None => {}
+                }
+            }
+        }
+
+        ui.label(None, &state.status);
+    });
+
+    opened
+}
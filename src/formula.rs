@@ -0,0 +1,184 @@
+//! The fractal formulas the app can render, and what the formula window says about each of them.
+//! Adding an entry to `FORMULAS` is all it takes for a new formula to show up.
+
+use crate::{Settings, period::BULB_NUCLEI};
+use macroquad::{
+    prelude::*,
+    ui::{hash, root_ui, widgets::Window},
+};
+use mandelbrot_core::{BurningShip, FractalFormula, Mandelbrot, Tricorn};
+use num::Complex;
+use std::sync::LazyLock;
+
+/// The formulas to choose from. The first is the default
+pub static FORMULAS: LazyLock<Vec<FormulaEntry>> = LazyLock::new(|| {
+    vec![
+        FormulaEntry {
+            name: "mandelbrot",
+            definition: &["z = z^2 + c", "starting at z = 0"],
+            formula: Box::new(Mandelbrot),
+            regions: MANDELBROT_REGIONS,
+            recommended_colorizer: "smooth",
+            bulbs: &BULB_NUCLEI,
+        },
+        FormulaEntry {
+            name: "tricorn",
+            definition: &["z = conj(z)^2 + c", "starting at z = 0"],
+            formula: Box::new(Tricorn),
+            regions: TRICORN_REGIONS,
+            recommended_colorizer: "smooth",
+            bulbs: &[],
+        },
+        FormulaEntry {
+            name: "burning ship",
+            definition: &["z = (|re z| + i|im z|)^2 + c", "starting at z = 0"],
+            formula: Box::new(BurningShip),
+            regions: BURNING_SHIP_REGIONS,
+            recommended_colorizer: "histogram",
+            bulbs: &[],
+        },
+    ]
+});
+
+const MANDELBROT_REGIONS: &[Region] = &[
+    Region::new("whole set", -0.4, 0.0, 1.0, 500),
+    Region::new("seahorse valley", -0.745, 0.113, 50.0, 1000),
+    Region::new("elephant valley", 0.285, 0.01, 40.0, 1000),
+    Region::new("triple spiral valley", -0.088, 0.654, 40.0, 1000),
+    // misiurewicz points land on a repeating cycle without being in it, so the spirals around them look the same at every zoom
+    Region::new("misiurewicz point i", 0.0, 1.0, 200.0, 1000),
+    Region::new("misiurewicz point M3,1", -0.101096, 0.956287, 400.0, 2000),
+    Region::new("period 3 mini set", -1.7549, 0.0, 60.0, 1000),
+    Region::new("period 4 mini set", -0.15652, 1.032247, 100.0, 2000),
+];
+const TRICORN_REGIONS: &[Region] = &[
+    Region::new("whole set", -0.3, 0.0, 1.0, 500),
+    Region::new("left spike", -1.75, 0.0, 10.0, 500),
+];
+const BURNING_SHIP_REGIONS: &[Region] = &[
+    Region::new("whole set", -0.5, -0.5, 1.0, 500),
+    Region::new("the ship", -1.762, -0.028, 25.0, 1000),
+];
+
+/// A view worth visiting
+pub struct Region {
+    pub name: &'static str,
+    pub center: Complex<f32>,
+    pub scale: f32,
+    /// enough iterations to show the detail at `scale`
+    pub iteration_max: usize,
+}
+
+impl Region {
+    const fn new(
+        name: &'static str,
+        center_re: f32,
+        center_im: f32,
+        scale: f32,
+        iteration_max: usize,
+    ) -> Self {
+        Self {
+            name,
+            center: Complex::new(center_re, center_im),
+            scale,
+            iteration_max,
+        }
+    }
+}
+
+pub struct FormulaEntry {
+    /// Shown in the UI and used to select the formula
+    pub name: &'static str,
+    /// the iteration, one short line per entry
+    pub definition: &'static [&'static str],
+    pub formula: Box<dyn FractalFormula<f32> + Send>,
+    /// the first region is where the formula opens
+    pub regions: &'static [Region],
+    /// name of the registered `Colorizer` that shows the formula best
+    pub recommended_colorizer: &'static str,
+    /// (nucleus, period) of the bulbs worth labelling
+    pub bulbs: &'static [(Complex<f32>, usize)],
+}
+
+/// The formula called `name`, or the first formula if there isn't one
+pub fn find_formula(name: &str) -> &'static FormulaEntry {
+    FORMULAS
+        .iter()
+        .find(|entry| entry.name == name)
+        .unwrap_or(&FORMULAS[0])
+}
+
+/// Pick a formula, and optionally a second one to compare it with, and read about it.
+/// `region_index` is the region picked in the list of the formula's regions.
+/// Returns the region to jump to when `Go` is clicked or a different formula is picked
+pub fn formula_window(
+    settings: &mut Settings,
+    region_index: &mut usize,
+) -> Option<&'static Region> {
+    let names = FORMULAS.iter().map(|entry| entry.name).collect::<Vec<_>>();
+    let mut formula_index = names
+        .iter()
+        .position(|&name| name == settings.formula)
+        .unwrap_or(0);
+
+    // the first choice turns the comparison off
+    let compare_names = std::iter::once("none")
+        .chain(names.iter().copied())
+        .collect::<Vec<_>>();
+    let mut compare_index = settings
+        .compare_formula
+        .as_deref()
+        .and_then(|compare_formula| names.iter().position(|&name| name == compare_formula))
+        .map_or(0, |index| index + 1);
+
+    let mut region = None;
+    let window_size = vec2(250.0, 260.0);
+    Window::new(
+        hash!(),
+        vec2(screen_width() - window_size.x, 0.0),
+        window_size,
+    )
+    .label("formula")
+    .titlebar(true)
+    .ui(&mut *root_ui(), |ui| {
+        ui.combo_box(hash!(), "formula", &names, &mut formula_index);
+        let entry = &FORMULAS[formula_index];
+        if entry.name != settings.formula {
+            settings.formula = entry.name.to_owned();
+            region = entry.regions.first();
+            *region_index = 0;
+        }
+        ui.combo_box(hash!(), "compare with", &compare_names, &mut compare_index);
+        let compare_formula = compare_index.checked_sub(1).map(|index| names[index]);
+        if compare_formula != settings.compare_formula.as_deref() {
+            settings.compare_formula = compare_formula.map(str::to_owned);
+        }
+
+        for line in entry.definition {
+            ui.label(None, line);
+        }
+
+        ui.label(
+            None,
+            &format!("recommended coloring: {}", entry.recommended_colorizer),
+        );
+        if settings.coloring.colorizer != entry.recommended_colorizer
+            && ui.button(None, "Use Recommended Coloring")
+        {
+            settings.coloring.colorizer = entry.recommended_colorizer.to_owned();
+        }
+
+        let region_names = entry
+            .regions
+            .iter()
+            .map(|entry_region| entry_region.name)
+            .collect::<Vec<_>>();
+        *region_index = (*region_index).min(region_names.len() - 1);
+        ui.combo_box(hash!(), "region", &region_names, region_index);
+        if ui.button(None, "Go") {
+            region = entry.regions.get(*region_index);
+        }
+    });
+
+    region
+}
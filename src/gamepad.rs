@@ -0,0 +1,90 @@
+//! Navigating with a gamepad, so the demo can run on a TV without a mouse or keyboard.
+//! The left stick pans, the triggers zoom, up and down on the d-pad change the iterations, and A toggles the orbit.
+//! Gamepads are read with gilrs, which doesn't run in the browser, so the wasm build ignores them
+
+use crate::navigation::{KeyboardCommand, KeyboardSteps};
+#[cfg(not(target_arch = "wasm32"))]
+use gilrs::{Axis, Button, EventType, Gilrs};
+#[cfg(not(target_arch = "wasm32"))]
+use macroquad::prelude::*;
+
+/// Stick deflections smaller than this are drift
+#[cfg(not(target_arch = "wasm32"))]
+const DEAD_ZONE: f32 = 0.3;
+/// Seconds between steps while the stick or a trigger is held
+#[cfg(not(target_arch = "wasm32"))]
+const REPEAT_INTERVAL: f64 = 0.25;
+
+pub struct GamepadInput {
+    /// `None` if gamepads can't be read on this system
+    #[cfg(not(target_arch = "wasm32"))]
+    gilrs: Option<Gilrs>,
+    /// when the held stick or trigger last stepped, from `get_time`
+    #[cfg(not(target_arch = "wasm32"))]
+    repeated_at: f64,
+}
+
+impl Default for GamepadInput {
+    fn default() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            gilrs: Gilrs::new()
+                .inspect_err(|error| eprintln!("Gamepads are unavailable: {error}"))
+                .ok(),
+            #[cfg(not(target_arch = "wasm32"))]
+            repeated_at: 0.0,
+        }
+    }
+}
+
+impl GamepadInput {
+    /// The command of the gamepad this frame, if any. Call once per frame so no presses are missed
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn command(&mut self, steps: &KeyboardSteps) -> Option<KeyboardCommand> {
+        let gilrs = self.gilrs.as_mut()?;
+        let iteration_step = steps.iteration_step as isize;
+
+        let mut pressed = None;
+        while let Some(event) = gilrs.next_event() {
+            let EventType::ButtonPressed(button, _) = event.event else {
+                continue;
+            };
+            pressed = pressed.or(match button {
+                Button::DPadUp => Some(KeyboardCommand::Iterations(iteration_step)),
+                Button::DPadDown => Some(KeyboardCommand::Iterations(-iteration_step)),
+                Button::South => Some(KeyboardCommand::ToggleOrbit),
+                _ => None,
+            });
+        }
+        if pressed.is_some() {
+            return pressed;
+        }
+
+        let (_, gamepad) = gilrs.gamepads().next()?;
+        // the stick's y points up and the screen's points down
+        let stick = vec2(
+            gamepad.value(Axis::LeftStickX),
+            -gamepad.value(Axis::LeftStickY),
+        );
+        let held = if stick.length() > DEAD_ZONE {
+            KeyboardCommand::Pan(stick * steps.pan_fraction)
+        } else if gamepad.is_pressed(Button::RightTrigger2) {
+            KeyboardCommand::Zoom(steps.zoom_factor)
+        } else if gamepad.is_pressed(Button::LeftTrigger2) {
+            KeyboardCommand::Zoom(steps.zoom_factor.recip())
+        } else {
+            return None;
+        };
+        let now = get_time();
+        if now - self.repeated_at < REPEAT_INTERVAL {
+            return None;
+        }
+        self.repeated_at = now;
+        Some(held)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn command(&mut self, _steps: &KeyboardSteps) -> Option<KeyboardCommand> {
+        None
+    }
+}
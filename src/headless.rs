@@ -0,0 +1,285 @@
+//! Rendering without a window, driven by newline-delimited JSON on stdin, so other programs can run the renderer as a subprocess.
+//! Each line of stdin is one `RenderRequest` and gets exactly one line of response on stdout, in the same order.
+//!
+//! ```text
+//! > {"id": 1, "center": [-0.745, 0.113], "scale": 50, "width": 400, "height": 300, "output": "seahorse.png"}
+//! < {"id":1,"png":"seahorse.png"}
+//! > {"id": 2, "width": 64, "height": 48, "coloring": {"colorizer": "histogram"}}
+//! < {"id":2,"width":64,"height":48,"rgba":"AAAA/w..."}
+//! ```
+
+use crate::{
+    coloring::create_mandelbrot_image,
+    colorizer::COLORIZERS,
+    complex_dimensions,
+    error::image_size,
+    export::{Interior, export_png, exterior_alpha},
+    formula::FORMULAS,
+    view::{ViewParams, view_job},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+/// The highest iteration limit a request may ask for. The histogram colorizer keeps a count for every iteration,
+/// so an unchecked limit from another program could allocate without bound
+const ITERATION_MAX_LIMIT: usize = 1_000_000;
+
+/// What to render. Missing fields use the same defaults as the app
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct RenderRequest {
+    /// echoed back in the response so requests and responses can be matched
+    id: Option<Value>,
+    #[serde(flatten)]
+    view: ViewParams,
+    width: usize,
+    height: usize,
+    /// where to write the image as a PNG. The pixels are sent back as base64 instead when this is missing
+    output: Option<String>,
+    /// `opaque`, `transparent`, or `mask`. See `export::Interior`
+    interior: String,
+}
+
+impl Default for RenderRequest {
+    fn default() -> Self {
+        Self {
+            id: None,
+            view: ViewParams::default(),
+            width: 800,
+            height: 600,
+            output: None,
+            interior: String::from(Interior::NAMES[0]),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RenderResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<Value>,
+    #[serde(flatten)]
+    result: RenderResult,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum RenderResult {
+    /// the path the PNG was written to
+    Png {
+        png: String,
+        /// the path the mask was written to
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mask: Option<String>,
+    },
+    /// RGBA8 pixels in row major order, base64 encoded
+    Tile {
+        width: usize,
+        height: usize,
+        rgba: String,
+        /// one byte of opacity per pixel, base64 encoded
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mask: Option<String>,
+    },
+    Error {
+        error: String,
+    },
+}
+
+/// Answer render requests from stdin until it closes
+pub fn serve() {
+    let stdout = io::stdout();
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(error) => {
+                eprintln!("Failed to read a render request: {error}");
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<RenderRequest>(&line) {
+            Ok(mut request) => RenderResponse {
+                id: request.id.take(),
+                result: render(request).unwrap_or_else(|error| RenderResult::Error { error }),
+            },
+            Err(error) => RenderResponse {
+                id: None,
+                result: RenderResult::Error {
+                    error: format!("Invalid render request: {error}"),
+                },
+            },
+        };
+
+        let mut stdout = stdout.lock();
+        let written = serde_json::to_writer(&mut stdout, &response)
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(stdout))
+            .and_then(|_| stdout.flush());
+        if let Err(error) = written {
+            // whoever was reading has gone away
+            eprintln!("Failed to write a render response: {error}");
+            break;
+        }
+    }
+}
+
+fn render(request: RenderRequest) -> Result<RenderResult, String> {
+    let RenderRequest {
+        id: _,
+        view:
+            ViewParams {
+                center,
+                scale,
+                iteration_max,
+                formula,
+                coloring,
+                rotation,
+            },
+        width,
+        height,
+        output,
+        interior,
+    } = request;
+
+    if width == 0 || height == 0 {
+        return Err(String::from(
+            "The image must be at least 1 pixel on each side",
+        ));
+    }
+    // check the size before spending time on the render
+    image_size(width, height).map_err(|error| error.to_string())?;
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(String::from("The scale must be a positive number"));
+    }
+    if !(1..=ITERATION_MAX_LIMIT).contains(&iteration_max) {
+        return Err(format!(
+            "The iteration limit must be between 1 and {ITERATION_MAX_LIMIT}"
+        ));
+    }
+    let formula = FORMULAS
+        .iter()
+        .find(|entry| entry.name == formula)
+        .ok_or_else(|| {
+            let names = FORMULAS.iter().map(|entry| entry.name).collect::<Vec<_>>();
+            format!(
+                "Unknown formula {formula:?}. Available formulas: {}",
+                names.join(", ")
+            )
+        })?;
+    {
+        let colorizers = COLORIZERS
+            .read()
+            .expect("a colorizer panicked while being registered");
+        if !colorizers.contains(&coloring.colorizer) {
+            return Err(format!(
+                "Unknown colorizer {:?}. Available colorizers: {}",
+                coloring.colorizer,
+                colorizers.names().join(", ")
+            ));
+        }
+    }
+    let interior = Interior::from_name(&interior).ok_or_else(|| {
+        format!(
+            "Unknown interior {interior:?}. Available interiors: {}",
+            Interior::NAMES.join(", ")
+        )
+    })?;
+    let view = ViewParams {
+        center,
+        scale,
+        iteration_max,
+        formula: formula.name.to_owned(),
+        coloring,
+        rotation,
+    };
+    let job = view_job(&view, width, height);
+    let pixel_size = complex_dimensions(scale, width as f32, height as f32).re / width as f32;
+    // the distance estimate smooths the edge of the interior
+    let store_paths = view.coloring.slope_shading || interior != Interior::Opaque;
+    let data = job
+        .store_paths(store_paths)
+        .orbit_statistics(!store_paths)
+        .run();
+    let mut image = create_mandelbrot_image(&data, iteration_max, &view.coloring)
+        .map_err(|error| error.to_string())?;
+
+    match output {
+        Some(path) => {
+            let mask = export_png(&image, &data, pixel_size, interior, &path, &view)?;
+            Ok(RenderResult::Png { png: path, mask })
+        }
+        None => {
+            let mut mask = None;
+            match interior {
+                Interior::Opaque => {}
+                Interior::Transparent => {
+                    let alpha = exterior_alpha(&data, pixel_size);
+                    for (pixel, alpha) in image.bytes.chunks_exact_mut(4).zip(alpha) {
+                        pixel[3] = alpha;
+                    }
+                }
+                Interior::Mask => mask = Some(encode_base64(&exterior_alpha(&data, pixel_size))),
+            }
+            Ok(RenderResult::Tile {
+                width,
+                height,
+                rgba: encode_base64(&image.bytes),
+                mask,
+            })
+        }
+    }
+}
+
+/// Standard base64 with padding
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |group, (index, &byte)| {
+                group | ((byte as u32) << (16 - 8 * index))
+            });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[((group >> (18 - 6 * index)) & 0b11_1111) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode_base64;
+
+    #[test]
+    fn base64_matches_the_rfc_4648_test_vectors() {
+        for (bytes, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encode_base64(bytes.as_bytes()), encoded);
+        }
+    }
+
+    #[test]
+    fn base64_uses_the_whole_alphabet() {
+        assert_eq!(encode_base64(&[0x00, 0x10, 0x83]), "ABCD");
+        assert_eq!(encode_base64(&[0xfb, 0xff, 0xbf]), "+/+/");
+        assert_eq!(encode_base64(&[0xff, 0xff, 0xff, 0xff]), "/////w==");
+    }
+}
@@ -0,0 +1,72 @@
+//! An overlay listing every control, built from the same bindings the input code reads
+
+use crate::navigation::{KeyboardSteps, bindings};
+use macroquad::prelude::*;
+
+const FONT_SIZE: f32 = 20.0;
+const LINE_HEIGHT: f32 = 24.0;
+/// Pixels between the panel's edge and its text
+const PADDING: f32 = 16.0;
+/// Pixels from the start of a line to the description
+const DESCRIPTION_OFFSET: f32 = 190.0;
+
+/// How the orbit overlay works, one short line per entry
+const ORBIT_EXPLANATION: [&str; 4] = [
+    "The lines follow the orbit z0, z1, z2, ... of the c under the mouse,",
+    "iterating z = z^2 + c. Each z is colored like the pixels that escape",
+    "after as many iterations. Points whose orbits leave |z| = 2 escape,",
+    "and points whose orbits never do are in the set (black).",
+];
+
+/// (input, what it does) for every key and gesture binding. Inputs next to each other with the same description share a line
+fn help_lines(steps: &KeyboardSteps) -> Vec<(String, &'static str)> {
+    let mut lines: Vec<(String, &'static str)> = Vec::new();
+    for binding in bindings(steps) {
+        let description = binding.description();
+        let name = binding.input_name();
+        match lines.last_mut() {
+            Some((inputs, last_description)) if *last_description == description => {
+                inputs.push_str(&format!(" / {name}"));
+            }
+            _ => lines.push((name, description)),
+        }
+    }
+    lines
+}
+
+/// Draw the list of controls over the middle of the screen
+pub fn draw_help(steps: &KeyboardSteps) {
+    let lines = help_lines(steps);
+    let line_count = lines.len() + 1 + ORBIT_EXPLANATION.len();
+    let size = vec2(
+        DESCRIPTION_OFFSET * 2.0 + PADDING * 2.0 + 180.0,
+        line_count as f32 * LINE_HEIGHT + PADDING * 2.0,
+    );
+    let top_left = (vec2(screen_width(), screen_height()) - size) / 2.0;
+    draw_rectangle(
+        top_left.x,
+        top_left.y,
+        size.x,
+        size.y,
+        Color::new(0.0, 0.0, 0.0, 0.8),
+    );
+
+    let left = top_left.x + PADDING;
+    let mut baseline = top_left.y + PADDING + FONT_SIZE;
+    for (input, description) in &lines {
+        draw_text(input, left, baseline, FONT_SIZE, YELLOW);
+        draw_text(
+            description,
+            left + DESCRIPTION_OFFSET,
+            baseline,
+            FONT_SIZE,
+            WHITE,
+        );
+        baseline += LINE_HEIGHT;
+    }
+    baseline += LINE_HEIGHT;
+    for line in ORBIT_EXPLANATION {
+        draw_text(line, left, baseline, FONT_SIZE, LIGHTGRAY);
+        baseline += LINE_HEIGHT;
+    }
+}
@@ -0,0 +1,99 @@
+use macroquad::prelude::*;
+use mandelbrot_core::EscapeGrid;
+
+/// Bars the escape times from 0 to `iteration_max` are split between
+const BIN_COUNT: usize = 64;
+/// The share of the iteration range at its top end. Many escapes there mean the limit is cutting orbits off
+const TOP_SHARE: f32 = 0.1;
+
+/// How many pixels of a render escaped after how many iterations
+pub struct EscapeHistogram {
+    /// the pixels that escaped in each equal share of `0..=iteration_max`
+    bins: Vec<usize>,
+    /// pixels that never escaped
+    inside: usize,
+    /// pixels that escaped in the top `TOP_SHARE` of the iteration range
+    escaped_late: usize,
+    iteration_max: usize,
+}
+
+impl EscapeHistogram {
+    pub fn new(mandelbrot_data: &EscapeGrid, iteration_max: usize) -> Self {
+        let bin_count = BIN_COUNT.min(iteration_max + 1);
+        let late_start = iteration_max as f32 * (1.0 - TOP_SHARE);
+        let mut bins = vec![0; bin_count];
+        let mut inside = 0;
+        let mut escaped_late = 0;
+        for pixel_index in 0..mandelbrot_data.len() {
+            let Some(escape_time) = mandelbrot_data.escape_time(pixel_index) else {
+                inside += 1;
+                continue;
+            };
+            let escape_time = escape_time.min(iteration_max);
+            bins[escape_time * bin_count / (iteration_max + 1)] += 1;
+            if escape_time as f32 >= late_start {
+                escaped_late += 1;
+            }
+        }
+
+        Self {
+            bins,
+            inside,
+            escaped_late,
+            iteration_max,
+        }
+    }
+
+    /// Draw the bars inside `area`, on a log scale so the rare long escapes still show,
+    /// labelled with how much of the view is inside the set and how much escaped near the limit
+    pub fn draw(&self, area: Rect) {
+        const PADDING: f32 = 24.0;
+        const FONT_SIZE: f32 = 16.0;
+
+        let pixel_count = (self.bins.iter().sum::<usize>() + self.inside).max(1) as f32;
+        draw_rectangle(area.x, area.y, area.w, area.h, BLACK.with_alpha(0.7));
+        draw_text(
+            &format!(
+                "inside: {:.1}%  escaped late: {:.1}%",
+                100.0 * self.inside as f32 / pixel_count,
+                100.0 * self.escaped_late as f32 / pixel_count
+            ),
+            area.x + 4.0,
+            area.y + FONT_SIZE,
+            FONT_SIZE,
+            WHITE,
+        );
+        draw_text(
+            &format!("escape time 0 to {}", self.iteration_max),
+            area.x + PADDING,
+            area.bottom() - 4.0,
+            FONT_SIZE,
+            LIGHTGRAY,
+        );
+
+        let plot = Rect::new(
+            area.x + PADDING,
+            area.y + PADDING,
+            area.w - PADDING * 2.0,
+            area.h - PADDING * 2.0,
+        );
+        let tallest = (self.bins.iter().copied().max().unwrap_or(0) as f32).ln_1p();
+        let bar_width = plot.w / self.bins.len() as f32;
+        for (bin_index, &count) in self.bins.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let height = (count as f32).ln_1p() / tallest * plot.h;
+            draw_rectangle(
+                plot.x + bin_index as f32 * bar_width,
+                plot.bottom() - height,
+                bar_width.max(1.0),
+                height,
+                ORANGE,
+            );
+        }
+        // where escaping late starts
+        let late_x = plot.x + plot.w * (1.0 - TOP_SHARE);
+        draw_line(late_x, plot.y, late_x, plot.bottom(), 1.0, SKYBLUE);
+    }
+}
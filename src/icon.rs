@@ -0,0 +1,59 @@
+// This module is also compiled into the wasm example to render its favicon,
+// so it must only depend on macroquad and num.
+
+use macroquad::{color::hsl_to_rgb, miniquad::conf::Icon};
+use num::Complex;
+
+// frame the whole set
+const ICON_CENTER: Complex<f32> = Complex::new(-0.75, 0.0);
+const ICON_WIDTH: f32 = 2.6;
+const ICON_ITERATION_MAX: usize = 64;
+
+/// A tiny rendering of the Mandelbrot set at every size the window icon needs
+pub fn window_icon() -> Icon {
+    Icon {
+        small: render_icon(16).try_into().expect("16x16 RGBA"),
+        medium: render_icon(32).try_into().expect("32x32 RGBA"),
+        big: render_icon(64).try_into().expect("64x64 RGBA"),
+    }
+}
+
+/// Render a `size`×`size` RGBA image of the set
+fn render_icon(size: usize) -> Vec<u8> {
+    (0..size * size)
+        .flat_map(|pixel_index| {
+            let row_index = pixel_index / size;
+            let column_index = pixel_index % size;
+            // sample the middle of each pixel
+            let x_percent = (column_index as f32 + 0.5) / size as f32;
+            let y_percent = (row_index as f32 + 0.5) / size as f32;
+            let c = ICON_CENTER
+                + Complex::new(
+                    (x_percent - 0.5) * ICON_WIDTH,
+                    (0.5 - y_percent) * ICON_WIDTH,
+                );
+
+            icon_color(c)
+        })
+        .collect()
+}
+
+/// The set is black. Points outside fade from transparent to blue as they get closer to the boundary
+fn icon_color(c: Complex<f32>) -> [u8; 4] {
+    let mut z = Complex::new(0.0, 0.0);
+    for iteration in 0..ICON_ITERATION_MAX {
+        z = z * z + c;
+        if z.norm_sqr() > 4.0 {
+            let normalized = iteration as f32 / ICON_ITERATION_MAX as f32;
+            let color = hsl_to_rgb(0.6, 1.0, 0.3 + normalized * 0.4);
+            return [
+                (color.r * 255.0) as _,
+                (color.g * 255.0) as _,
+                (color.b * 255.0) as _,
+                (normalized.sqrt() * 255.0) as _,
+            ];
+        }
+    }
+
+    [0, 0, 0, 255]
+}
@@ -0,0 +1,99 @@
+//! A small picture of the Julia set of the `c` under the mouse, next to the minimap.
+//! Points inside the Mandelbrot set have connected Julia sets, and points outside have scattered dust,
+//! so moving the mouse across the boundary shows the Julia set breaking apart
+
+use crate::{
+    coloring::{Coloring, create_mandelbrot_image},
+    complex_dimensions,
+    minimap::Minimap,
+};
+use macroquad::prelude::*;
+use mandelbrot_core::{EscapeGrid, Julia, RenderJob};
+use num::Complex;
+
+const JULIA_WIDTH: usize = 160;
+const JULIA_HEIGHT: usize = 120;
+const JULIA_ITERATION_MAX: usize = 200;
+/// Seconds between updates, so sweeping the mouse doesn't calculate a new set every frame
+const JULIA_UPDATE_INTERVAL: f64 = 0.2;
+/// Pixels between the picture and the minimap
+const JULIA_MARGIN: f32 = 10.0;
+
+/// The Julia set of the last hovered `c`
+#[derive(Default)]
+pub struct JuliaPreview {
+    /// the `c` of `data`
+    c: Option<Complex<f32>>,
+    /// when `data` was last calculated, from `get_time`
+    updated_at: f64,
+    data: Option<EscapeGrid>,
+    /// how `texture` was colored
+    coloring: Coloring,
+    texture: Option<Texture2D>,
+}
+
+impl JuliaPreview {
+    /// Where the picture is drawn, to the right of the minimap
+    fn rect() -> Rect {
+        let minimap = Minimap::rect();
+        Rect::new(
+            minimap.right() + JULIA_MARGIN,
+            minimap.bottom() - JULIA_HEIGHT as f32,
+            JULIA_WIDTH as f32,
+            JULIA_HEIGHT as f32,
+        )
+    }
+
+    /// Calculate the set of the hovered `c` if it's changed and it's been long enough since the last update,
+    /// and recolor it if the coloring changed. Call every frame
+    pub fn update(&mut self, c: Option<Complex<f32>>, coloring: &Coloring) {
+        if let Some(c) = c
+            && self.c != Some(c)
+            && get_time() - self.updated_at >= JULIA_UPDATE_INTERVAL
+        {
+            self.data = Some(
+                RenderJob::new()
+                    .resolution(JULIA_WIDTH, JULIA_HEIGHT)
+                    .center(Complex::new(0.0, 0.0))
+                    .dimensions(complex_dimensions(
+                        1.0,
+                        JULIA_WIDTH as f32,
+                        JULIA_HEIGHT as f32,
+                    ))
+                    .iteration_max(JULIA_ITERATION_MAX)
+                    .formula(Julia { c })
+                    .store_paths(false)
+                    .orbit_statistics(true)
+                    .run(),
+            );
+            self.c = Some(c);
+            self.updated_at = get_time();
+            self.texture = None;
+        }
+
+        if self.texture.is_none() || *coloring != self.coloring {
+            self.texture = self.data.as_ref().map(|data| {
+                let image = create_mandelbrot_image(data, JULIA_ITERATION_MAX, coloring)
+                    .expect("the julia preview is smaller than the largest image");
+                Texture2D::from_image(&image)
+            });
+            self.coloring = coloring.clone();
+        }
+    }
+
+    pub fn draw(&self) {
+        let (Some(c), Some(texture)) = (self.c, &self.texture) else {
+            return;
+        };
+        let rect = Self::rect();
+        draw_texture(texture, rect.x, rect.y, WHITE);
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, DARKGRAY);
+        draw_text(
+            &format!("julia c = {c:.4}"),
+            rect.x + 4.0,
+            rect.y + 14.0,
+            16.0,
+            WHITE,
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! The Julia set of the hovered `c` on the right half of the screen, next to the Mandelbrot set.
+//! Both halves show the same part of the complex plane, so zooming into the Mandelbrot set near `c`
+//! zooms into its Julia set too, and the two look alike there.
+//! Moving the mouse over the Julia set keeps the last `c` so it can be looked at
+
+use crate::{
+    MandelbrotRender,
+    coloring::{Coloring, create_mandelbrot_image},
+};
+use macroquad::prelude::*;
+use mandelbrot_core::{EscapeGrid, Julia, RenderJob};
+use num::Complex;
+
+/// Seconds between updates, so sweeping the mouse doesn't calculate a new set every frame
+const JULIA_UPDATE_INTERVAL: f64 = 0.2;
+
+/// What a Julia set was calculated for
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct JuliaView {
+    c: Complex<f32>,
+    center: Complex<f32>,
+    dimensions: Complex<f32>,
+    rotation: f32,
+    iteration_max: usize,
+    width: usize,
+    height: usize,
+}
+
+/// The Julia set drawn beside the main view
+#[derive(Default)]
+pub struct JuliaSplit {
+    /// what `data` was calculated for
+    view: Option<JuliaView>,
+    /// when `data` was last calculated, from `get_time`
+    updated_at: f64,
+    data: Option<EscapeGrid>,
+    /// how `texture` was colored
+    coloring: Coloring,
+    texture: Option<Texture2D>,
+}
+
+impl JuliaSplit {
+    /// Calculate the set of the hovered `c` over the same view as `render`
+    /// if anything changed and it's been long enough since the last update,
+    /// and recolor it if the coloring changed. Call every frame
+    pub fn update(
+        &mut self,
+        c: Option<Complex<f32>>,
+        render: &MandelbrotRender,
+        coloring: &Coloring,
+    ) {
+        // keep showing the last c while nothing is hovered
+        let c = c.or(self.view.map(|view| view.c));
+        if let Some(c) = c {
+            let view = JuliaView {
+                c,
+                center: render.center,
+                dimensions: render.dimensions,
+                rotation: render.rotation,
+                iteration_max: render.iteration_max,
+                width: render.data.width(),
+                height: render.data.height(),
+            };
+            if self.view != Some(view) && get_time() - self.updated_at >= JULIA_UPDATE_INTERVAL {
+                self.data = Some(
+                    RenderJob::new()
+                        .resolution(view.width, view.height)
+                        .center(view.center)
+                        .dimensions(view.dimensions)
+                        .rotation(view.rotation.to_radians() as f64)
+                        .iteration_max(view.iteration_max)
+                        .formula(Julia { c })
+                        .store_paths(false)
+                        .orbit_statistics(true)
+                        .run(),
+                );
+                self.view = Some(view);
+                self.updated_at = get_time();
+                self.texture = None;
+            }
+        }
+
+        if self.texture.is_none() || *coloring != self.coloring {
+            self.texture = self.data.as_ref().zip(self.view).and_then(|(data, view)| {
+                create_mandelbrot_image(data, view.iteration_max, coloring)
+                    .inspect_err(|error| eprintln!("Failed to color the julia set: {error}"))
+                    .ok()
+                    .map(|image| Texture2D::from_image(&image))
+            });
+            self.coloring = coloring.clone();
+        }
+    }
+
+    /// Draw the set starting `x` pixels from the left, with a line between it and the main view
+    pub fn draw(&self, x: f32) {
+        let (Some(view), Some(texture)) = (self.view, &self.texture) else {
+            return;
+        };
+        draw_texture(texture, x, 0.0, WHITE);
+        draw_line(x, 0.0, x, screen_height(), 2.0, WHITE);
+        draw_text(
+            &format!("julia c = {:.4}", view.c),
+            x + 10.0,
+            screen_height() - 10.0,
+            24.0,
+            WHITE,
+        );
+    }
+}
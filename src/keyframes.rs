@@ -0,0 +1,347 @@
+//! Zoom animations between keyframes. Each keyframe is a view, with its iteration limit and coloring,
+//! and the frames between two keyframes interpolate them: the scale on a log scale so the zoom looks steady,
+//! the center so the point being zoomed toward stays put, and the iteration limit and the numbers of the coloring linearly.
+//! The parts of the coloring that aren't numbers, like the palette, switch halfway.
+//! Frames are rendered off screen on another thread, to a numbered PNG sequence for assembling into a video,
+//! or straight to a video through `ffmpeg`, see `video`.
+
+use crate::{
+    coloring::{Coloring, create_mandelbrot_image},
+    png_metadata::write_png,
+    poster::PosterJob,
+    video::VideoEncoder,
+    view::{ViewParams, view_job},
+};
+use macroquad::{
+    prelude::*,
+    ui::{hash, root_ui, widgets::Window},
+};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+const DEFAULT_FRAMES_BETWEEN: usize = 60;
+const FRAMES_BETWEEN_MAX: f32 = 600.0;
+const DEFAULT_FPS: usize = 30;
+const FPS_MAX: f32 = 120.0;
+const DEFAULT_BITRATE: usize = 8000;
+const BITRATE_MIN: f32 = 500.0;
+const BITRATE_MAX: f32 = 50000.0;
+
+/// The sizes frames can be rendered at. `None` is the size of the view
+const RESOLUTIONS: [(&str, Option<(usize, usize)>); 4] = [
+    ("view", None),
+    ("1280x720", Some((1280, 720))),
+    ("1920x1080", Some((1920, 1080))),
+    ("3840x2160", Some((3840, 2160))),
+];
+
+/// `a` when `t` is 0 and `b` when `t` is 1
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// The view `t` of the way from `a` to `b`, where `t` is `0.0..=1.0`
+pub fn interpolate(a: &ViewParams, b: &ViewParams, t: f32) -> ViewParams {
+    let scale = lerp(a.scale.ln(), b.scale.ln(), t).exp();
+    // move the center as far as the width of the view has changed, so the point the zoom heads to stays in the same place
+    let center_progress = if a.scale == b.scale {
+        t
+    } else {
+        (scale.recip() - a.scale.recip()) / (b.scale.recip() - a.scale.recip())
+    };
+    // the keyframe the parts that can't be interpolated come from
+    let from = if t < 0.5 { a } else { b };
+    ViewParams {
+        center: a.center + (b.center - a.center) * center_progress,
+        scale,
+        iteration_max: lerp(a.iteration_max as f32, b.iteration_max as f32, t).round() as usize,
+        rotation: lerp(a.rotation, b.rotation, t),
+        formula: from.formula.clone(),
+        coloring: Coloring {
+            exponent: lerp(a.coloring.exponent, b.coloring.exponent, t),
+            palette_density: lerp(a.coloring.palette_density, b.coloring.palette_density, t),
+            palette_offset: lerp(a.coloring.palette_offset, b.coloring.palette_offset, t),
+            light_azimuth: lerp(a.coloring.light_azimuth, b.coloring.light_azimuth, t),
+            light_elevation: lerp(a.coloring.light_elevation, b.coloring.light_elevation, t),
+            ..from.coloring.clone()
+        },
+    }
+}
+
+/// Frames in the whole animation of `keyframes`, including the first and last
+fn frame_count(keyframes: &[ViewParams], frames_between: usize) -> usize {
+    match keyframes.len() {
+        0 => 0,
+        keyframe_count => (keyframe_count - 1) * frames_between + 1,
+    }
+}
+
+/// The view of frame `frame_index` of the animation of `keyframes`
+fn frame_view(keyframes: &[ViewParams], frames_between: usize, frame_index: usize) -> ViewParams {
+    let last_segment = keyframes.len().saturating_sub(2);
+    let segment = (frame_index / frames_between).min(last_segment);
+    let Some(next) = keyframes.get(segment + 1) else {
+        return keyframes[segment].clone();
+    };
+    let t = (frame_index - segment * frames_between) as f32 / frames_between as f32;
+    interpolate(&keyframes[segment], next, t)
+}
+
+/// Calculate and color `view` at `width` by `height` pixels. Storing `true` in `cancel` stops before the next row
+fn render_frame(
+    view: &ViewParams,
+    width: usize,
+    height: usize,
+    cancel: &AtomicBool,
+) -> Result<Image, String> {
+    let data = view_job(view, width, height)
+        .run_with_progress(|_, _| {}, cancel)
+        .map_err(|error| error.to_string())?;
+    create_mandelbrot_image(&data, view.iteration_max, &view.coloring)
+        .map_err(|error| error.to_string())
+}
+
+/// Where rendered frames go
+enum FrameOutput {
+    /// numbered PNGs in the folder
+    Png(String),
+    Video(VideoEncoder),
+}
+
+/// Render every frame of the animation of `keyframes` at `width` by `height` pixels to `output`.
+/// `frames_done` counts the frames written so far, and storing `true` in `cancel` stops,
+/// keeping the frames rendered so far
+fn render_frames(
+    keyframes: &[ViewParams],
+    frames_between: usize,
+    (width, height): (usize, usize),
+    mut output: FrameOutput,
+    frames_done: &AtomicUsize,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    for frame_index in 0..frame_count(keyframes, frames_between) {
+        let view = frame_view(keyframes, frames_between, frame_index);
+        let written =
+            render_frame(&view, width, height, cancel).and_then(|image| match &mut output {
+                FrameOutput::Png(folder) => {
+                    let path = format!("{folder}/frame_{frame_index:05}.png");
+                    let rgba = png::ColorType::Rgba;
+                    write_png(&path, &image.bytes, width, height, rgba, Some(&view))
+                }
+                FrameOutput::Video(encoder) => encoder.write_frame(&image.bytes),
+            });
+        if cancel.load(Ordering::Relaxed) {
+            return Err(match output {
+                FrameOutput::Png(_) => String::from("Stopped"),
+                FrameOutput::Video(encoder) => match encoder.finish() {
+                    Ok(path) => format!("Stopped, saved {path}"),
+                    Err(error) => error,
+                },
+            });
+        }
+        if let Err(error) = written {
+            if let FrameOutput::Video(encoder) = output {
+                // the frames so far are still a video
+                let _ = encoder.finish();
+            }
+            return Err(error);
+        }
+        frames_done.store(frame_index + 1, Ordering::Relaxed);
+    }
+    match output {
+        FrameOutput::Png(_) => Ok(()),
+        FrameOutput::Video(encoder) => encoder.finish().map(|_| ()),
+    }
+}
+
+/// What the keyframes window shows between frames
+pub struct KeyframeState {
+    keyframes: Vec<ViewParams>,
+    /// index into `keyframes` of the one picked in the list
+    selected: usize,
+    /// frames from one keyframe to the next
+    frames_between: usize,
+    /// index into `RESOLUTIONS` of the size frames are rendered at
+    resolution: usize,
+    /// where the numbered frames are written
+    folder: String,
+    /// where videos are written. The extension picks the container
+    video_path: String,
+    fps: usize,
+    /// kilobits per second of video
+    bitrate: usize,
+    /// the frames being rendered
+    render: Option<PosterJob>,
+    /// the result of the last render
+    status: String,
+}
+
+impl Default for KeyframeState {
+    fn default() -> Self {
+        Self {
+            keyframes: Vec::new(),
+            selected: 0,
+            frames_between: DEFAULT_FRAMES_BETWEEN,
+            resolution: 0,
+            folder: String::from("frames"),
+            video_path: String::from("zoom.mp4"),
+            fps: DEFAULT_FPS,
+            bitrate: DEFAULT_BITRATE,
+            render: None,
+            status: String::new(),
+        }
+    }
+}
+
+impl KeyframeState {
+    /// Frames in the whole animation, including the first and last keyframes
+    fn frame_count(&self) -> usize {
+        frame_count(&self.keyframes, self.frames_between)
+    }
+
+    /// The size frames are rendered at, given the size of the view
+    fn frame_size(&self, view_width: usize, view_height: usize) -> (usize, usize) {
+        RESOLUTIONS[self.resolution]
+            .1
+            .unwrap_or((view_width, view_height))
+    }
+
+    /// Start rendering every frame at `size` to `output` on another thread
+    fn start(&mut self, size: (usize, usize), output: FrameOutput) {
+        let path = match &output {
+            FrameOutput::Png(folder) => folder.clone(),
+            FrameOutput::Video(_) => self.video_path.clone(),
+        };
+        let (keyframes, frames_between) = (self.keyframes.clone(), self.frames_between);
+        let started = PosterJob::spawn(self.frame_count(), &path, move |frames_done, cancel| {
+            render_frames(
+                &keyframes,
+                frames_between,
+                size,
+                output,
+                frames_done,
+                cancel,
+            )
+        });
+        match started {
+            Ok(render) => self.render = Some(render),
+            Err(error) => self.status = error,
+        }
+    }
+}
+
+/// Add `view` as a keyframe, list the keyframes, and render the animation between them.
+/// `view_width` by `view_height` is the size of the view, which frames are rendered at unless another resolution is picked.
+/// Returns the view of a keyframe when the user goes to it
+pub fn keyframes_window(
+    state: &mut KeyframeState,
+    view: impl FnOnce() -> ViewParams,
+    view_width: usize,
+    view_height: usize,
+) -> Option<ViewParams> {
+    if let Some(render) = state.render.take_if(|render| render.is_finished()) {
+        state.status = render.join();
+    }
+
+    let mut recalled = None;
+    Window::new(hash!(), vec2(520.0, 0.0), vec2(250.0, 350.0))
+        .label("keyframes")
+        .titlebar(true)
+        .ui(&mut *root_ui(), |ui| {
+            if ui.button(None, "Add Keyframe") {
+                state.keyframes.push(view());
+                state.selected = state.keyframes.len() - 1;
+            }
+
+            if state.keyframes.is_empty() {
+                ui.label(None, "no keyframes yet");
+            } else {
+                let names = (1..=state.keyframes.len())
+                    .map(|number| format!("keyframe {number}"))
+                    .collect::<Vec<_>>();
+                let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+                state.selected = state.selected.min(names.len() - 1);
+                ui.combo_box(hash!(), "keyframe", &names, &mut state.selected);
+                if ui.button(None, "Go") {
+                    recalled = Some(state.keyframes[state.selected].clone());
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "Delete") && state.render.is_none() {
+                    state.keyframes.remove(state.selected);
+                }
+            }
+
+            // the settings can't change partway through a render
+            let rendering = state.render.is_some();
+            let mut frames_between = state.frames_between as f32;
+            ui.slider(
+                hash!(),
+                "frames between",
+                1.0..FRAMES_BETWEEN_MAX,
+                &mut frames_between,
+            );
+            let mut resolution = state.resolution;
+            let resolution_names = RESOLUTIONS.map(|(name, _)| name);
+            ui.combo_box(hash!(), "resolution", &resolution_names, &mut resolution);
+            let mut fps = state.fps as f32;
+            ui.slider(hash!(), "fps", 1.0..FPS_MAX, &mut fps);
+            let mut bitrate = state.bitrate as f32;
+            ui.slider(hash!(), "kbit/s", BITRATE_MIN..BITRATE_MAX, &mut bitrate);
+            if !rendering {
+                state.frames_between = (frames_between as usize).max(1);
+                state.resolution = resolution;
+                state.fps = (fps as usize).max(1);
+                state.bitrate = bitrate as usize;
+            }
+            ui.input_text(hash!(), "folder", &mut state.folder);
+            ui.input_text(hash!(), "video", &mut state.video_path);
+
+            match &state.render {
+                Some(render) => {
+                    ui.label(
+                        None,
+                        &format!("rendering {:.0}% done", render.progress() * 100.0),
+                    );
+                    if ui.button(None, "Stop") {
+                        render.cancel();
+                    }
+                }
+                None => {
+                    let render_frames = ui.button(None, "Render Frames");
+                    ui.same_line(0.0);
+                    let render_video = ui.button(None, "Render Video");
+                    let (width, height) = state.frame_size(view_width, view_height);
+                    if (render_frames || render_video) && state.keyframes.len() < 2 {
+                        state.status = String::from("Add at least 2 keyframes");
+                    } else if render_frames {
+                        match std::fs::create_dir_all(&state.folder) {
+                            Ok(()) => {
+                                let output = FrameOutput::Png(state.folder.clone());
+                                state.start((width, height), output);
+                            }
+                            Err(error) => {
+                                state.status =
+                                    format!("Failed to create {:?}: {error}", state.folder)
+                            }
+                        }
+                    } else if render_video {
+                        // H.264 in 4:2:0 halves the color resolution, so it needs even sides
+                        let size = ((width & !1).max(2), (height & !1).max(2));
+                        match VideoEncoder::start(
+                            &state.video_path,
+                            size.0,
+                            size.1,
+                            state.fps,
+                            state.bitrate,
+                        ) {
+                            Ok(encoder) => state.start(size, FrameOutput::Video(encoder)),
+                            Err(error) => state.status = error,
+                        }
+                    }
+                }
+            }
+            ui.label(None, &format!("{} frames", state.frame_count()));
+            ui.label(None, &state.status);
+        });
+
+    recalled
+}
@@ -0,0 +1,137 @@
+//! Locations in the `.kfr` format of Kalles Fraktaler, which Fraktaler 3 also reads,
+//! so the many deep zoom locations shared in it can be opened here.
+//! A `.kfr` file is `Key: value` lines. Only the location, rotation, iteration limit, and fractal are read and written,
+//! the coloring is left as it was. Locations deeper than `f32` can resolve open, but render as blocks.
+
+use crate::view::ViewParams;
+use num::Complex;
+
+/// Kalles Fraktaler numbers its fractal types. These are the ones the app has a formula for
+const FRACTAL_TYPES: [(u32, &str); 3] = [(0, "mandelbrot"), (1, "burning ship"), (4, "tricorn")];
+
+/// Kalles Fraktaler's zoom fits `4 / zoom` into the height of the view, while `scale` fits `4 / scale` into its width,
+/// so the height is `4 / (scale * aspect)` and `scale = zoom / aspect`. `aspect` is the width of the view over its height
+fn zoom_to_scale(zoom: f64, aspect: f32) -> f32 {
+    (zoom / aspect as f64) as f32
+}
+
+/// The inverse of `zoom_to_scale`
+fn scale_to_zoom(scale: f32, aspect: f32) -> f64 {
+    scale as f64 * aspect as f64
+}
+
+/// `view` at the location in a `.kfr` file. `aspect` is the width of the view over its height
+pub fn from_kfr(text: &str, aspect: f32, view: ViewParams) -> Result<ViewParams, String> {
+    let value = |key: &str| {
+        text.lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(line_key, _)| line_key.trim().eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.trim())
+    };
+    let number = |key: &str| -> Result<Option<f64>, String> {
+        value(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|error| format!("{key} {value:?} isn't a number: {error}"))
+            })
+            .transpose()
+    };
+
+    let (Some(re), Some(im)) = (number("Re")?, number("Im")?) else {
+        return Err(String::from("The location has no Re and Im"));
+    };
+    let power = number("Power")?.unwrap_or(2.0);
+    if power != 2.0 {
+        return Err(format!("Only power 2 is supported, not {power}"));
+    }
+    let fractal_type = number("FractalType")?.unwrap_or(0.0) as u32;
+    let formula = FRACTAL_TYPES
+        .iter()
+        .find(|(type_number, _)| *type_number == fractal_type)
+        .map(|(_, name)| (*name).to_owned())
+        .ok_or_else(|| format!("Fractal type {fractal_type} isn't supported"))?;
+    let zoom = number("Zoom")?;
+    if let Some(zoom) = zoom
+        && !(zoom.is_finite() && zoom > 0.0)
+    {
+        return Err(format!("Zoom {zoom} isn't a positive number"));
+    }
+
+    Ok(ViewParams {
+        center: Complex::new(re as f32, im as f32),
+        scale: zoom.map_or(view.scale, |zoom| zoom_to_scale(zoom, aspect)),
+        iteration_max: number("Iterations")?
+            .map_or(view.iteration_max, |iterations| iterations as usize),
+        rotation: number("RotateAngle")?.map_or(0.0, |angle| angle as f32),
+        formula,
+        ..view
+    })
+}
+
+/// The location of `view` as a `.kfr` file. `aspect` is the width of the view over its height
+pub fn to_kfr(view: &ViewParams, aspect: f32) -> Result<String, String> {
+    let fractal_type = FRACTAL_TYPES
+        .iter()
+        .find(|(_, name)| *name == view.formula)
+        .map(|(type_number, _)| type_number)
+        .ok_or_else(|| format!("Kalles Fraktaler has no {} formula", view.formula))?;
+    Ok(format!(
+        "Re: {}\r\nIm: {}\r\nZoom: {:E}\r\nIterations: {}\r\nRotateAngle: {}\r\nFractalType: {fractal_type}\r\nPower: 2\r\n",
+        view.center.re,
+        view.center.im,
+        scale_to_zoom(view.scale, aspect),
+        view.iteration_max,
+        view.rotation,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_kfr, to_kfr, zoom_to_scale};
+    use crate::view::ViewParams;
+    use num::Complex;
+
+    #[test]
+    fn zoom_fits_the_height_and_scale_fits_the_width() {
+        // a square view shows the same span either way
+        assert_eq!(zoom_to_scale(1.0, 1.0), 1.0);
+        // a view twice as wide as it is tall needs half the scale to show the same height
+        assert_eq!(zoom_to_scale(8.0, 2.0), 4.0);
+        assert_eq!(zoom_to_scale(1.0, 0.5), 2.0);
+    }
+
+    #[test]
+    fn a_view_survives_a_round_trip() {
+        let view = ViewParams {
+            center: Complex::new(-0.743_643_9, 0.131_825_9),
+            scale: 1234.5,
+            iteration_max: 4000,
+            formula: String::from("burning ship"),
+            rotation: 30.0,
+            ..ViewParams::default()
+        };
+        let aspect = 16.0 / 9.0;
+        let kfr = to_kfr(&view, aspect).unwrap();
+        let read = from_kfr(&kfr, aspect, ViewParams::default()).unwrap();
+        assert!((read.scale - view.scale).abs() / view.scale < 1e-6);
+        assert_eq!(
+            ViewParams {
+                scale: view.scale,
+                ..read
+            },
+            view
+        );
+    }
+
+    #[test]
+    fn zoom_must_be_positive() {
+        for zoom in ["0", "-2", "inf", "NaN"] {
+            let kfr = format!("Re: 0\r\nIm: 0\r\nZoom: {zoom}\r\n");
+            assert!(
+                from_kfr(&kfr, 1.0, ViewParams::default()).is_err(),
+                "{zoom}"
+            );
+        }
+    }
+}
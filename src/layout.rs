@@ -0,0 +1,115 @@
+//! Where the controls window is and whether it's collapsed.
+//! The layout is kept in a JSON file next to the app, so the window reopens where it was left instead of covering the image
+
+use macroquad::{prelude::*, ui::Id};
+use serde::{Deserialize, Serialize};
+
+const LAYOUT_PATH: &str = "mandelbrot_layout.json";
+
+/// there is no file system to save to in the browser, so the layout resets with the tab
+const LAYOUT_SAVED: bool = cfg!(not(target_arch = "wasm32"));
+
+/// The size of the controls window, before the UI scale
+const EXPANDED_SIZE: Vec2 = vec2(250.0, 300.0);
+const COLLAPSED_SIZE: Vec2 = vec2(150.0, 60.0);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlsLayout {
+    /// the top left corner of the window, in screen coordinates
+    position: [f32; 2],
+    collapsed: bool,
+    /// where the title bar was grabbed, relative to `position`, while it's being dragged
+    #[serde(skip)]
+    grab: Option<Vec2>,
+    /// counts the times the window collapsed or expanded. Each is a new window, so it opens at `position`
+    #[serde(skip)]
+    generation: usize,
+}
+
+impl Default for ControlsLayout {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 0.0],
+            collapsed: false,
+            grab: None,
+            generation: 0,
+        }
+    }
+}
+
+impl ControlsLayout {
+    /// The layout saved by the last session, moved onto the screen, or the default if nothing has been saved yet
+    pub fn load() -> Self {
+        if !LAYOUT_SAVED {
+            return Self::default();
+        }
+        let loaded = match std::fs::read_to_string(LAYOUT_PATH) {
+            Ok(json) => serde_json::from_str(&json).map_err(|error| error.to_string()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.to_string()),
+        };
+        let mut layout = loaded.unwrap_or_else(|error| {
+            eprintln!("Failed to load the window layout: {error}");
+            Self::default()
+        });
+        // the screen may have shrunk since the layout was saved. Keep enough of the window on it to drag it back
+        let position_max = (vec2(screen_width(), screen_height()) - COLLAPSED_SIZE).max(Vec2::ZERO);
+        layout.position = layout.position().clamp(Vec2::ZERO, position_max).into();
+        layout
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if !LAYOUT_SAVED {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        std::fs::write(LAYOUT_PATH, json).map_err(|error| error.to_string())
+    }
+
+    pub fn position(&self) -> Vec2 {
+        Vec2::from(self.position)
+    }
+
+    pub fn size(&self, ui_scale: f32) -> Vec2 {
+        let size = if self.collapsed {
+            COLLAPSED_SIZE
+        } else {
+            EXPANDED_SIZE
+        };
+        size * ui_scale
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        if collapsed != self.collapsed {
+            self.collapsed = collapsed;
+            self.generation += 1;
+        }
+    }
+
+    /// The id of the window to show
+    pub fn window_id(&self) -> Id {
+        macroquad::hash!("controls", self.generation)
+    }
+
+    /// Follow the window while its title bar is dragged, since macroquad doesn't say where its windows are.
+    /// Call once per frame before the window is shown
+    pub fn track_drag(&mut self, ui_scale: f32, title_height: f32) {
+        let mouse_position = Vec2::from(mouse_position());
+        let position = self.position();
+        let title_bar = Rect::new(position.x, position.y, self.size(ui_scale).x, title_height);
+        if is_mouse_button_pressed(MouseButton::Left) && title_bar.contains(mouse_position) {
+            self.grab = Some(mouse_position - position);
+        }
+        if let Some(grab) = self.grab {
+            self.position = (mouse_position - grab).into();
+        }
+        if !is_mouse_button_down(MouseButton::Left) {
+            self.grab = None;
+        }
+    }
+}
@@ -1,29 +1,111 @@
+mod animation;
+mod bookmark_previews;
+mod bookmarks;
+mod clipboard;
+mod coloring;
+mod colorizer;
+mod contour;
+mod deep_zoom;
+mod dimension;
+mod error;
+mod export;
+mod formula;
+mod gamepad;
+mod headless;
+mod help;
+mod histogram;
+mod icon;
+mod julia_preview;
+mod julia_split;
+mod keyframes;
+mod kfr;
+mod layout;
+mod measure;
+mod minimap;
+mod navigation;
+mod orbit;
+mod orbit_playback;
+mod palette;
+mod par;
+mod performance;
+mod period;
+mod png_metadata;
+mod poster;
+mod preferences;
+mod profiler;
+mod raw_export;
+mod render_task;
+mod session;
+mod shading;
+mod share;
+mod simplify;
+mod suggest;
+mod table;
+mod tutorial;
+mod ui_scale;
+mod video;
+mod view;
+
+use animation::frame_delta;
+use bookmarks::{BookmarksState, bookmarks_window};
+use coloring::{Coloring, create_mandelbrot_image};
+use colorizer::{COLORIZERS, iteration_color};
+use contour::create_contour_image;
+use dimension::{DimensionEstimate, draw_dimension_plot, estimate_box_counting_dimension};
+use export::{ExportState, export_window};
+use formula::{FORMULAS, FormulaEntry, find_formula, formula_window};
+use gamepad::GamepadInput;
+use help::draw_help;
+use histogram::EscapeHistogram;
+use julia_preview::JuliaPreview;
+use julia_split::JuliaSplit;
+use keyframes::{KeyframeState, keyframes_window};
+use layout::ControlsLayout;
 use macroquad::{
     color::hsl_to_rgb,
     miniquad::window::screen_size,
     prelude::*,
-    ui::{hash, root_ui, widgets::Window},
+    ui::{Ui, hash, root_ui, widgets::Window},
+};
+use mandelbrot_core::{EscapeGrid, FractalFormula, RenderJob};
+use measure::Measurement;
+use minimap::Minimap;
+use navigation::{
+    BoxZoom, CoordinateEntry, Debounce, DragPan, KeyboardCommand, KeyboardFocus, KeyboardSteps,
+    PinchZoom, ViewHistory, VisitedView, box_zoom_factor, keyboard_command, navigation_window,
+    wheel_zoom,
 };
-use mandelbrot::calculate_mandelbrot_escape_times_and_paths;
 use num::Complex;
-use rayon::iter::{
-    IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
+use orbit::{HoveredPoint, OrbitColors, OrbitSource, OrbitStyle, PinnedOrbits, past_escape};
+use orbit_playback::OrbitPlayback;
+use palette::Palette;
+use performance::draw_performance_stats;
+use period::draw_bulb_labels;
+use poster::render_poster;
+use preferences::Preferences;
+use profiler::{IterationProfile, create_cost_image};
+use render_task::{RenderTask, Reuse};
+use session::{Autosave, SavedSession, Session, recovery_window};
+use share::{ShareState, share_window};
+use simplify::{limit_vertices, simplify_polyline};
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
 };
+use suggest::suggest_palette;
+use tutorial::{Tutorial, tutorial_window};
+use ui_scale::{UI_SCALE_MAX, UI_SCALE_MIN, UiScale, detected_ui_scale};
+use view::ViewParams;
 
-fn rgba_to_array(color: Color) -> [u8; 4] {
-    [
-        (color.r * 255.0) as _,
-        (color.g * 255.0) as _,
-        (color.b * 255.0) as _,
-        (color.a * 255.0) as _,
-    ]
-}
-
+/// `rotation` is how far the view is turned around its center, in degrees counterclockwise
 fn complex_to_screen_coordinate(
     z: Complex<f32>,
     center: Complex<f32>,
     dimensions: Complex<f32>,
+    rotation: f32,
 ) -> Vec2 {
+    // turn z back the other way, so the rest is the same as for an upright view
+    let z = center + (z - center) * Complex::cis(-rotation.to_radians());
     let top_left = Complex::new(
         center.re - dimensions.re / 2.0,
         center.im + dimensions.im / 2.0,
@@ -32,121 +114,1133 @@ fn complex_to_screen_coordinate(
     let x_percent = (z.re - top_left.re) / dimensions.re;
     let y_percent = 1.0 - (top_left.im - z.im) / dimensions.im;
 
-    let x = x_percent * screen_width();
-    let y = y_percent * screen_height();
+    let view_size = view_size(dimensions);
+    let x = x_percent * view_size.x;
+    let y = y_percent * view_size.y;
 
     vec2(x, y)
 }
 
-fn calculate_complex_dimensions(scale: f32) -> Complex<f32> {
+fn screen_to_complex_coordinate(
+    screen_position: Vec2,
+    center: Complex<f32>,
+    dimensions: Complex<f32>,
+    rotation: f32,
+) -> Complex<f32> {
+    // inverse of `complex_to_screen_coordinate`
+    let bottom_left = Complex::new(
+        center.re - dimensions.re / 2.0,
+        center.im - dimensions.im / 2.0,
+    );
+
+    let view_size = view_size(dimensions);
+    let x_percent = screen_position.x / view_size.x;
+    let y_percent = screen_position.y / view_size.y;
+
+    let upright = Complex::new(
+        bottom_left.re + x_percent * dimensions.re,
+        bottom_left.im + y_percent * dimensions.im,
+    );
+    center + (upright - center) * Complex::cis(rotation.to_radians())
+}
+
+/// The size on screen of the view showing `dimensions` of the complex plane.
+/// Views are as tall as the screen, and as wide as their aspect ratio makes them
+fn view_size(dimensions: Complex<f32>) -> Vec2 {
+    vec2(
+        screen_height() * dimensions.re / dimensions.im,
+        screen_height(),
+    )
+}
+
+/// How wide each view is on screen. Comparing formulas or showing the Julia set beside the view
+/// splits the screen between two views
+fn view_width(settings: &Settings) -> f32 {
+    if settings.compare_formula.is_some() || settings.julia_side_by_side {
+        screen_width() / 2.0
+    } else {
+        screen_width()
+    }
+}
+
+fn calculate_complex_dimensions(scale: f32, settings: &Settings) -> Complex<f32> {
+    complex_dimensions(scale, view_width(settings), screen_height())
+}
+
+/// The zoom slider goes up to a scale of 10 to this power
+const SCALE_EXPONENT_MAX: f32 = 14.0;
+
+/// Each halving of the pixel size adds this many iterations with `auto_iterations`
+const AUTO_ITERATIONS_PER_OCTAVE: f32 = 64.0;
+const AUTO_ITERATIONS_MIN: usize = 100;
+const AUTO_ITERATIONS_MAX: usize = 50_000;
+
+/// The iteration limit for pixels `pixel_size` wide on the complex plane, proportional to `-log₂(pixel_size)`.
+/// Deeper zooms need longer orbits to tell apart the points near the boundary that they resolve
+fn auto_iteration_max(pixel_size: f32) -> usize {
+    let iteration_max = -pixel_size.log2() * AUTO_ITERATIONS_PER_OCTAVE;
+    (iteration_max.max(0.0) as usize).clamp(AUTO_ITERATIONS_MIN, AUTO_ITERATIONS_MAX)
+}
+
+/// How wide the view is at scale 1, the default view of the Mandelbrot set
+const BASE_WIDTH: f32 = 4.0;
+
+/// The size of the complex plane shown by a `width` by `height` pixel image
+fn complex_dimensions(scale: f32, width: f32, height: f32) -> Complex<f32> {
     // Treat scale as a zoom level. Larger values = zoom in
-    const BASE_WIDTH: f32 = 4.0; // default view of the Mandelbrot set
-    let base_height = BASE_WIDTH * screen_height() / screen_width(); // maintain aspect ratio
+    let base_height = BASE_WIDTH * height / width; // maintain aspect ratio
 
     Complex::new(BASE_WIDTH, base_height) / scale
 }
 
+/// The scale that shows `dimensions`, the inverse of `complex_dimensions`
+fn dimensions_scale(dimensions: Complex<f32>) -> f32 {
+    BASE_WIDTH / dimensions.re
+}
+
+/// The view at `center`, `scale`, and `iteration_max`, drawn with the formula, coloring, and rotation in `settings`
+fn view_params(
+    center: Complex<f32>,
+    scale: f32,
+    iteration_max: usize,
+    settings: &Settings,
+) -> ViewParams {
+    ViewParams {
+        center,
+        scale,
+        iteration_max,
+        formula: settings.formula.clone(),
+        coloring: settings.coloring.clone(),
+        rotation: settings.rotation,
+    }
+}
+
+/// Go to `view`, taking its formula, coloring, and rotation into `settings` and keeping the rest of the settings
+fn apply_view(
+    view: ViewParams,
+    center: &mut Complex<f32>,
+    scale: &mut f32,
+    iteration_max: &mut usize,
+    settings: &mut Settings,
+) {
+    // every field is named, so a new one can't be left out
+    let ViewParams {
+        center: view_center,
+        scale: view_scale,
+        iteration_max: view_iteration_max,
+        formula,
+        coloring,
+        rotation,
+    } = view;
+    *center = view_center;
+    *scale = view_scale;
+    *iteration_max = view_iteration_max;
+    settings.formula = formula;
+    settings.coloring = coloring;
+    settings.rotation = rotation;
+}
+
 fn serialize_index(row_index: usize, column_index: usize, width: usize) -> usize {
     row_index * width + column_index
 }
 
-fn calculate_pixel_index(screen_position: Vec2) -> usize {
+/// Everything produced by one run of the mandelbrot calculation
+struct MandelbrotRender {
+    /// The escape time, last z, and (unless only escape times were stored) z values of each pixel
+    data: EscapeGrid,
+    iteration_max: usize,
+    /// the middle of the view on the complex plane
+    center: Complex<f32>,
+    /// the size of the view on the complex plane
+    dimensions: Complex<f32>,
+    /// how far the view is turned around `center`, in degrees counterclockwise
+    rotation: f32,
+    formula: &'static FormulaEntry,
+    /// how `data` was calculated. Used to recalculate paths that weren't stored
+    job: RenderJob<f32, &'static (dyn FractalFormula<f32> + Send)>,
+    /// how `texture` was colored
+    coloring: Coloring,
+    /// the colored pixels `texture` was made from, kept to save screenshots
+    image: Image,
+    texture: Texture2D,
+    /// equipotential lines and the spacing they were drawn with. created when first needed
+    contour_overlay: Option<(f32, Texture2D)>,
+    /// box-counting dimension of the boundary in view. created on request
+    dimension_estimate: Option<DimensionEstimate>,
+    /// iterations spent per screen region. created when first needed
+    iteration_profile: Option<IterationProfile>,
+    /// how many pixels escaped after how many iterations. created when first needed
+    escape_histogram: Option<EscapeHistogram>,
+    /// the iterations each pixel took. created when first needed
+    cost_overlay: Option<Texture2D>,
+    /// the same view rendered with the comparison formula, drawn to the right of this one
+    comparison: Option<Box<MandelbrotRender>>,
+    /// the next view, calculated a few rows each frame and shown in place of this one once it's finished
+    pending: Option<RenderTask>,
+    /// seconds from starting to finishing the calculation of `data`, and of the comparison's
+    render_seconds: f64,
+}
+
+impl MandelbrotRender {
+    /// Calculate a view all at once, before the window can draw anything else
+    fn new(
+        center: Complex<f32>,
+        dimensions: Complex<f32>,
+        iteration_max: usize,
+        settings: &Settings,
+    ) -> Self {
+        let mut render = Self::generate_formula(
+            center,
+            dimensions,
+            iteration_max,
+            find_formula(&settings.formula),
+            settings,
+        );
+        render.comparison = settings.compare_formula.as_deref().map(|name| {
+            Box::new(Self::generate_formula(
+                center,
+                dimensions,
+                iteration_max,
+                find_formula(name),
+                settings,
+            ))
+        });
+        render
+    }
+
+    fn generate_formula(
+        center: Complex<f32>,
+        dimensions: Complex<f32>,
+        iteration_max: usize,
+        formula: &'static FormulaEntry,
+        settings: &Settings,
+    ) -> Self {
+        let job = Self::formula_job(center, dimensions, iteration_max, formula, settings);
+        let start_time = get_time();
+        let data = job.run();
+        let mut render = Self::from_data(
+            job,
+            data,
+            center,
+            dimensions,
+            settings.rotation,
+            formula,
+            &settings.coloring,
+        );
+        render.render_seconds = get_time() - start_time;
+        render
+    }
+
+    /// How to calculate the view of one formula at the size of the window
+    fn formula_job(
+        center: Complex<f32>,
+        dimensions: Complex<f32>,
+        iteration_max: usize,
+        formula: &'static FormulaEntry,
+        settings: &Settings,
+    ) -> RenderJob<f32, &'static (dyn FractalFormula<f32> + Send)> {
+        RenderJob::new()
+            .resolution(view_width(settings) as usize, screen_height() as usize)
+            .center(center)
+            .dimensions(dimensions)
+            .rotation(settings.rotation.to_radians() as f64)
+            .iteration_max(iteration_max)
+            .formula(&*formula.formula)
+            .store_paths(!settings.escape_times_only)
+            // colorings that summarize the orbit can't use the path when it isn't stored
+            .orbit_statistics(settings.escape_times_only)
+    }
+
+    /// Start calculating a new view, replacing any view that's still being calculated.
+    /// This view stays on screen until the new one is finished
+    fn generate(
+        &mut self,
+        center: Complex<f32>,
+        dimensions: Complex<f32>,
+        iteration_max: usize,
+        settings: &Settings,
+    ) {
+        self.pending = Some(RenderTask::new(center, dimensions, iteration_max, settings));
+    }
+
+    /// Calculate some more of the pending view, and show it if it's finished. Call every frame
+    fn update_pending(&mut self, coloring: &Coloring) {
+        if let Some(pending) = &mut self.pending
+            && pending.step(coloring)
+        {
+            let pending = self.pending.take().expect("checked above");
+            *self = pending.finish(coloring);
+        }
+    }
+
+    /// Stop calculating the pending view, keeping this one
+    fn cancel_pending(&mut self) {
+        self.pending = None;
+    }
+
+    /// A coarse version of the pending view and the size to stretch it to, once one has been calculated
+    fn preview(&self) -> Option<(&Texture2D, Vec2)> {
+        let pending = self.pending.as_ref()?;
+        let (width, height) = pending.size();
+        Some((pending.preview()?, vec2(width as f32, height as f32)))
+    }
+
+    /// How much of the pending view has been calculated, from 0 to 1. `None` if nothing is pending
+    fn progress(&self) -> Option<f32> {
+        self.pending.as_ref().map(RenderTask::progress)
+    }
+
+    /// The center, dimensions, and iteration limit of the view being calculated, or of this view if nothing is pending
+    fn target(&self) -> (Complex<f32>, Complex<f32>, usize) {
+        match &self.pending {
+            Some(pending) => (pending.center, pending.dimensions, pending.iteration_max),
+            None => (self.center, self.dimensions, self.iteration_max),
+        }
+    }
+
+    /// The rotation of the view being calculated, or of this view if nothing is pending
+    fn target_rotation(&self) -> f32 {
+        match &self.pending {
+            Some(pending) => pending.rotation,
+            None => self.rotation,
+        }
+    }
+
+    /// The size in pixels of the view being calculated, or of this view if nothing is pending
+    fn target_size(&self) -> (usize, usize) {
+        match &self.pending {
+            Some(pending) => pending.size(),
+            None => (self.data.width(), self.data.height()),
+        }
+    }
+
+    /// Render again, reusing as much of this render as possible:
+    /// raising only the iteration limit continues the orbits that hadn't escaped instead of starting over,
+    /// and moving only the center (snapped to a whole number of pixels) copies the pixels both views share.
+    /// Generates everything again if anything else about the view changed.
+    /// Either way the new view is calculated in the background like `generate`.
+    /// Returns the center of the new view, which panning snaps
+    fn regenerate(
+        &mut self,
+        center: Complex<f32>,
+        dimensions: Complex<f32>,
+        iteration_max: usize,
+        settings: &Settings,
+    ) -> Complex<f32> {
+        let same_layout = dimensions == self.dimensions
+            && self.rotation == settings.rotation
+            && self.formula.name == settings.formula
+            && self.data.has_paths() != settings.escape_times_only
+            && (self.data.width(), self.data.height())
+                == (view_width(settings) as usize, screen_height() as usize)
+            && self
+                .comparison
+                .as_ref()
+                .map(|comparison| comparison.formula.name)
+                == settings.compare_formula.as_deref();
+        let only_iterations_raised =
+            same_layout && center == self.center && iteration_max >= self.iteration_max;
+        let only_panned =
+            same_layout && center != self.center && iteration_max == self.iteration_max;
+        if !only_iterations_raised && !only_panned {
+            self.generate(center, dimensions, iteration_max, settings);
+            return center;
+        }
+
+        let pan = only_panned.then(|| self.pixel_shift(center));
+        let center = pan.map_or(center, |(center, _, _)| center);
+        let formulas = std::iter::once(&*self)
+            .chain(self.comparison.as_deref())
+            .map(|formula_render| {
+                let grid = formula_render.data.clone();
+                let reuse = match pan {
+                    Some((_, row_shift, column_shift)) => Reuse::Shifted {
+                        grid,
+                        row_shift,
+                        column_shift,
+                    },
+                    None => Reuse::Continued(grid),
+                };
+                let job = formula_render
+                    .job
+                    .clone()
+                    .center(center)
+                    .iteration_max(iteration_max);
+                (job, formula_render.formula, reuse)
+            })
+            .collect();
+        self.pending = Some(RenderTask::reuse(
+            center,
+            self.dimensions,
+            self.rotation,
+            iteration_max,
+            formulas,
+        ));
+        center
+    }
+
+    /// `center` rounded to a whole number of pixels from this view's center, so the pixels both views share line up,
+    /// and how many rows and columns that moves the view
+    fn pixel_shift(&self, center: Complex<f32>) -> (Complex<f32>, isize, isize) {
+        let pixel_size = Complex::new(
+            self.dimensions.re / self.data.width() as f32,
+            self.dimensions.im / self.data.height() as f32,
+        );
+        // the pixels run along the turned view
+        let turn = Complex::cis(self.rotation.to_radians());
+        let shift = (center - self.center) / turn;
+        let column_shift = (shift.re / pixel_size.re).round();
+        let row_shift = (shift.im / pixel_size.im).round();
+        let center = self.center
+            + Complex::new(column_shift * pixel_size.re, row_shift * pixel_size.im) * turn;
+        (center, row_shift as isize, column_shift as isize)
+    }
+
+    /// Color `data` and keep it with how it was calculated
+    fn from_data(
+        job: RenderJob<f32, &'static (dyn FractalFormula<f32> + Send)>,
+        data: EscapeGrid,
+        center: Complex<f32>,
+        dimensions: Complex<f32>,
+        rotation: f32,
+        formula: &'static FormulaEntry,
+        coloring: &Coloring,
+    ) -> Self {
+        // create an image and texture from the mandelbrot data
+        let image = create_mandelbrot_image(&data, data.iteration_max(), coloring)
+            .expect("windows are smaller than the largest image");
+        let texture = Texture2D::from_image(&image);
+
+        Self {
+            iteration_max: data.iteration_max(),
+            data,
+            center,
+            dimensions,
+            rotation,
+            formula,
+            job,
+            coloring: coloring.clone(),
+            image,
+            texture,
+            contour_overlay: None,
+            dimension_estimate: None,
+            iteration_profile: None,
+            escape_histogram: None,
+            cost_overlay: None,
+            comparison: None,
+            pending: None,
+            render_seconds: 0.0,
+        }
+    }
+
+    /// The escape time and path of the pixel under `screen_position`, recalculating the path if it wasn't stored.
+    /// `None` if the position is outside the data, which happens when the window grows until the next render
+    fn pixel_escape_data(
+        &self,
+        screen_position: Vec2,
+    ) -> Option<(Option<usize>, Cow<'_, [Complex<f32>]>)> {
+        let row_index = screen_position.y as usize;
+        let column_index = screen_position.x as usize;
+        if row_index >= self.data.height() || column_index >= self.data.width() {
+            return None;
+        }
+
+        let pixel_index = self.data.pixel_index(row_index, column_index);
+        if let Some(escape_path) = self.data.path(pixel_index) {
+            return Some((
+                self.data.escape_time(pixel_index),
+                Cow::Borrowed(escape_path),
+            ));
+        }
+
+        let pixel = self.job.pixel_coordinate(row_index, column_index);
+        let (escape_time, escape_path) = self.job.escape_path(pixel);
+        Some((escape_time, Cow::Owned(escape_path)))
+    }
+
+    /// Where this render is (or will be once the pending view is finished), to go back to it later
+    fn visited_view(&self) -> VisitedView {
+        let (center, dimensions, iteration_max) = self.target();
+        VisitedView {
+            center,
+            scale: dimensions_scale(dimensions),
+            iteration_max,
+        }
+    }
+
+    /// Recreate the texture from the existing data if the coloring has changed
+    fn update_coloring(&mut self, coloring: &Coloring) {
+        if let Some(comparison) = &mut self.comparison {
+            comparison.update_coloring(coloring);
+        }
+        if &self.coloring == coloring {
+            return;
+        }
+        let image = create_mandelbrot_image(&self.data, self.iteration_max, coloring)
+            .expect("the image was already made from this data");
+        // the size hasn't changed, so the texture can be reused. color cycling recolors every frame
+        self.texture.update(&image);
+        self.image = image;
+        self.coloring = coloring.clone();
+    }
+
+    fn contour_texture(&mut self, spacing: f32) -> &Texture2D {
+        if self
+            .contour_overlay
+            .as_ref()
+            .is_some_and(|(overlay_spacing, _)| *overlay_spacing != spacing)
+        {
+            self.contour_overlay = None;
+        }
+
+        let (_, texture) = self.contour_overlay.get_or_insert_with(|| {
+            let image = create_contour_image(&self.data, spacing, WHITE.with_alpha(0.6))
+                .expect("the image was already made from this data");
+            (spacing, Texture2D::from_image(&image))
+        });
+        texture
+    }
+
+    fn iteration_profile(&mut self, region_size: usize) -> &IterationProfile {
+        if self
+            .iteration_profile
+            .as_ref()
+            .is_some_and(|profile| profile.region_size != region_size)
+        {
+            self.iteration_profile = None;
+        }
+
+        self.iteration_profile.get_or_insert_with(|| {
+            IterationProfile::new(&self.data, region_size, self.iteration_max)
+        })
+    }
+
+    fn cost_texture(&mut self) -> &Texture2D {
+        self.cost_overlay.get_or_insert_with(|| {
+            let image = create_cost_image(&self.data, self.iteration_max)
+                .expect("the image was already made from this data");
+            Texture2D::from_image(&image)
+        })
+    }
+
+    fn escape_histogram(&mut self) -> &EscapeHistogram {
+        self.escape_histogram
+            .get_or_insert_with(|| EscapeHistogram::new(&self.data, self.iteration_max))
+    }
+}
+
+/// Options that only change how things are displayed
+#[derive(Clone, PartialEq)]
+struct Settings {
+    /// whether the hovered orbit comes from the rendered pixel grid or the exact cursor position
+    orbit_source: OrbitSource,
+    /// draw the orbit of the hovered point
+    show_orbit: bool,
+    /// draw orbits one step at a time
+    orbit_playback: bool,
+    orbit_style: OrbitStyle,
+    /// how many more z values of escaped orbits to draw after they escape
+    steps_past_escape: usize,
+    /// draw the circle an orbit has to leave to escape, and mark where the hovered orbit left it
+    show_bailout: bool,
+    /// draw equipotential lines over the image
+    show_contours: bool,
+    /// how many smoothed iterations apart the equipotential lines are
+    contour_spacing: f32,
+    /// write the period of the major bulbs on screen
+    label_bulbs: bool,
+    /// shade screen regions by how many iterations were spent on them
+    show_iteration_profile: bool,
+    /// side length in pixels of the regions the iteration profile is split into
+    profile_region_size: f32,
+    /// color each pixel by how many iterations it took
+    show_cost_heatmap: bool,
+    /// plot how many pixels escaped after how many iterations
+    show_histogram: bool,
+    /// pick `iteration_max` from the zoom level with `auto_iteration_max` instead of the slider
+    auto_iterations: bool,
+    /// render again by itself once the center, scale, or iterations stop changing, instead of waiting for `Generate Image`
+    auto_render: bool,
+    /// only store the escape time and last z of each pixel, recalculating paths when they're needed.
+    /// Uses far less memory at high resolutions and iteration counts. Applies to the next generated image
+    escape_times_only: bool,
+    /// how far the navigation keys move the view
+    keyboard_steps: KeyboardSteps,
+    /// draw a map of the whole fractal with the view outlined in the corner
+    show_minimap: bool,
+    /// draw the Julia set of the hovered `c` next to the minimap
+    show_julia_preview: bool,
+    /// draw the Julia set of the hovered `c` over the same view on the right half of the screen.
+    /// Comparing formulas takes its place
+    julia_side_by_side: bool,
+    fullscreen: bool,
+    /// draw the frame rate and how fast the last view rendered
+    show_performance: bool,
+    /// write the escape time of the hovered pixel next to the mouse
+    show_tooltip: bool,
+    /// rotate the palette offset every frame
+    color_cycling: bool,
+    /// palette lengths the offset rotates per second
+    color_cycle_speed: f32,
+    /// take the UI scale from the screen's DPI instead of `ui_scale`
+    auto_ui_scale: bool,
+    /// how much larger to draw the controls and their text
+    ui_scale: f32,
+    /// draw the controls in black and white instead of grays
+    high_contrast: bool,
+    /// how far the view is turned around its center, in degrees counterclockwise
+    rotation: f32,
+    /// name of the entry in `FORMULAS` to render
+    formula: String,
+    /// name of the entry in `FORMULAS` to render next to `formula`, over the same view. `None` shows only `formula`
+    compare_formula: Option<String>,
+    coloring: Coloring,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            orbit_source: OrbitSource::Cursor,
+            show_orbit: true,
+            orbit_playback: false,
+            orbit_style: OrbitStyle::default(),
+            steps_past_escape: 0,
+            show_bailout: false,
+            show_contours: false,
+            contour_spacing: 1.0,
+            label_bulbs: false,
+            show_iteration_profile: false,
+            profile_region_size: 64.0,
+            show_cost_heatmap: false,
+            show_histogram: false,
+            auto_iterations: false,
+            auto_render: true,
+            escape_times_only: false,
+            keyboard_steps: KeyboardSteps::default(),
+            show_minimap: true,
+            show_julia_preview: true,
+            julia_side_by_side: false,
+            fullscreen: false,
+            show_performance: false,
+            show_tooltip: true,
+            color_cycling: false,
+            color_cycle_speed: 0.1,
+            auto_ui_scale: true,
+            ui_scale: 1.0,
+            high_contrast: false,
+            rotation: 0.0,
+            formula: String::from(FORMULAS[0].name),
+            compare_formula: None,
+            coloring: Coloring::default(),
+        }
+    }
+}
+
+impl Settings {
+    fn ui_scale(&self) -> f32 {
+        if self.auto_ui_scale {
+            detected_ui_scale()
+        } else {
+            self.ui_scale
+        }
+    }
+}
+
+/// Orbits are simplified until no dropped z value is further than this many pixels from the drawn line
+const ESCAPE_PATH_TOLERANCE: f32 = 0.5;
+
+/// The color of the lines of the hovered orbit
+const ESCAPE_PATH_COLOR: Color = SKYBLUE;
+
+/// Draw a circle at each z value, colored as `style` says, and a line of `line_color` connecting to the next z value.
+/// `to_screen` maps the complex plane to screen positions
+fn draw_escape_path(
+    z_values: &[Complex<f32>],
+    to_screen: impl Fn(Complex<f32>) -> Vec2,
+    line_color: Color,
+    iteration_max: usize,
+    coloring: &Coloring,
+    style: &OrbitStyle,
+) {
+    let points = z_values.iter().map(|&z| to_screen(z)).collect::<Vec<_>>();
+    let mut kept = limit_vertices(
+        simplify_polyline(&points, ESCAPE_PATH_TOLERANCE),
+        style.point_max,
+    );
+    // always mark c
+    if kept.len() > 1 && kept[1] != 1 {
+        kept.insert(1, 1);
+    }
+
+    for segment in kept.windows(2) {
+        let (i, next) = (segment[0], segment[1]);
+        // make size an opacity proportional to the index as a percentage
+        let age = (1.0 - (i as f32 / z_values.len() as f32)).clamp(0.3, 1.0);
+        let dot_color = match style.colors {
+            OrbitColors::Palette => iteration_color(i, iteration_max, coloring),
+            OrbitColors::Solid => line_color,
+            OrbitColors::Rainbow => hsl_to_rgb(i as f32 / z_values.len() as f32, 1.0, 0.5),
+        }
+        .with_alpha(age);
+        let line_color = line_color.with_alpha(age);
+
+        let start = points[i];
+        let end = points[next];
+
+        draw_line(
+            start.x,
+            start.y,
+            end.x,
+            end.y,
+            style.line_thickness * age,
+            line_color,
+        );
+        draw_circle(start.x, start.y, style.dot_size * age, dot_color);
+    }
+}
+
+/// Once `|z|` is larger than this the orbit is guaranteed to escape to ∞
+const BAILOUT_RADIUS: f32 = 2.0;
+
+/// Draw the bailout circle and mark the first z value outside of it
+fn draw_bailout(
+    escape_time: Option<usize>,
+    z_values: &[Complex<f32>],
+    center: Complex<f32>,
+    dimensions: Complex<f32>,
+    rotation: f32,
+) {
+    let origin = complex_to_screen_coordinate(Complex::new(0.0, 0.0), center, dimensions, rotation);
+    let radius = BAILOUT_RADIUS / dimensions.re * view_size(dimensions).x;
+    draw_circle(origin.x, origin.y, radius, WHITE.with_alpha(0.08));
+    draw_circle_lines(origin.x, origin.y, radius, 2.0, WHITE);
+
+    let Some(escape_time) = escape_time else {
+        return;
+    };
+    if let Some(&escaped) = z_values.get(escape_time) {
+        let escaped = complex_to_screen_coordinate(escaped, center, dimensions, rotation);
+        draw_circle_lines(escaped.x, escaped.y, 8.0, 2.0, YELLOW);
+        draw_text(
+            &format!("escaped at z{escape_time}"),
+            escaped.x + 10.0,
+            escaped.y - 10.0,
+            16.0,
+            YELLOW,
+        );
+    }
+}
+
+/// Draw the view rendered with the comparison formula to the right of the main view, with both formulas named
+/// and the hovered c marked in the comparison view too
+fn draw_comparison(comparison: &MandelbrotRender, formula_name: &str, c_screen_position: Vec2) {
+    const FONT_SIZE: f32 = 24.0;
+
+    let view_width = comparison.texture.width();
+    draw_texture(&comparison.texture, view_width, 0.0, WHITE);
+    draw_line(view_width, 0.0, view_width, screen_height(), 2.0, WHITE);
+
+    let label_y = screen_height() - 10.0;
+    draw_text(formula_name, 10.0, label_y, FONT_SIZE, WHITE);
+    draw_text(
+        comparison.formula.name,
+        view_width + 10.0,
+        label_y,
+        FONT_SIZE,
+        WHITE,
+    );
+
+    draw_circle_lines(
+        c_screen_position.x + view_width,
+        c_screen_position.y,
+        6.0,
+        2.0,
+        RED,
+    );
+}
+
+/// A bar filled up to `progress`, from 0 to 1
+fn progress_bar(ui: &mut Ui, progress: f32) {
+    let size = vec2(150.0, 10.0);
+    let mut canvas = ui.canvas();
+    let position = canvas.request_space(size);
+    canvas.rect(
+        Rect::new(position.x, position.y, size.x, size.y),
+        DARKGRAY,
+        LIGHTGRAY,
+    );
+    canvas.rect(
+        Rect::new(position.x, position.y, size.x * progress, size.y),
+        DARKGRAY,
+        DARKGREEN,
+    );
+}
+
+/// The height of the status bar along the bottom of the screen
+const STATUS_BAR_HEIGHT: f32 = 22.0;
+
+/// Draw the palette the orbit's z values are colored with, from `z₀` to `iteration_max`, at the right of the status bar
+fn draw_orbit_legend(iteration_max: usize, coloring: &Coloring) {
+    const FONT_SIZE: f32 = 16.0;
+    const WIDTH: f32 = 150.0;
+    const HEIGHT: f32 = 8.0;
+    let iteration_max_label = iteration_max.to_string();
+    let label_width = measure_text(&iteration_max_label, None, FONT_SIZE as u16, 1.0).width;
+    let right = screen_width() - 6.0;
+    let left = right - label_width - 4.0 - WIDTH;
+    let top = screen_height() - (STATUS_BAR_HEIGHT + HEIGHT) / 2.0;
+    let baseline = screen_height() - 6.0;
+
+    // one pixel wide slices of the palette
+    for x in 0..WIDTH as usize {
+        let iteration = x * iteration_max / WIDTH as usize;
+        let color = iteration_color(iteration, iteration_max, coloring);
+        draw_rectangle(left + x as f32, top, 1.0, HEIGHT, color);
+    }
+    draw_rectangle_lines(left, top, WIDTH, HEIGHT, 1.0, GRAY);
+    draw_text("0", left - 12.0, baseline, FONT_SIZE, WHITE);
+    draw_text(
+        &iteration_max_label,
+        right - label_width,
+        baseline,
+        FONT_SIZE,
+        WHITE,
+    );
+}
+
+/// Write `text` in a bar along the bottom of the screen
+fn draw_status_bar(text: &str) {
+    const FONT_SIZE: f32 = 18.0;
+    let top = screen_height() - STATUS_BAR_HEIGHT;
+    draw_rectangle(
+        0.0,
+        top,
+        screen_width(),
+        STATUS_BAR_HEIGHT,
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+    draw_text(text, 6.0, top + STATUS_BAR_HEIGHT - 6.0, FONT_SIZE, WHITE);
+}
+
+/// Write the escape time of the rendered pixel at `screen_position` in a box next to the mouse.
+/// Read from the render, so it costs nothing however high the iteration limit is
+fn draw_escape_time_tooltip(render: &MandelbrotRender, screen_position: Vec2) {
+    const FONT_SIZE: u16 = 16;
+    const PADDING: f32 = 4.0;
+
+    let mouse_position = Vec2::from(mouse_position());
     let row_index = screen_position.y as usize;
     let column_index = screen_position.x as usize;
-    let width = screen_width() as usize;
+    if row_index >= render.data.height()
+        || column_index >= render.data.width()
+        || root_ui().is_mouse_over(mouse_position)
+    {
+        return;
+    }
+    let text = match render
+        .data
+        .escape_time(render.data.pixel_index(row_index, column_index))
+    {
+        Some(escape_time) => format!("escape time {escape_time}"),
+        None => String::from("interior"),
+    };
 
-    serialize_index(row_index, column_index, width)
+    let text_dimensions = measure_text(&text, None, FONT_SIZE, 1.0);
+    let size = vec2(text_dimensions.width, FONT_SIZE as f32) + PADDING * 2.0;
+    // below and to the right of the pointer, unless that would leave the screen
+    let mut corner = mouse_position + 16.0;
+    if corner.x + size.x > screen_width() {
+        corner.x = mouse_position.x - 16.0 - size.x;
+    }
+    if corner.y + size.y > screen_height() - STATUS_BAR_HEIGHT {
+        corner.y = mouse_position.y - 16.0 - size.y;
+    }
+    draw_rectangle(corner.x, corner.y, size.x, size.y, BLACK.with_alpha(0.7));
+    draw_text(
+        &text,
+        corner.x + PADDING,
+        corner.y + PADDING + text_dimensions.offset_y,
+        FONT_SIZE as f32,
+        WHITE,
+    );
 }
 
-fn create_mandelbrot_image(
-    mandelbrot_data: &[(Option<usize>, Vec<Complex<f32>>)],
-    iteration_max: usize,
-) -> Image {
-    // start with a blank image
-    let mut image = Image::gen_image_color(screen_width() as u16, screen_height() as u16, BLACK);
-
-    // update each pixel color in parallel
-    image
-        .get_image_data_mut() // we need the image pixel data to change
-        .par_iter_mut() // we want to edit all pixels at once
-        .zip(mandelbrot_data.par_iter()) // we zip each pixel color with it's mandelbrot data
-        .for_each(|(pixel_color, (escape_time, escape_path))| {
-            let color = match escape_time {
-                &Some(escape_time) => {
-                    let last_z = escape_path.last().expect("all paths start at 0+0i");
-                    let smoothed_iteration = escape_time as f32 + 1.0 - last_z.norm().log2().log2();
-                    let normalized = smoothed_iteration / iteration_max as f32;
-
-                    let hue = (normalized % 1.0).powf(0.7);
-                    let saturation = 1.0;
-                    let luminance = normalized.powf(0.3) * 0.5;
-
-                    rgba_to_array(hsl_to_rgb(hue, saturation, luminance))
-                }
-                None => [0, 0, 0, 255],
-            };
-            *pixel_color = color;
-        });
+/// What the controls window shows between frames
+struct ControlsState {
+    coordinate_entry: CoordinateEntry,
+    layout: ControlsLayout,
+}
 
-    image
+impl Default for ControlsState {
+    fn default() -> Self {
+        Self {
+            coordinate_entry: CoordinateEntry::default(),
+            layout: ControlsLayout::load(),
+        }
+    }
 }
 
 fn controls_window(
     center: &mut Complex<f32>,
     scale: &mut f32,
-    dimensions: &mut Complex<f32>,
+    state: &mut ControlsState,
     iteration_max: &mut usize,
-    mandelbrot_data: &mut Vec<(Option<usize>, Vec<Complex<f32>>)>,
-    image: &mut Image,
-    texture: &mut Texture2D,
+    settings: &mut Settings,
+    pinned_orbits: &mut PinnedOrbits,
+    render: &mut MandelbrotRender,
 ) {
-    let window_size = vec2(250.0, 250.0);
-    let generate_text_dimensions = measure_text("Generate Image", None, 16, 1.0);
-    let generate_button_position = vec2(0.0, window_size.y - generate_text_dimensions.height * 4.0);
-    let reset_button_position = vec2(
-        generate_text_dimensions.width * 1.25,
-        generate_button_position.y,
-    );
-    let c_label_dimensions = measure_text("c: ", None, 16, 1.0);
-    let c_label_position = vec2(
-        0.0,
-        generate_button_position.y - c_label_dimensions.height * 4.0,
-    );
-    Window::new(hash!(), Vec2::ZERO, window_size)
-        .label("controls")
-        .titlebar(true)
-        .ui(&mut *root_ui(), |ui| {
-            ui.slider(hash!(), "Center Real", -2.0..2.0, &mut center.re);
-            ui.slider(hash!(), "Center Imaginary", -2.0..2.0, &mut center.im);
-            ui.slider(hash!(), "Scale", 1.0..1000.0, scale);
+    let layout = &mut state.layout;
+    let window = Window::new(
+        layout.window_id(),
+        layout.position(),
+        layout.size(settings.ui_scale()),
+    )
+    .label("controls")
+    .titlebar(true);
+    if layout.is_collapsed() {
+        let mut expand = false;
+        window.ui(&mut *root_ui(), |ui| {
+            expand = ui.button(None, "Show Controls");
+        });
+        if expand {
+            layout.set_collapsed(false);
+        }
+        return;
+    }
+
+    let mut collapse = false;
+    let coordinate_entry = &mut state.coordinate_entry;
+    window.ui(&mut *root_ui(), |ui| {
+        collapse = ui.button(None, "Hide Controls");
+        ui.slider(hash!(), "Center Real", -2.0..2.0, &mut center.re);
+        ui.slider(hash!(), "Center Imaginary", -2.0..2.0, &mut center.im);
+        // the slider picks the power of ten, so each step zooms as far as the last
+        let scale_exponent = scale.log10();
+        let mut new_scale_exponent = scale_exponent;
+        ui.slider(
+            hash!(),
+            "Zoom 10^x",
+            0.0..SCALE_EXPONENT_MAX,
+            &mut new_scale_exponent,
+        );
+        // converting back and forth would nudge the scale every frame
+        if new_scale_exponent != scale_exponent {
+            *scale = 10.0_f32.powf(new_scale_exponent);
+        }
+        coordinate_entry.ui(ui, center, scale);
+        ui.slider(hash!(), "rotation", -180.0..180.0, &mut settings.rotation);
 
+        ui.checkbox(hash!(), "auto iterations", &mut settings.auto_iterations);
+        if settings.auto_iterations {
+            ui.label(None, &format!("iterations: {iteration_max}"));
+        } else {
             let mut iteration_max_f32 = *iteration_max as f32;
             ui.slider(hash!(), "iterations", 100.0..5000.0, &mut iteration_max_f32);
             *iteration_max = iteration_max_f32 as usize;
+        }
 
-            if let Some(c) = mandelbrot_data
-                .get(calculate_pixel_index(mouse_position().into()))
-                .and_then(|(_, zs)| zs.get(1))
-            {
-                ui.label(c_label_position, &format!("c: {c}"));
+        let mut orbit_source_index = settings.orbit_source as usize;
+        ui.combo_box(
+            hash!(),
+            "orbit source",
+            &OrbitSource::NAMES,
+            &mut orbit_source_index,
+        );
+        settings.orbit_source = OrbitSource::ALL[orbit_source_index];
+        ui.checkbox(hash!(), "orbit", &mut settings.show_orbit);
+        ui.same_line(0.0);
+        ui.checkbox(hash!(), "playback", &mut settings.orbit_playback);
+        // the buttons are numbered, because buttons with the same label are the same button
+        let mut removed = None;
+        for (index, pinned) in pinned_orbits.orbits().iter().enumerate() {
+            if ui.button(None, format!("unpin {}", index + 1).as_str()) {
+                removed = Some(index);
             }
-            if ui.button(generate_button_position, "Generate Image") {
-                *dimensions = calculate_complex_dimensions(*scale);
-                *mandelbrot_data = calculate_mandelbrot_escape_times_and_paths(
-                    screen_width() as usize,
-                    screen_height() as usize,
-                    *center,
-                    *dimensions,
-                    *iteration_max,
-                );
-                *image = create_mandelbrot_image(mandelbrot_data, *iteration_max);
-                *texture = Texture2D::from_image(image);
+            ui.same_line(0.0);
+            ui.label(None, &format!("c = {:.5}", pinned.c));
+        }
+        if let Some(index) = removed {
+            pinned_orbits.remove(index);
+        }
+        if !pinned_orbits.orbits().is_empty() && ui.button(None, "Unpin All") {
+            pinned_orbits.clear();
+        }
+        let mut steps_past_escape_f32 = settings.steps_past_escape as f32;
+        ui.slider(
+            hash!(),
+            "past escape",
+            0.0..20.0,
+            &mut steps_past_escape_f32,
+        );
+        settings.steps_past_escape = steps_past_escape_f32 as usize;
+        if settings.show_orbit {
+            let style = &mut settings.orbit_style;
+            ui.slider(hash!(), "line width", 0.5..5.0, &mut style.line_thickness);
+            ui.slider(hash!(), "dot size", 0.0..8.0, &mut style.dot_size);
+            let mut colors_index = style.colors as usize;
+            ui.combo_box(
+                hash!(),
+                "orbit colors",
+                &OrbitColors::NAMES,
+                &mut colors_index,
+            );
+            style.colors = OrbitColors::ALL[colors_index];
+            // fewer points keep long orbits from slowing every frame down
+            let mut point_max_f32 = style.point_max as f32;
+            ui.slider(hash!(), "orbit points", 50.0..5000.0, &mut point_max_f32);
+            style.point_max = point_max_f32 as usize;
+        }
+        ui.checkbox(hash!(), "bailout circle", &mut settings.show_bailout);
+        ui.checkbox(hash!(), "julia preview", &mut settings.show_julia_preview);
+        ui.checkbox(
+            hash!(),
+            "julia side by side",
+            &mut settings.julia_side_by_side,
+        );
+        ui.checkbox(hash!(), "fullscreen", &mut settings.fullscreen);
+        ui.checkbox(hash!(), "performance", &mut settings.show_performance);
+        ui.checkbox(hash!(), "tooltip", &mut settings.show_tooltip);
+        ui.checkbox(hash!(), "auto ui scale", &mut settings.auto_ui_scale);
+        if !settings.auto_ui_scale {
+            ui.slider(
+                hash!(),
+                "ui scale",
+                UI_SCALE_MIN..UI_SCALE_MAX,
+                &mut settings.ui_scale,
+            );
+        }
+        ui.checkbox(hash!(), "high contrast", &mut settings.high_contrast);
+
+        ui.checkbox(hash!(), "contours", &mut settings.show_contours);
+        if settings.show_contours {
+            ui.slider(
+                hash!(),
+                "contour spacing",
+                0.1..10.0,
+                &mut settings.contour_spacing,
+            );
+        }
+
+        ui.checkbox(hash!(), "label bulbs", &mut settings.label_bulbs);
+
+        ui.checkbox(
+            hash!(),
+            "iteration profile",
+            &mut settings.show_iteration_profile,
+        );
+        if settings.show_iteration_profile {
+            ui.slider(
+                hash!(),
+                "region size",
+                8.0..256.0,
+                &mut settings.profile_region_size,
+            );
+        }
+        ui.checkbox(hash!(), "cost heatmap", &mut settings.show_cost_heatmap);
+        ui.checkbox(hash!(), "escape histogram", &mut settings.show_histogram);
+
+        ui.checkbox(
+            hash!(),
+            "escape times only",
+            &mut settings.escape_times_only,
+        );
+        ui.checkbox(hash!(), "auto render", &mut settings.auto_render);
+
+        let coloring = &mut settings.coloring;
+        let colorizer_names = COLORIZERS
+            .read()
+            .expect("a colorizer panicked while being registered")
+            .names();
+        let mut colorizer_index = colorizer_names
+            .iter()
+            .position(|&name| name == coloring.colorizer)
+            .unwrap_or(0);
+        ui.combo_box(hash!(), "coloring", &colorizer_names, &mut colorizer_index);
+        if colorizer_names[colorizer_index] != coloring.colorizer {
+            coloring.colorizer = colorizer_names[colorizer_index].to_owned();
+        }
+        if coloring.colorizer == "power" {
+            ui.slider(hash!(), "exponent", 0.05..2.0, &mut coloring.exponent);
+        }
+        let mut palette_index = coloring.palette as usize;
+        ui.combo_box(hash!(), "palette", &Palette::NAMES, &mut palette_index);
+        coloring.palette = Palette::ALL[palette_index];
+        ui.slider(
+            hash!(),
+            "palette density",
+            0.1..16.0,
+            &mut coloring.palette_density,
+        );
+        ui.slider(
+            hash!(),
+            "palette offset",
+            0.0..1.0,
+            &mut coloring.palette_offset,
+        );
+        if ui.button(None, "Suggest Palette") {
+            *coloring = suggest_palette(&render.data, render.iteration_max, coloring);
+        }
+        ui.checkbox(hash!(), "color cycling", &mut settings.color_cycling);
+        if settings.color_cycling {
+            ui.slider(
+                hash!(),
+                "cycle speed",
+                0.01..1.0,
+                &mut settings.color_cycle_speed,
+            );
+        }
+
+        ui.checkbox(hash!(), "slope shading", &mut coloring.slope_shading);
+        if coloring.slope_shading {
+            ui.slider(
+                hash!(),
+                "light azimuth",
+                0.0..360.0,
+                &mut coloring.light_azimuth,
+            );
+            ui.slider(
+                hash!(),
+                "light elevation",
+                0.0..90.0,
+                &mut coloring.light_elevation,
+            );
+            if !render.data.has_paths() {
+                ui.label(None, "needs paths, uncheck escape times only");
             }
-            if ui.button(reset_button_position, "Reset") {
-                *scale = 1.0;
-                *center = Complex::new(-0.4, 0.0);
+        }
+
+        if let Some(progress) = render.progress() {
+            progress_bar(ui, progress);
+            ui.label(None, &format!("rendering {:.0}%", progress * 100.0));
+            if ui.button(None, "Cancel") {
+                render.cancel_pending();
             }
-        });
+        }
+        if ui.button(None, "Generate Image") {
+            let dimensions = calculate_complex_dimensions(*scale, settings);
+            // panning snaps the center to whole pixels
+            *center = render.regenerate(*center, dimensions, *iteration_max, settings);
+        }
+        ui.same_line(0.0);
+        if ui.button(None, "Reset") {
+            *scale = 1.0;
+            *center = Complex::new(-0.4, 0.0);
+        }
+
+        let dimension_button_label = match render.dimension_estimate {
+            Some(_) => "Hide Dimension",
+            None => "Estimate Dimension",
+        };
+        if ui.button(None, dimension_button_label) {
+            render.dimension_estimate = match render.dimension_estimate {
+                Some(_) => None,
+                None => estimate_box_counting_dimension(&render.data),
+            };
+        }
+    });
+    if collapse {
+        state.layout.set_collapsed(true);
+    }
 }
 
 fn macroquad_configuration() -> Conf {
@@ -158,13 +1252,78 @@ fn macroquad_configuration() -> Conf {
         high_dpi: true,
         fullscreen: false,
         sample_count: 0,
-        icon: None,
+        icon: Some(icon::window_icon()),
         platform: Default::default(),
     }
 }
 
-#[macroquad::main(macroquad_configuration)]
-async fn main() {
+/// Render the view in the file at `view_path` to a poster at `output`, printing the progress
+fn render_poster_file(
+    view_path: &str,
+    width: &str,
+    height: &str,
+    output: &str,
+) -> Result<(), String> {
+    let parse_size = |size: &str| {
+        size.parse::<usize>()
+            .map_err(|error| format!("The poster size {size:?} isn't a number: {error}"))
+    };
+    let (width, height) = (parse_size(width)?, parse_size(height)?);
+    let view = ViewParams::load(
+        view_path,
+        width as f32 / height.max(1) as f32,
+        ViewParams::default(),
+    )?;
+
+    let rows_done = AtomicUsize::new(0);
+    let finished = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        // report the progress while the poster renders
+        scope.spawn(|| {
+            while !finished.load(Ordering::Relaxed) {
+                eprint!("\r{} of {height} rows", rows_done.load(Ordering::Relaxed));
+                std::thread::sleep(std::time::Duration::from_millis(500));
+            }
+            eprintln!();
+        });
+        let rendered = render_poster(
+            &view,
+            width,
+            height,
+            output,
+            &rows_done,
+            &AtomicBool::new(false),
+        );
+        finished.store(true, Ordering::Relaxed);
+        rendered
+    })?;
+    eprintln!("Saved {output}");
+    Ok(())
+}
+
+fn main() {
+    // answer render requests on stdin instead of opening a window with `--stdio`
+    if std::env::args().any(|argument| argument == "--stdio") {
+        headless::serve();
+        return;
+    }
+    // render a poster of a view file without opening a window with `--poster <view file> <width> <height> <output>`
+    let poster_arguments = std::env::args()
+        .skip_while(|argument| argument != "--poster")
+        .skip(1)
+        .collect::<Vec<_>>();
+    if let [view_path, width, height, output, ..] = poster_arguments.as_slice() {
+        if let Err(error) = render_poster_file(view_path, width, height, output) {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    macroquad::Window::from_config(macroquad_configuration(), run_app());
+}
+
+async fn run_app() {
     /* SETUP */
     // define the area of the complex plane being viewed
     let mut scale = 1.0;
@@ -173,81 +1332,684 @@ async fn main() {
     // define how many iterations of the mandelbrot formula should be performed to determine detail level
     let mut iteration_max = 500;
 
-    let mut dimensions = calculate_complex_dimensions(scale);
-
     // this is the c value in the mandelbrot formula zₙ₊₁ = zₙ² + c.
     let mut c_screen_position = Vec2::ZERO;
 
-    // A collection of (escape_time, z_values).
-    let mut mandelbrot_data = calculate_mandelbrot_escape_times_and_paths(
-        screen_width() as usize,
-        screen_height() as usize,
-        center,
-        dimensions,
-        iteration_max,
-    );
+    let mut settings = Settings::default();
+    Preferences::load().apply(&mut settings);
 
-    // create an image and texture from the mandelbrot_data
-    let mut image = create_mandelbrot_image(&mandelbrot_data, iteration_max);
-    let mut texture = Texture2D::from_image(&image);
+    // pick a colorizer with `--colorizer <name>`
+    if let Some(name) = std::env::args()
+        .skip_while(|argument| argument != "--colorizer")
+        .nth(1)
+    {
+        let colorizers = COLORIZERS
+            .read()
+            .expect("a colorizer panicked while being registered");
+        if colorizers.contains(&name) {
+            settings.coloring.colorizer = name;
+        } else {
+            eprintln!(
+                "Unknown colorizer {name:?}. Available colorizers: {}",
+                colorizers.names().join(", ")
+            );
+        }
+    }
+
+    let mut dimensions = calculate_complex_dimensions(scale, &settings);
+
+    // calculate the mandelbrot data and create an image and texture from it
+    let mut render = MandelbrotRender::new(center, dimensions, iteration_max, &settings);
+
+    // a leftover recovery file means the last run crashed or was killed
+    let mut recovered_session = SavedSession::load();
+    let mut autosave = Autosave::default();
+
+    let mut share_state = ShareState::default();
+    let mut bookmarks_state = BookmarksState::default();
+    let mut keyframe_state = KeyframeState::default();
+    let mut export_state = ExportState::default();
+    let mut tutorial = Tutorial::default();
+    let mut drag_pan = DragPan::default();
+    let mut box_zoom = BoxZoom::default();
+    let mut measurement = Measurement::default();
+    let mut pinch_zoom = PinchZoom::default();
+    let mut gamepad = GamepadInput::default();
+    let mut keyboard_focus = KeyboardFocus::default();
+    let mut show_help = false;
+    // the interesting region picked in the formula window
+    let mut region_index = 0;
+    let mut controls_state = ControlsState::default();
+    let mut minimap = Minimap::new(render.formula, &settings.coloring);
+    let mut julia_preview = JuliaPreview::default();
+    let mut julia_split = JuliaSplit::default();
+    let mut pinned_orbits = PinnedOrbits::default();
+    let mut orbit_playback = OrbitPlayback::default();
+    let mut controls_debounce = Debounce::default();
+    let mut resize_debounce = Debounce::default();
+    // the window starts windowed, and a restored session may ask for fullscreen
+    let mut is_fullscreen = false;
+    let mut ui_scale = UiScale::default();
+    let mut history = ViewHistory::new(render.visited_view());
+
+    // save the session and delete the recovery file on a clean exit
+    prevent_quit();
 
     /* MAIN LOOP */
     loop {
         /* DRAW LOGIC */
         // clear the background each frame
         clear_background(LIGHTGRAY);
+        ui_scale.apply(settings.ui_scale(), settings.high_contrast);
+        keyboard_focus.update();
 
         // draw the mandelbrot picture we generated
-        draw_texture(&texture, 0.0, 0.0, WHITE);
-
-        // draw a circle at each z value and a line connecting to the next z value
-        let z_values = mandelbrot_data
-            .get(calculate_pixel_index(c_screen_position))
-            .map(|(_escape_time, escape_path)| escape_path.as_slice())
-            .unwrap_or(&[]);
-        for i in 0..z_values.len().saturating_sub(1) {
-            // make size an opacity proportional to the index as a percentage
-            let age = (1.0 - (i as f32 / z_values.len() as f32)).clamp(0.3, 1.0);
-            let dot_color = match i {
-                0 => LIGHTGRAY,
-                1 => RED,
-                _ => ORANGE,
+        render.update_pending(&settings.coloring);
+        // follow the mouse while it's dragging the image, until the new view is calculated
+        let drag_offset = drag_pan.offset();
+        // only recolors the stored escape times, nothing is calculated again
+        if settings.color_cycling {
+            settings.coloring.palette_offset = (settings.coloring.palette_offset
+                + frame_delta() * settings.color_cycle_speed)
+                .rem_euclid(1.0);
+        }
+        render.update_coloring(&settings.coloring);
+        if let Some((preview, size)) = render.preview() {
+            draw_texture_ex(
+                preview,
+                drag_offset.x,
+                drag_offset.y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(size),
+                    ..Default::default()
+                },
+            );
+        } else {
+            draw_texture(&render.texture, drag_offset.x, drag_offset.y, WHITE);
+        }
+        // the contours belong to the old view
+        if settings.show_contours && render.preview().is_none() {
+            draw_texture(
+                render.contour_texture(settings.contour_spacing),
+                drag_offset.x,
+                drag_offset.y,
+                WHITE,
+            );
+        }
+        // like the contours, the heatmap belongs to the old view
+        if settings.show_cost_heatmap && render.preview().is_none() {
+            draw_texture(
+                render.cost_texture(),
+                drag_offset.x,
+                drag_offset.y,
+                WHITE.with_alpha(0.85),
+            );
+        }
+        if settings.show_iteration_profile {
+            render
+                .iteration_profile(settings.profile_region_size as usize)
+                .draw();
+        }
+        if settings.label_bulbs {
+            draw_bulb_labels(center, dimensions, settings.rotation, render.formula.bulbs);
+        }
+
+        // find the z values for the hovered c value
+        let rendered_escape_data;
+        let exact_escape_data;
+        let (escape_time, z_values) = match settings.orbit_source {
+            OrbitSource::RenderedPixel => {
+                rendered_escape_data = render.pixel_escape_data(c_screen_position);
+                rendered_escape_data
+                    .as_ref()
+                    .map(|(escape_time, escape_path)| (*escape_time, &**escape_path))
+                    .unwrap_or_default()
+            }
+            OrbitSource::Cursor => {
+                // the image on screen, which may not be the view the controls are set to yet
+                let c = screen_to_complex_coordinate(
+                    c_screen_position,
+                    render.center,
+                    render.dimensions,
+                    render.rotation,
+                );
+                exact_escape_data = render
+                    .job
+                    .clone()
+                    .iteration_max(render.iteration_max)
+                    .escape_path(c);
+                (exact_escape_data.0, exact_escape_data.1.as_slice())
+            }
+        };
+        let hovered = HoveredPoint::new(escape_time, z_values, &*render.formula.formula);
+        // show where escaping orbits fly off to
+        let extended_z_values;
+        let z_values = if escape_time.is_some() && settings.steps_past_escape > 0 {
+            let past_escape_z_values = past_escape(
+                z_values,
+                settings.steps_past_escape,
+                &*render.formula.formula,
+            );
+            extended_z_values = [z_values, &past_escape_z_values].concat();
+            extended_z_values.as_slice()
+        } else {
+            z_values
+        };
+
+        // the tutorial draws its own orbit
+        if tutorial.is_running() {
+            tutorial.draw(
+                center,
+                dimensions,
+                settings.rotation,
+                render.iteration_max,
+                &settings.coloring,
+            );
+        } else if settings.show_orbit {
+            // playback shows every orbit up to the same step
+            let z_values = if settings.orbit_playback {
+                orbit_playback.update(hovered.map(|hovered| hovered.c));
+                orbit_playback.played(z_values)
+            } else {
+                z_values
+            };
+            for pinned in pinned_orbits.orbits() {
+                let pinned_z_values = if settings.orbit_playback {
+                    orbit_playback.played(&pinned.z_values)
+                } else {
+                    &pinned.z_values
+                };
+                draw_escape_path(
+                    pinned_z_values,
+                    |z| complex_to_screen_coordinate(z, center, dimensions, settings.rotation),
+                    pinned.color,
+                    render.iteration_max,
+                    &settings.coloring,
+                    &settings.orbit_style,
+                );
             }
-            .with_alpha(age);
-            let line_color = SKYBLUE.with_alpha(age);
-            let size = 3.0 * age;
+            if settings.show_bailout {
+                draw_bailout(escape_time, z_values, center, dimensions, settings.rotation);
+            }
+            draw_escape_path(
+                z_values,
+                |z| complex_to_screen_coordinate(z, center, dimensions, settings.rotation),
+                ESCAPE_PATH_COLOR,
+                render.iteration_max,
+                &settings.coloring,
+                &settings.orbit_style,
+            );
+        }
 
-            let start = complex_to_screen_coordinate(z_values[i], center, dimensions);
-            let end = complex_to_screen_coordinate(z_values[i + 1], center, dimensions);
+        let render_view = Rect::new(
+            0.0,
+            0.0,
+            render.data.width() as f32,
+            render.data.height() as f32,
+        );
+        box_zoom.draw(render_view);
+        measurement.draw(
+            |c| complex_to_screen_coordinate(c, render.center, render.dimensions, render.rotation),
+            |position| {
+                screen_to_complex_coordinate(
+                    position,
+                    render.center,
+                    render.dimensions,
+                    render.rotation,
+                )
+            },
+        );
+
+        if settings.show_minimap {
+            minimap.update(render.formula, &settings.coloring);
+            minimap.draw(render.center, render.dimensions);
+        }
+
+        // drawn after the overlays, so orbits that leave the left view don't cover the right one
+        if let Some(comparison) = &render.comparison {
+            draw_comparison(comparison, render.formula.name, c_screen_position);
+        } else if settings.julia_side_by_side {
+            julia_split.update(
+                hovered.map(|hovered| hovered.c),
+                &render,
+                &settings.coloring,
+            );
+            julia_split.draw(render.data.width() as f32);
+        }
+
+        if settings.show_julia_preview {
+            julia_preview.update(hovered.map(|hovered| hovered.c), &settings.coloring);
+            julia_preview.draw();
+        }
+        if let Some(hovered) = hovered {
+            draw_status_bar(&hovered.status_line());
+        }
+        if settings.show_tooltip && !tutorial.is_running() {
+            draw_escape_time_tooltip(&render, c_screen_position);
+        }
+        if settings.show_orbit && !tutorial.is_running() {
+            draw_orbit_legend(render.iteration_max, &settings.coloring);
+        }
+        if settings.show_performance {
+            draw_performance_stats(&render);
+        }
+        if show_help {
+            draw_help(&settings.keyboard_steps);
+        }
 
-            draw_line(start.x, start.y, end.x, end.y, size / 3.0, line_color);
-            draw_circle(start.x, start.y, size, dot_color);
+        // the plots stack up from the bottom right corner
+        let plot_size = vec2(280.0, 180.0);
+        let mut plot_position =
+            vec2(screen_width(), screen_height() - STATUS_BAR_HEIGHT) - plot_size - 10.0;
+        if let Some(estimate) = &render.dimension_estimate {
+            draw_dimension_plot(
+                estimate,
+                Rect::new(plot_position.x, plot_position.y, plot_size.x, plot_size.y),
+            );
+            plot_position.y -= plot_size.y + 10.0;
+        }
+        if settings.show_histogram {
+            render.escape_histogram().draw(Rect::new(
+                plot_position.x,
+                plot_position.y,
+                plot_size.x,
+                plot_size.y,
+            ));
         }
 
         /* INPUT LOGIC */
+        if settings.auto_iterations {
+            let pixel_size =
+                calculate_complex_dimensions(scale, &settings).re / view_width(&settings);
+            iteration_max = auto_iteration_max(pixel_size);
+        }
+        controls_state
+            .layout
+            .track_drag(settings.ui_scale(), ui_scale.title_height());
         controls_window(
             &mut center,
             &mut scale,
-            &mut dimensions,
+            &mut controls_state,
             &mut iteration_max,
-            &mut mandelbrot_data,
-            &mut image,
-            &mut texture,
+            &mut settings,
+            &mut pinned_orbits,
+            &mut render,
+        );
+        // `Generate Image` renders the view the controls were set to
+        dimensions = render.target().1;
+        // follow the controls once they've been left alone for a moment, so dragging a slider doesn't render every frame
+        let controls_moved = render.target()
+            != (
+                center,
+                calculate_complex_dimensions(scale, &settings),
+                iteration_max,
+            )
+            || render.target_rotation() != settings.rotation;
+        // the render is the size the window was, so the image and the overlays drawn over it would no longer line up.
+        // the new size is picked up like any other resize
+        if settings.fullscreen != is_fullscreen {
+            set_fullscreen(settings.fullscreen);
+            is_fullscreen = settings.fullscreen;
+        }
+        // Resizing by dragging changes the size every frame, so wait for it to finish
+        let window_size = (view_width(&settings) as usize, screen_height() as usize);
+        if resize_debounce.settled(window_size) && window_size != render.target_size() {
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            render.generate(center, dimensions, iteration_max, &settings);
+        }
+        if controls_debounce.settled((center, scale, iteration_max, settings.rotation))
+            && settings.auto_render
+            && controls_moved
+        {
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            center = render.regenerate(center, dimensions, iteration_max, &settings);
+        }
+
+        tutorial_window(&mut tutorial, &render, &settings.coloring);
+        let exported_view = || view_params(center, scale, iteration_max, &settings);
+        if let Some(view) = export_window(&mut export_state, &render, exported_view) {
+            apply_view(
+                view,
+                &mut center,
+                &mut scale,
+                &mut iteration_max,
+                &mut settings,
+            );
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            render.generate(center, dimensions, iteration_max, &settings);
+        }
+        let history_view = navigation_window(
+            &mut settings.keyboard_steps,
+            &mut settings.show_minimap,
+            &mut measurement.active,
+            &mut history,
         );
+        let compare_formula = settings.compare_formula.clone();
+        if let Some(region) = formula_window(&mut settings, &mut region_index) {
+            center = region.center;
+            scale = region.scale;
+            iteration_max = region.iteration_max;
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            render.generate(center, dimensions, iteration_max, &settings);
+        } else if settings.compare_formula != compare_formula {
+            // splitting or joining the screen changes the shape of the view
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            render.generate(center, dimensions, iteration_max, &settings);
+        }
+
+        // the tutorial and region selection use the left mouse button for themselves
+        let over_minimap =
+            settings.show_minimap && Minimap::rect().contains(mouse_position().into());
+        // shift clicking pins the hovered orbit instead
+        let shift_down = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        if shift_down
+            && is_mouse_button_pressed(MouseButton::Left)
+            && !root_ui().is_mouse_over(mouse_position().into())
+            && let Some(hovered) = hovered
+        {
+            pinned_orbits.pin(hovered.c, z_values.to_vec());
+        }
+        // a second finger turns a drag into a pinch
+        let pinch = pinch_zoom.update();
+        if pinch_zoom.is_pinching() {
+            drag_pan.cancel();
+        }
+        let can_drag = !tutorial.is_running()
+            && !export_state.is_selecting_region()
+            && !over_minimap
+            && !shift_down
+            && !pinch_zoom.is_pinching()
+            && !measurement.active;
+        // the minimap takes its own clicks
+        if !over_minimap {
+            measurement.update(render_view, |position| {
+                screen_to_complex_coordinate(
+                    position,
+                    render.center,
+                    render.dimensions,
+                    render.rotation,
+                )
+            });
+        }
+        if let Some(offset) = drag_pan.update(can_drag) {
+            // the content moved with the mouse, so the center moves the other way
+            let pixel_size = Complex::new(
+                render.dimensions.re / render.data.width() as f32,
+                render.dimensions.im / render.data.height() as f32,
+            );
+            center = render.center
+                - Complex::new(offset.x * pixel_size.re, offset.y * pixel_size.im)
+                    * Complex::cis(render.rotation.to_radians());
+            // the image that was dragged, not a view that's still being calculated
+            center = render.regenerate(center, render.dimensions, iteration_max, &settings);
+        }
+        if settings.show_minimap
+            && let Some(clicked) = minimap.clicked()
+        {
+            center = render.regenerate(clicked, dimensions, iteration_max, &settings);
+        }
+        if let Some(pinch) = pinch {
+            // the point that was between the fingers stays between them
+            let pinched = screen_to_complex_coordinate(
+                pinch.from,
+                render.center,
+                render.dimensions,
+                render.rotation,
+            );
+            scale = dimensions_scale(render.dimensions) * pinch.factor;
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            center = pinched
+                - screen_to_complex_coordinate(
+                    pinch.to,
+                    Complex::new(0.0, 0.0),
+                    dimensions,
+                    settings.rotation,
+                );
+            if settings.auto_iterations {
+                iteration_max = auto_iteration_max(dimensions.re / view_width(&settings));
+            }
+            render.generate(center, dimensions, iteration_max, &settings);
+        }
+        if let Some(selection) = box_zoom.update(render_view) {
+            center = screen_to_complex_coordinate(
+                selection.center(),
+                render.center,
+                render.dimensions,
+                render.rotation,
+            );
+            // zoom relative to what's on screen, even if the scale slider has moved since it was rendered
+            scale = dimensions_scale(render.dimensions) * box_zoom_factor(selection, render_view);
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            if settings.auto_iterations {
+                iteration_max = auto_iteration_max(dimensions.re / view_width(&settings));
+            }
+            render.generate(center, dimensions, iteration_max, &settings);
+        }
+        if let Some(factor) = wheel_zoom(&settings.keyboard_steps) {
+            // the point under the mouse stays under the mouse
+            let mouse_position = Vec2::from(mouse_position());
+            let zoomed =
+                screen_to_complex_coordinate(mouse_position, center, dimensions, settings.rotation);
+            scale *= factor;
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            center = zoomed
+                - screen_to_complex_coordinate(
+                    mouse_position,
+                    Complex::new(0.0, 0.0),
+                    dimensions,
+                    settings.rotation,
+                );
+            if settings.auto_iterations {
+                iteration_max = auto_iteration_max(dimensions.re / view_width(&settings));
+            }
+            render.generate(center, dimensions, iteration_max, &settings);
+        }
+        let command = keyboard_command(&settings.keyboard_steps, &keyboard_focus)
+            .or_else(|| gamepad.command(&settings.keyboard_steps));
+        match command {
+            Some(KeyboardCommand::Pan(direction)) => {
+                // step from the view being calculated, so pressing a key repeatedly doesn't lose steps
+                center = render.target().0
+                    + Complex::new(direction.x * dimensions.re, direction.y * dimensions.im)
+                        * Complex::cis(settings.rotation.to_radians());
+                center = render.regenerate(center, dimensions, iteration_max, &settings);
+            }
+            Some(KeyboardCommand::Zoom(factor)) => {
+                scale *= factor;
+                dimensions = calculate_complex_dimensions(scale, &settings);
+                if settings.auto_iterations {
+                    iteration_max = auto_iteration_max(dimensions.re / view_width(&settings));
+                }
+                render.generate(center, dimensions, iteration_max, &settings);
+            }
+            // the zoom picks the iterations with `auto_iterations`
+            Some(KeyboardCommand::Iterations(step)) if !settings.auto_iterations => {
+                iteration_max = iteration_max.saturating_add_signed(step).max(1);
+                center = render.regenerate(center, dimensions, iteration_max, &settings);
+            }
+            Some(KeyboardCommand::ToggleOrbit) => settings.show_orbit = !settings.show_orbit,
+            Some(KeyboardCommand::ToggleFullscreen) => settings.fullscreen = !settings.fullscreen,
+            Some(KeyboardCommand::ToggleHelp) => show_help = !show_help,
+            Some(KeyboardCommand::Screenshot) => export_state.save_screenshot(
+                &render,
+                &view_params(center, scale, iteration_max, &settings),
+            ),
+            _ => {}
+        }
+
+        if let Some(view) = history_view.or_else(|| history.keyboard_command(command)) {
+            center = view.center;
+            scale = view.scale;
+            iteration_max = view.iteration_max;
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            center = render.regenerate(center, dimensions, iteration_max, &settings);
+            // panning snaps the center, which shouldn't count as visiting another view
+            history.replace_current(render.visited_view());
+        }
+        history.visit(render.visited_view());
 
+        let previous_c_screen_position = c_screen_position;
         c_screen_position = Vec2::from(mouse_position()).clamp(Vec2::ZERO, screen_size().into());
-        if is_mouse_button_pressed(MouseButton::Right) {
-            if let Some(&c) = mandelbrot_data
-                .get(calculate_pixel_index(c_screen_position))
-                .and_then(|(_, zs)| zs.get(1))
-            {
-                center = c;
+        // a right drag zooms to a box instead, so only recenter once the button is released without one
+        if box_zoom.clicked() {
+            if let Some(hovered) = hovered {
+                center = hovered.c;
             }
         }
 
         if let Some(touch) = touches().first() {
             c_screen_position = touch.position.clamp(Vec2::ZERO, screen_size().into());
         }
+        // hovering either view picks the same c. The comparison is drawn one render width to the right,
+        // which isn't half the screen after a resize until the next render
+        let render_width = render.data.width() as f32;
+        if render.comparison.is_some() && c_screen_position.x >= render_width {
+            c_screen_position.x -= render_width;
+        } else if settings.julia_side_by_side && c_screen_position.x >= render_width {
+            // keep the c of the Julia set while the mouse is over it
+            c_screen_position = previous_c_screen_position;
+        }
+
+        /* SESSION LOGIC */
+        let current_session = || Session {
+            center,
+            scale,
+            iteration_max,
+            settings: settings.clone(),
+        };
+        if let Some(shared) = share_window(&mut share_state, current_session) {
+            // only take the view, the rest of the settings are personal preference
+            let view = view_params(
+                shared.center,
+                shared.scale,
+                shared.iteration_max,
+                &shared.settings,
+            );
+            apply_view(
+                view,
+                &mut center,
+                &mut scale,
+                &mut iteration_max,
+                &mut settings,
+            );
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            render.generate(center, dimensions, iteration_max, &settings);
+        }
+        let current_view = || view_params(center, scale, iteration_max, &settings);
+        // previews are rendered while nothing else is, and only replace views without a comparison
+        bookmarks_state.update_previews(
+            render.progress().is_none()
+                && !is_mouse_button_down(MouseButton::Left)
+                && settings.compare_formula.is_none(),
+            |view| {
+                MandelbrotRender::formula_job(
+                    view.center,
+                    calculate_complex_dimensions(view.scale, &settings),
+                    view.iteration_max,
+                    find_formula(&view.formula),
+                    &settings,
+                )
+                .rotation(view.rotation.to_radians() as f64)
+            },
+        );
+        if let Some(view) = bookmarks_window(
+            &mut bookmarks_state,
+            current_view,
+            view_width(&settings) / screen_height(),
+            command,
+        ) {
+            apply_view(
+                view,
+                &mut center,
+                &mut scale,
+                &mut iteration_max,
+                &mut settings,
+            );
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            let formula = find_formula(&settings.formula);
+            let job = MandelbrotRender::formula_job(
+                center,
+                dimensions,
+                iteration_max,
+                formula,
+                &settings,
+            );
+            match bookmarks_state
+                .prerendered(&job)
+                .filter(|_| settings.compare_formula.is_none())
+            {
+                // shown straight away, it was rendered in the background
+                Some(data) => {
+                    render = MandelbrotRender::from_data(
+                        job,
+                        data,
+                        center,
+                        dimensions,
+                        settings.rotation,
+                        formula,
+                        &settings.coloring,
+                    );
+                }
+                None => render.generate(center, dimensions, iteration_max, &settings),
+            }
+        }
+        let current_view = || view_params(center, scale, iteration_max, &settings);
+        if let Some(view) = keyframes_window(
+            &mut keyframe_state,
+            current_view,
+            render.data.width(),
+            render.data.height(),
+        ) {
+            apply_view(
+                view,
+                &mut center,
+                &mut scale,
+                &mut iteration_max,
+                &mut settings,
+            );
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            render.generate(center, dimensions, iteration_max, &settings);
+        }
+        if settings.show_orbit && settings.orbit_playback && !tutorial.is_running() {
+            orbit_playback.window(z_values.len());
+        }
+        if let Some(session) = recovery_window(&mut recovered_session) {
+            center = session.center;
+            scale = session.scale;
+            iteration_max = session.iteration_max;
+            // the preferences aren't part of the session
+            let preferences = Preferences::from_settings(&settings);
+            settings = session.settings;
+            preferences.apply(&mut settings);
+            dimensions = calculate_complex_dimensions(scale, &settings);
+            render.generate(center, dimensions, iteration_max, &settings);
+        }
+        // don't overwrite the recovery file until the user has decided what to do with it
+        if recovered_session.is_none() {
+            autosave.update(|| Session {
+                center,
+                scale,
+                iteration_max,
+                settings: settings.clone(),
+            });
+        }
+        if is_quit_requested() {
+            Session {
+                center,
+                scale,
+                iteration_max,
+                settings: settings.clone(),
+            }
+            .save_on_exit();
+            if let Err(error) = controls_state.layout.save() {
+                eprintln!("Failed to save the window layout: {error}");
+            }
+            if let Err(error) = Preferences::from_settings(&settings).save() {
+                eprintln!("Failed to save the preferences: {error}");
+            }
+            break;
+        }
 
         // this frame is done.
         // tell macroquad it can take control until next frame
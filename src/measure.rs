@@ -0,0 +1,89 @@
+//! Measuring between two points on the complex plane, to estimate the size of a feature or the spacing of a period.
+//! The points are kept on the complex plane, so the measurement stays put while the view moves
+
+use macroquad::{prelude::*, ui::root_ui};
+use num::Complex;
+
+const MEASURE_COLOR: Color = SKYBLUE;
+
+#[derive(Debug, Default)]
+pub struct Measurement {
+    /// whether left clicks place the ends of the measurement
+    pub active: bool,
+    start: Option<Complex<f32>>,
+    /// `None` while the end follows the mouse
+    end: Option<Complex<f32>>,
+}
+
+impl Measurement {
+    /// Place the ends with left clicks inside `view`: the first click starts a measurement, the second finishes it,
+    /// and the next starts a new one. `to_complex` maps screen positions to the complex plane. Call every frame
+    pub fn update(&mut self, view: Rect, to_complex: impl Fn(Vec2) -> Complex<f32>) {
+        if !self.active {
+            self.start = None;
+            self.end = None;
+            return;
+        }
+        let mouse_position = Vec2::from(mouse_position());
+        if !is_mouse_button_pressed(MouseButton::Left)
+            || !view.contains(mouse_position)
+            || root_ui().is_mouse_over(mouse_position)
+        {
+            return;
+        }
+        let clicked = to_complex(mouse_position);
+        match (self.start, self.end) {
+            (Some(_), None) => self.end = Some(clicked),
+            _ => {
+                self.start = Some(clicked);
+                self.end = None;
+            }
+        }
+    }
+
+    /// Draw a line between the ends, labelled with the complex difference, its magnitude, and its length on screen.
+    /// `to_screen` maps the complex plane to screen positions, and `to_complex` is its inverse
+    pub fn draw(
+        &self,
+        to_screen: impl Fn(Complex<f32>) -> Vec2,
+        to_complex: impl Fn(Vec2) -> Complex<f32>,
+    ) {
+        const FONT_SIZE: f32 = 16.0;
+
+        let Some(start) = self.start else {
+            return;
+        };
+        let end = self
+            .end
+            .unwrap_or_else(|| to_complex(Vec2::from(mouse_position())));
+        let (start_position, end_position) = (to_screen(start), to_screen(end));
+        draw_line(
+            start_position.x,
+            start_position.y,
+            end_position.x,
+            end_position.y,
+            2.0,
+            MEASURE_COLOR,
+        );
+        for position in [start_position, end_position] {
+            draw_circle_lines(position.x, position.y, 4.0, 2.0, MEASURE_COLOR);
+        }
+
+        // scientific notation, since deep zooms measure tiny distances
+        let difference = end - start;
+        let lines = [
+            format!("delta = {:.4e} + {:.4e}i", difference.re, difference.im),
+            format!("|delta| = {:.4e}", difference.norm()),
+            format!("{:.1} px", start_position.distance(end_position)),
+        ];
+        for (index, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                end_position.x + 10.0,
+                end_position.y + 10.0 + index as f32 * FONT_SIZE,
+                FONT_SIZE,
+                MEASURE_COLOR,
+            );
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! A small map of the whole fractal in the corner of the screen, with the part in view outlined.
+//! Clicking the map moves the view there
+
+use crate::{
+    STATUS_BAR_HEIGHT,
+    coloring::{Coloring, create_mandelbrot_image},
+    complex_dimensions,
+    formula::FormulaEntry,
+};
+use macroquad::prelude::*;
+use mandelbrot_core::{EscapeGrid, RenderJob};
+use num::Complex;
+
+const MINIMAP_WIDTH: usize = 160;
+const MINIMAP_HEIGHT: usize = 120;
+/// The whole fractal is coarse enough at this size that more iterations wouldn't show
+const MINIMAP_ITERATION_MAX: usize = 200;
+/// The middle of the map, the same as the default view
+const MINIMAP_CENTER: Complex<f32> = Complex::new(-0.4, 0.0);
+/// Pixels between the map and the edges of the screen
+const MINIMAP_MARGIN: f32 = 10.0;
+
+/// The default view of one formula at a low resolution, calculated once and recolored when the coloring changes
+pub struct Minimap {
+    formula: &'static FormulaEntry,
+    data: EscapeGrid,
+    /// how `texture` was colored
+    coloring: Coloring,
+    texture: Texture2D,
+}
+
+impl Minimap {
+    pub fn new(formula: &'static FormulaEntry, coloring: &Coloring) -> Self {
+        let data = RenderJob::new()
+            .resolution(MINIMAP_WIDTH, MINIMAP_HEIGHT)
+            .center(MINIMAP_CENTER)
+            .dimensions(Self::dimensions())
+            .iteration_max(MINIMAP_ITERATION_MAX)
+            .formula(&*formula.formula)
+            .store_paths(false)
+            .orbit_statistics(true)
+            .run();
+        Self {
+            texture: Self::color(&data, coloring),
+            formula,
+            data,
+            coloring: coloring.clone(),
+        }
+    }
+
+    fn color(data: &EscapeGrid, coloring: &Coloring) -> Texture2D {
+        let image = create_mandelbrot_image(data, MINIMAP_ITERATION_MAX, coloring)
+            .expect("the minimap is smaller than the largest image");
+        Texture2D::from_image(&image)
+    }
+
+    /// The size of the map on the complex plane
+    fn dimensions() -> Complex<f32> {
+        complex_dimensions(1.0, MINIMAP_WIDTH as f32, MINIMAP_HEIGHT as f32)
+    }
+
+    /// Where the map is drawn, in the bottom left corner above the status bar
+    pub fn rect() -> Rect {
+        Rect::new(
+            MINIMAP_MARGIN,
+            screen_height() - STATUS_BAR_HEIGHT - MINIMAP_HEIGHT as f32 - MINIMAP_MARGIN,
+            MINIMAP_WIDTH as f32,
+            MINIMAP_HEIGHT as f32,
+        )
+    }
+
+    /// Calculate the map again for another formula, or recolor it. Call every frame
+    pub fn update(&mut self, formula: &'static FormulaEntry, coloring: &Coloring) {
+        if !std::ptr::eq(formula, self.formula) {
+            *self = Self::new(formula, coloring);
+        } else if *coloring != self.coloring {
+            self.texture = Self::color(&self.data, coloring);
+            self.coloring = coloring.clone();
+        }
+    }
+
+    /// Draw the map with the view at `center` that's `dimensions` big outlined
+    pub fn draw(&self, center: Complex<f32>, dimensions: Complex<f32>) {
+        let rect = Self::rect();
+        draw_texture(&self.texture, rect.x, rect.y, WHITE);
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, DARKGRAY);
+
+        // deep zooms would be smaller than a pixel, so the outline has a minimum size
+        const OUTLINE_SIZE_MIN: f32 = 4.0;
+        let top_left = Self::to_screen(center - dimensions / 2.0);
+        let bottom_right = Self::to_screen(center + dimensions / 2.0);
+        let size = (bottom_right - top_left).max(Vec2::splat(OUTLINE_SIZE_MIN));
+        let middle = (top_left + bottom_right) / 2.0;
+        draw_rectangle_lines(
+            middle.x - size.x / 2.0,
+            middle.y - size.y / 2.0,
+            size.x,
+            size.y,
+            2.0,
+            WHITE,
+        );
+    }
+
+    /// Where `c` is drawn on the map
+    fn to_screen(c: Complex<f32>) -> Vec2 {
+        let rect = Self::rect();
+        let dimensions = Self::dimensions();
+        let corner = MINIMAP_CENTER - dimensions / 2.0;
+        vec2(
+            rect.x + (c.re - corner.re) / dimensions.re * rect.w,
+            rect.y + (c.im - corner.im) / dimensions.im * rect.h,
+        )
+    }
+
+    /// The point on the map that was clicked this frame, if any
+    pub fn clicked(&self) -> Option<Complex<f32>> {
+        let mouse_position = Vec2::from(mouse_position());
+        let rect = Self::rect();
+        if !is_mouse_button_pressed(MouseButton::Left) || !rect.contains(mouse_position) {
+            return None;
+        }
+        let dimensions = Self::dimensions();
+        let corner = MINIMAP_CENTER - dimensions / 2.0;
+        let fraction = (mouse_position - rect.point()) / rect.size();
+        Some(Complex::new(
+            corner.re + fraction.x * dimensions.re,
+            corner.im + fraction.y * dimensions.im,
+        ))
+    }
+}
@@ -0,0 +1,701 @@
+//! Moving around the complex plane with the mouse and keyboard
+
+use macroquad::{
+    prelude::*,
+    ui::{Ui, hash, root_ui, widgets::Window},
+};
+use num::Complex;
+use serde::{Deserialize, Serialize};
+
+/// Panning by dragging the image with the left mouse button
+#[derive(Debug, Default)]
+pub struct DragPan {
+    /// where the current drag started, in screen coordinates
+    start: Option<Vec2>,
+}
+
+impl DragPan {
+    /// Start, follow, or finish a drag. Pass `false` for `enabled` while something else uses the left mouse button.
+    /// Returns how far the image was dragged, in screen pixels, when a drag that moved it ends
+    pub fn update(&mut self, enabled: bool) -> Option<Vec2> {
+        let mouse_position = Vec2::from(mouse_position());
+        // dragging a window doesn't pan
+        if enabled
+            && is_mouse_button_pressed(MouseButton::Left)
+            && !root_ui().is_mouse_over(mouse_position)
+        {
+            self.start = Some(mouse_position);
+        }
+        if !is_mouse_button_released(MouseButton::Left) {
+            return None;
+        }
+        let offset = mouse_position - self.start.take()?;
+        // a click that barely moved shouldn't render again
+        (offset.length() >= 1.0).then_some(offset)
+    }
+
+    /// Forget the current drag, e.g. when a second finger turns it into a pinch
+    pub fn cancel(&mut self) {
+        self.start = None;
+    }
+
+    /// How far the image has been dragged so far, to draw it under the cursor until it's calculated again
+    pub fn offset(&self) -> Vec2 {
+        self.start
+            .map(|start| Vec2::from(mouse_position()) - start)
+            .unwrap_or_default()
+    }
+}
+
+/// Zooming by pinching two fingers together or apart, on touch screens without a mouse wheel or keyboard.
+/// A single finger drags like the mouse, because macroquad turns the first touch into mouse events
+#[derive(Debug, Default)]
+pub struct PinchZoom {
+    /// the id and starting position of each touch of the current pinch
+    start: Option<[(u64, Vec2); 2]>,
+    /// where the touches of the current pinch are now
+    current: [Vec2; 2],
+}
+
+/// A finished pinch
+#[derive(Debug, Clone, Copy)]
+pub struct Pinch {
+    /// how many times further apart the fingers ended than they started
+    pub factor: f32,
+    /// the point between the fingers when they touched down, in screen coordinates
+    pub from: Vec2,
+    /// the point between the fingers when they lifted, in screen coordinates
+    pub to: Vec2,
+}
+
+impl PinchZoom {
+    /// Pinches further than this in or out are clamped, so a slip can't zoom to nothing
+    const FACTOR_RANGE: (f32, f32) = (0.05, 20.0);
+
+    /// Start, follow, or finish a pinch. Returns the pinch once either finger lifts
+    pub fn update(&mut self) -> Option<Pinch> {
+        let touches = touches();
+        let is_down =
+            |touch: &Touch| !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled);
+
+        let Some(start) = self.start else {
+            if let [first, second] = touches.as_slice()
+                && is_down(first)
+                && is_down(second)
+            {
+                self.start = Some([(first.id, first.position), (second.id, second.position)]);
+                self.current = [first.position, second.position];
+            }
+            return None;
+        };
+
+        let mut lifted = false;
+        for (current, &(id, _)) in self.current.iter_mut().zip(&start) {
+            match touches.iter().find(|touch| touch.id == id) {
+                Some(touch) => {
+                    *current = touch.position;
+                    lifted |= !is_down(touch);
+                }
+                None => lifted = true,
+            }
+        }
+        if !lifted {
+            return None;
+        }
+        self.start = None;
+
+        let start_distance = start[0].1.distance(start[1].1);
+        // fingers that touched down on the same spot can't tell how far they were pinched
+        (start_distance >= 1.0).then(|| {
+            let (factor_min, factor_max) = Self::FACTOR_RANGE;
+            Pinch {
+                factor: (self.current[0].distance(self.current[1]) / start_distance)
+                    .clamp(factor_min, factor_max),
+                from: (start[0].1 + start[1].1) / 2.0,
+                to: (self.current[0] + self.current[1]) / 2.0,
+            }
+        })
+    }
+
+    pub fn is_pinching(&self) -> bool {
+        self.start.is_some()
+    }
+}
+
+/// Zooming to a rectangle dragged out with the right mouse button
+#[derive(Debug, Default)]
+pub struct BoxZoom {
+    /// where the current drag started, in screen coordinates
+    start: Option<Vec2>,
+    /// the last drag, which ended this frame, was too small to zoom
+    clicked: bool,
+}
+
+impl BoxZoom {
+    /// The smallest box, in screen pixels on each side, that zooms. Anything smaller is a click
+    const SIDE_MIN: f32 = 4.0;
+
+    /// Start, follow, or finish a drag inside the `view` on screen.
+    /// Returns the box, clamped to the `view`, when a drag ends
+    pub fn update(&mut self, view: Rect) -> Option<Rect> {
+        self.clicked = false;
+        let mouse_position = Vec2::from(mouse_position());
+        if is_mouse_button_pressed(MouseButton::Right)
+            && view.contains(mouse_position)
+            && !root_ui().is_mouse_over(mouse_position)
+        {
+            self.start = Some(mouse_position);
+        }
+        if !is_mouse_button_released(MouseButton::Right) {
+            return None;
+        }
+        let selection = self.selection(view)?;
+        self.start = None;
+        let zooms = selection.w >= Self::SIDE_MIN && selection.h >= Self::SIDE_MIN;
+        self.clicked = !zooms;
+        zooms.then_some(selection)
+    }
+
+    /// Whether the right button was released this frame without dragging out a box big enough to zoom
+    pub fn clicked(&self) -> bool {
+        self.clicked
+    }
+
+    /// The box being dragged out, clamped to the `view`
+    fn selection(&self, view: Rect) -> Option<Rect> {
+        let start = self.start?;
+        let end = Vec2::from(mouse_position());
+        let view_end = view.point() + view.size();
+        let low = start.min(end).clamp(view.point(), view_end);
+        let high = start.max(end).clamp(view.point(), view_end);
+        Some(Rect::new(low.x, low.y, high.x - low.x, high.y - low.y))
+    }
+
+    /// Outline the box being dragged out
+    pub fn draw(&self, view: Rect) {
+        if let Some(selection) = self.selection(view) {
+            draw_rectangle_lines(
+                selection.x,
+                selection.y,
+                selection.w,
+                selection.h,
+                2.0,
+                WHITE,
+            );
+        }
+    }
+}
+
+/// How much to zoom in to fit `selection` inside `view`. The shorter side of the selection is expanded
+/// to the shape of the view, so the whole selection stays visible
+pub fn box_zoom_factor(selection: Rect, view: Rect) -> f32 {
+    (view.w / selection.w).min(view.h / selection.h)
+}
+
+/// How far each key press or mouse wheel notch moves the view. Saved with the preferences
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyboardSteps {
+    /// share of the view's width or height the arrow keys pan by
+    pub pan_fraction: f32,
+    /// how many times `+` zooms in and `-` zooms out
+    pub zoom_factor: f32,
+    /// how many times each notch of the mouse wheel zooms in or out
+    pub wheel_zoom_factor: f32,
+    /// how many iterations `]` adds and `[` removes
+    pub iteration_step: usize,
+}
+
+impl Default for KeyboardSteps {
+    fn default() -> Self {
+        Self {
+            pan_fraction: 0.1,
+            zoom_factor: 1.5,
+            wheel_zoom_factor: 1.25,
+            iteration_step: 100,
+        }
+    }
+}
+
+/// A change to the view asked for with the keyboard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyboardCommand {
+    /// move the center by this share of the view's size, in screen directions (y points down)
+    Pan(Vec2),
+    /// multiply the scale by this
+    Zoom(f32),
+    /// add this many iterations
+    Iterations(isize),
+    /// show or hide the hovered orbit
+    ToggleOrbit,
+    /// switch between fullscreen and a window
+    ToggleFullscreen,
+    /// show or hide the list of controls
+    ToggleHelp,
+    /// save the rendered image to a new file
+    Screenshot,
+    /// go back through the view history
+    Back,
+    /// go forward through the view history
+    Forward,
+    /// put the location on the clipboard
+    CopyLocation,
+    /// go to the location on the clipboard
+    PasteLocation,
+}
+
+impl KeyboardCommand {
+    /// What the command does, for the help overlay
+    pub fn description(&self) -> &'static str {
+        match *self {
+            Self::Pan(direction) if direction.x < 0.0 => "pan left",
+            Self::Pan(direction) if direction.x > 0.0 => "pan right",
+            Self::Pan(direction) if direction.y < 0.0 => "pan up",
+            Self::Pan(_) => "pan down",
+            Self::Zoom(factor) if factor >= 1.0 => "zoom in",
+            Self::Zoom(_) => "zoom out",
+            Self::Iterations(step) if step >= 0 => "more iterations",
+            Self::Iterations(_) => "fewer iterations",
+            Self::ToggleOrbit => "show or hide the orbit",
+            Self::ToggleFullscreen => "fullscreen",
+            Self::ToggleHelp => "show or hide this help",
+            Self::Screenshot => "save a screenshot",
+            Self::Back => "previous view",
+            Self::Forward => "next view",
+            Self::CopyLocation => "copy the location",
+            Self::PasteLocation => "go to a copied location",
+        }
+    }
+}
+
+/// Modifier keys a binding needs held down. A binding is skipped while any modifier it doesn't ask for is held,
+/// so ctrl+S doesn't also take a screenshot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    /// ctrl, or command on macOS
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        shift: false,
+        ctrl: false,
+        alt: false,
+    };
+    pub const SHIFT: Self = Self {
+        shift: true,
+        ..Self::NONE
+    };
+    pub const CTRL: Self = Self {
+        ctrl: true,
+        ..Self::NONE
+    };
+
+    /// The modifiers held down this frame
+    pub fn held() -> Self {
+        let any_down = |keys: [KeyCode; 2]| keys.into_iter().any(is_key_down);
+        Self {
+            shift: any_down([KeyCode::LeftShift, KeyCode::RightShift]),
+            ctrl: any_down([KeyCode::LeftControl, KeyCode::RightControl])
+                || any_down([KeyCode::LeftSuper, KeyCode::RightSuper]),
+            alt: any_down([KeyCode::LeftAlt, KeyCode::RightAlt]),
+        }
+    }
+}
+
+/// Whether keys are going to a window, like a text box being typed in, rather than to the view.
+/// Macroquad doesn't say which widget has the keyboard, so clicking inside a window hands the keyboard to it,
+/// and clicking outside every window or pressing Escape hands it back
+#[derive(Debug, Default)]
+pub struct KeyboardFocus {
+    in_window: bool,
+}
+
+impl KeyboardFocus {
+    /// Call once per frame, before the keys are read
+    pub fn update(&mut self) {
+        let clicked = [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+            .into_iter()
+            .any(is_mouse_button_pressed);
+        if clicked {
+            self.in_window = root_ui().is_mouse_over(mouse_position().into());
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.in_window = false;
+        }
+    }
+
+    /// Whether the view's keys should be ignored: a window has the keyboard, or the mouse is over one
+    pub fn keys_taken(&self) -> bool {
+        self.in_window || root_ui().is_mouse_over(mouse_position().into())
+    }
+}
+
+/// The name of a key bound in `bindings`, with the modifiers it needs
+pub fn key_name(key: KeyCode, modifiers: Modifiers) -> String {
+    // the characters shift types on these keys
+    match (key, modifiers) {
+        (KeyCode::Equal, Modifiers::SHIFT) => return String::from("+"),
+        (KeyCode::Slash, Modifiers::SHIFT) => return String::from("?"),
+        _ => {}
+    }
+    let mut name = String::new();
+    if modifiers.ctrl {
+        name.push_str("ctrl+");
+    }
+    if modifiers.alt {
+        name.push_str("alt+");
+    }
+    if modifiers.shift {
+        name.push_str("shift+");
+    }
+    name.push_str(&match key {
+        KeyCode::Left => String::from("left"),
+        KeyCode::Right => String::from("right"),
+        KeyCode::Up => String::from("up"),
+        KeyCode::Down => String::from("down"),
+        KeyCode::Equal => String::from("="),
+        KeyCode::KpAdd => String::from("keypad +"),
+        KeyCode::Minus => String::from("-"),
+        KeyCode::KpSubtract => String::from("keypad -"),
+        KeyCode::RightBracket => String::from("]"),
+        KeyCode::LeftBracket => String::from("["),
+        KeyCode::Slash => String::from("/"),
+        key => format!("{key:?}"),
+    });
+    name
+}
+
+/// How many times to zoom in (or out, under 1) for the mouse wheel this frame, if it turned.
+/// Scrolling over a window scrolls the window instead
+pub fn wheel_zoom(steps: &KeyboardSteps) -> Option<f32> {
+    let (_, notches) = mouse_wheel();
+    if notches == 0.0 || root_ui().is_mouse_over(mouse_position().into()) {
+        return None;
+    }
+    // the browser reports much bigger numbers than one per notch
+    Some(steps.wheel_zoom_factor.powf(notches.signum()))
+}
+
+/// An input and what it does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    /// a key, with the modifiers it needs, read by `keyboard_command`
+    Key(KeyCode, Modifiers, KeyboardCommand),
+    /// a mouse or touch gesture, as (input, what it does). Gestures are followed where they're handled, like `DragPan`,
+    /// so they're only here to be listed
+    Gesture(&'static str, &'static str),
+}
+
+impl Binding {
+    /// The input, as it's written in the help
+    pub fn input_name(&self) -> String {
+        match *self {
+            Self::Key(key, modifiers, _) => key_name(key, modifiers),
+            Self::Gesture(input, _) => String::from(input),
+        }
+    }
+
+    /// What the binding does
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Key(_, _, command) => command.description(),
+            Self::Gesture(_, description) => description,
+        }
+    }
+}
+
+/// Every binding, in the order they're listed in the help
+pub fn bindings(steps: &KeyboardSteps) -> [Binding; 26] {
+    let key = |key, command| Binding::Key(key, Modifiers::NONE, command);
+    let pan = |x: f32, y: f32| KeyboardCommand::Pan(vec2(x, y) * steps.pan_fraction);
+    let zoom_in = KeyboardCommand::Zoom(steps.zoom_factor);
+    let zoom_out = KeyboardCommand::Zoom(steps.zoom_factor.recip());
+    let iteration_step = steps.iteration_step as isize;
+    [
+        key(KeyCode::Left, pan(-1.0, 0.0)),
+        key(KeyCode::Right, pan(1.0, 0.0)),
+        key(KeyCode::Up, pan(0.0, -1.0)),
+        key(KeyCode::Down, pan(0.0, 1.0)),
+        // `+` is shift and `=`, and `=` zooms in too so shift isn't needed
+        Binding::Key(KeyCode::Equal, Modifiers::SHIFT, zoom_in),
+        key(KeyCode::Equal, zoom_in),
+        key(KeyCode::KpAdd, zoom_in),
+        key(KeyCode::Minus, zoom_out),
+        key(KeyCode::KpSubtract, zoom_out),
+        key(
+            KeyCode::RightBracket,
+            KeyboardCommand::Iterations(iteration_step),
+        ),
+        key(
+            KeyCode::LeftBracket,
+            KeyboardCommand::Iterations(-iteration_step),
+        ),
+        key(KeyCode::O, KeyboardCommand::ToggleOrbit),
+        key(KeyCode::F11, KeyboardCommand::ToggleFullscreen),
+        key(KeyCode::S, KeyboardCommand::Screenshot),
+        key(KeyCode::H, KeyboardCommand::ToggleHelp),
+        // `?` is shift and `/`
+        Binding::Key(
+            KeyCode::Slash,
+            Modifiers::SHIFT,
+            KeyboardCommand::ToggleHelp,
+        ),
+        key(KeyCode::Backspace, KeyboardCommand::Back),
+        Binding::Key(
+            KeyCode::Backspace,
+            Modifiers::SHIFT,
+            KeyboardCommand::Forward,
+        ),
+        Binding::Key(KeyCode::C, Modifiers::CTRL, KeyboardCommand::CopyLocation),
+        Binding::Key(KeyCode::V, Modifiers::CTRL, KeyboardCommand::PasteLocation),
+        Binding::Gesture("drag", "pan"),
+        Binding::Gesture("wheel", "zoom at the mouse"),
+        Binding::Gesture("right drag", "zoom to the box"),
+        Binding::Gesture("right click", "center on the mouse"),
+        Binding::Gesture("pinch", "zoom"),
+        Binding::Gesture("shift+click", "pin the orbit"),
+    ]
+}
+
+/// The names of the keys bound to `command`, like `H / ?`
+pub fn command_keys(steps: &KeyboardSteps, command: KeyboardCommand) -> String {
+    bindings(steps)
+        .iter()
+        .filter(|binding| matches!(binding, Binding::Key(_, _, bound) if *bound == command))
+        .map(Binding::input_name)
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+/// The command of the key pressed this frame, if any.
+/// Keys are ignored while a window has the keyboard or the mouse is over one, so typing in a text box doesn't move the view
+pub fn keyboard_command(steps: &KeyboardSteps, focus: &KeyboardFocus) -> Option<KeyboardCommand> {
+    if focus.keys_taken() {
+        return None;
+    }
+
+    let held = Modifiers::held();
+    bindings(steps)
+        .into_iter()
+        .find_map(|binding| match binding {
+            Binding::Key(key, modifiers, command) if modifiers == held && is_key_pressed(key) => {
+                Some(command)
+            }
+            _ => None,
+        })
+}
+
+/// Waits for a value, like the view the controls are set to, to stop changing
+#[derive(Debug)]
+pub struct Debounce<T> {
+    value: Option<T>,
+    /// when `value` last changed, from `get_time`
+    changed_at: f64,
+}
+
+impl<T> Default for Debounce<T> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            changed_at: 0.0,
+        }
+    }
+}
+
+impl<T: PartialEq> Debounce<T> {
+    /// Seconds a value has to stay the same to be settled
+    const DELAY: f64 = 0.3;
+
+    /// Call every frame with the current value. Whether it has stayed the same for `DELAY` seconds
+    pub fn settled(&mut self, value: T) -> bool {
+        if self.value.as_ref() != Some(&value) {
+            self.value = Some(value);
+            self.changed_at = get_time();
+        }
+        get_time() - self.changed_at >= Self::DELAY
+    }
+}
+
+/// Text boxes for typing exact coordinates and zoom levels, which the sliders are too coarse for
+#[derive(Debug, Default)]
+pub struct CoordinateEntry {
+    re: String,
+    im: String,
+    scale: String,
+    /// the center and scale the text was last filled in from
+    shown: Option<(Complex<f32>, f32)>,
+    /// why the last entry was rejected
+    error: String,
+}
+
+impl CoordinateEntry {
+    /// Show the text boxes in `ui`. They follow `center` and `scale` when those change some other way,
+    /// and pressing Enter sets them to the typed values, which can be in scientific notation like `-1.5e-3`
+    pub fn ui(&mut self, ui: &mut Ui, center: &mut Complex<f32>, scale: &mut f32) {
+        if self.shown != Some((*center, *scale)) {
+            self.re = center.re.to_string();
+            self.im = center.im.to_string();
+            self.scale = scale.to_string();
+            self.shown = Some((*center, *scale));
+        }
+
+        ui.input_text(hash!(), "real", &mut self.re);
+        ui.input_text(hash!(), "imaginary", &mut self.im);
+        ui.input_text(hash!(), "zoom", &mut self.scale);
+        if !self.error.is_empty() {
+            ui.label(None, &self.error);
+        }
+
+        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+            match self.parse() {
+                Ok((typed_center, typed_scale)) => {
+                    *center = typed_center;
+                    *scale = typed_scale;
+                    self.error.clear();
+                }
+                Err(error) => self.error = error,
+            }
+        }
+    }
+
+    fn parse(&self) -> Result<(Complex<f32>, f32), String> {
+        let parse = |text: &str, name: &str| match text.trim().parse::<f32>() {
+            Ok(value) if value.is_finite() => Ok(value),
+            _ => Err(format!("{name} isn't a number")),
+        };
+        let center = Complex::new(parse(&self.re, "real")?, parse(&self.im, "imaginary")?);
+        let scale = parse(&self.scale, "zoom")?;
+        if scale <= 0.0 {
+            return Err(String::from("zoom must be positive"));
+        }
+        Ok((center, scale))
+    }
+}
+
+/// A view that was rendered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VisitedView {
+    pub center: Complex<f32>,
+    pub scale: f32,
+    pub iteration_max: usize,
+}
+
+/// The views rendered so far, to go back and forward through like a browser
+#[derive(Debug)]
+pub struct ViewHistory {
+    /// oldest first
+    views: Vec<VisitedView>,
+    /// index into `views` of the view on screen
+    current: usize,
+}
+
+impl ViewHistory {
+    /// The most views remembered. The oldest are forgotten first
+    const LENGTH_MAX: usize = 100;
+
+    pub fn new(view: VisitedView) -> Self {
+        Self {
+            views: vec![view],
+            current: 0,
+        }
+    }
+
+    /// Remember `view` if it isn't the current view. The views that could have been gone forward to are forgotten
+    pub fn visit(&mut self, view: VisitedView) {
+        if self.views[self.current] == view {
+            return;
+        }
+        self.views.truncate(self.current + 1);
+        self.views.push(view);
+        if self.views.len() > Self::LENGTH_MAX {
+            self.views.remove(0);
+        }
+        self.current = self.views.len() - 1;
+    }
+
+    /// Change the current view without visiting a new one, e.g. when going back didn't land exactly on the old view
+    pub fn replace_current(&mut self, view: VisitedView) {
+        self.views[self.current] = view;
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.current > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.current + 1 < self.views.len()
+    }
+
+    /// The view before the current one, which becomes the current one
+    pub fn back(&mut self) -> Option<VisitedView> {
+        self.can_go_back().then(|| {
+            self.current -= 1;
+            self.views[self.current]
+        })
+    }
+
+    /// The view after the current one, which becomes the current one
+    pub fn forward(&mut self) -> Option<VisitedView> {
+        self.can_go_forward().then(|| {
+            self.current += 1;
+            self.views[self.current]
+        })
+    }
+
+    /// The view `Back` or `Forward` goes to, for the command from `keyboard_command`
+    pub fn keyboard_command(&mut self, command: Option<KeyboardCommand>) -> Option<VisitedView> {
+        match command? {
+            KeyboardCommand::Back => self.back(),
+            KeyboardCommand::Forward => self.forward(),
+            _ => None,
+        }
+    }
+}
+
+/// Lists the keys, sets how far they move the view, toggles the minimap and measuring, and goes back and forward through the `history`.
+/// Returns the view to go to when `Back` or `Forward` is clicked
+pub fn navigation_window(
+    steps: &mut KeyboardSteps,
+    show_minimap: &mut bool,
+    measuring: &mut bool,
+    history: &mut ViewHistory,
+) -> Option<VisitedView> {
+    let mut view = None;
+    let window_size = vec2(250.0, 225.0);
+    Window::new(
+        hash!(),
+        vec2(screen_width() - window_size.x, 530.0),
+        window_size,
+    )
+    .label("navigation")
+    .titlebar(true)
+    .ui(&mut *root_ui(), |ui| {
+        let help_keys = command_keys(steps, KeyboardCommand::ToggleHelp);
+        ui.label(None, &format!("{help_keys}: list every control"));
+        ui.slider(hash!(), "pan step", 0.01..0.5, &mut steps.pan_fraction);
+        ui.slider(hash!(), "zoom step", 1.1..4.0, &mut steps.zoom_factor);
+        ui.slider(
+            hash!(),
+            "wheel zoom",
+            1.05..2.0,
+            &mut steps.wheel_zoom_factor,
+        );
+        let mut iteration_step = steps.iteration_step as f32;
+        ui.slider(hash!(), "iteration step", 10.0..1000.0, &mut iteration_step);
+        steps.iteration_step = iteration_step as usize;
+        ui.checkbox(hash!(), "minimap", show_minimap);
+        ui.checkbox(hash!(), "measure", measuring);
+
+        if ui.button(None, "Back") {
+            view = history.back();
+        }
+        ui.same_line(0.0);
+        if ui.button(None, "Forward") {
+            view = history.forward();
+        }
+    });
+    view
+}
@@ -0,0 +1,180 @@
+use crate::{
+    contour::smooth_iteration,
+    period::{PERIOD_MAX, calculate_period},
+    shading::distance_estimate,
+};
+use macroquad::prelude::*;
+use mandelbrot_core::FractalFormula;
+use num::Complex;
+
+/// Where the hovered orbit overlay gets its `c` value from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrbitSource {
+    /// use the path stored in `mandelbrot_data` for the pixel under the cursor
+    RenderedPixel,
+    /// iterate the exact (subpixel) complex coordinate under the cursor
+    Cursor,
+}
+
+impl OrbitSource {
+    pub const ALL: [Self; 2] = [Self::RenderedPixel, Self::Cursor];
+    pub const NAMES: [&str; 2] = ["rendered pixel", "exact cursor"];
+}
+
+/// How the dots at the z values of an orbit are colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrbitColors {
+    /// by iteration, through the palette the image is colored with
+    Palette,
+    /// the color of the orbit's lines
+    Solid,
+    /// through every hue from the first z value to the last
+    Rainbow,
+}
+
+impl OrbitColors {
+    pub const ALL: [Self; 3] = [Self::Palette, Self::Solid, Self::Rainbow];
+    pub const NAMES: [&str; 3] = ["palette", "solid", "rainbow"];
+}
+
+/// How orbits are drawn. Later z values are drawn smaller and fainter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitStyle {
+    /// width in pixels of the lines between z values
+    pub line_thickness: f32,
+    /// radius in pixels of the dot at each z value
+    pub dot_size: f32,
+    pub colors: OrbitColors,
+    /// orbits that settle into a cycle can't be simplified much, so they are thinned out to this many z values
+    pub point_max: usize,
+}
+
+impl Default for OrbitStyle {
+    fn default() -> Self {
+        Self {
+            line_thickness: 1.0,
+            dot_size: 3.0,
+            colors: OrbitColors::Palette,
+            point_max: 1000,
+        }
+    }
+}
+
+/// What is known about the point under the cursor
+#[derive(Debug, Clone, Copy)]
+pub struct HoveredPoint {
+    pub c: Complex<f32>,
+    /// `None` if the orbit never escaped
+    pub escape_time: Option<usize>,
+    /// the continuous escape time, if the orbit escaped
+    pub smooth_iteration: Option<f32>,
+    /// how far the point is from the set, if the orbit escaped and the derivative didn't overflow
+    pub distance: Option<f32>,
+    /// the period of the attracting cycle, if the point is inside the set
+    pub period: Option<usize>,
+}
+
+impl HoveredPoint {
+    /// `escape_path` must start at `z₀ = 0` so that `z₁ = c`, and come from iterating `formula`
+    pub fn new(
+        escape_time: Option<usize>,
+        escape_path: &[Complex<f32>],
+        formula: &dyn FractalFormula<f32>,
+    ) -> Option<Self> {
+        let &c = escape_path.get(1)?;
+        let &last_z = escape_path.last()?;
+        let period = match escape_time {
+            Some(_) => None,
+            None => calculate_period(c, last_z, PERIOD_MAX, formula),
+        };
+        Some(Self {
+            c,
+            escape_time,
+            smooth_iteration: escape_time.map(|escape_time| smooth_iteration(escape_time, last_z)),
+            distance: escape_time.and_then(|_| distance_estimate(escape_path)),
+            period,
+        })
+    }
+
+    /// One line describing the point, e.g. `c = -0.75+0.1i  escaped at 37 (smooth 36.42)  distance 1.2e-3`
+    pub fn status_line(&self) -> String {
+        let mut status = format!("c = {}", self.c);
+        match (self.escape_time, self.period) {
+            (Some(escape_time), _) => status.push_str(&format!("  escaped at {escape_time}")),
+            (None, Some(period)) => status.push_str(&format!("  inside, period {period}")),
+            (None, None) => status.push_str("  inside"),
+        }
+        if let Some(smooth_iteration) = self.smooth_iteration {
+            status.push_str(&format!(" (smooth {smooth_iteration:.2})"));
+        }
+        if let Some(distance) = self.distance {
+            status.push_str(&format!("  distance {distance:.2e}"));
+        }
+        status
+    }
+}
+
+/// Escaped orbits are followed until `|z|` passes this, long before `f32` overflows
+const PAST_ESCAPE_NORM_MAX: f32 = 1.0e6;
+
+/// Up to `steps` more z values of an escaped orbit, continuing where `escape_path` stopped.
+/// `escape_path` must start at `z₀ = 0` so that `z₁ = c`, and come from iterating `formula`
+pub fn past_escape(
+    escape_path: &[Complex<f32>],
+    steps: usize,
+    formula: &dyn FractalFormula<f32>,
+) -> Vec<Complex<f32>> {
+    let (Some(&c), Some(&last_z)) = (escape_path.get(1), escape_path.last()) else {
+        return Vec::new();
+    };
+    std::iter::successors(Some(last_z), |&z| Some(formula.step(z, &c)))
+        .skip(1)
+        .take(steps)
+        // NaN and ∞ fail the comparison too
+        .take_while(|z| z.norm() < PAST_ESCAPE_NORM_MAX)
+        .collect()
+}
+
+/// Colors of the pinned orbits, used in turn
+const PINNED_COLORS: [Color; 6] = [GREEN, MAGENTA, YELLOW, PINK, LIME, VIOLET];
+
+/// An orbit that stays on screen while the mouse moves on
+#[derive(Debug, Clone)]
+pub struct PinnedOrbit {
+    pub c: Complex<f32>,
+    /// the orbit as it was when it was pinned, with the formula and iteration limit of the time
+    pub z_values: Vec<Complex<f32>>,
+    pub color: Color,
+}
+
+/// The pinned orbits, oldest first
+#[derive(Debug, Default)]
+pub struct PinnedOrbits {
+    orbits: Vec<PinnedOrbit>,
+    /// index into `PINNED_COLORS` of the next orbit's color
+    next_color: usize,
+}
+
+impl PinnedOrbits {
+    pub fn pin(&mut self, c: Complex<f32>, z_values: Vec<Complex<f32>>) {
+        self.orbits.push(PinnedOrbit {
+            c,
+            z_values,
+            color: PINNED_COLORS[self.next_color],
+        });
+        self.next_color = (self.next_color + 1) % PINNED_COLORS.len();
+    }
+
+    pub fn orbits(&self) -> &[PinnedOrbit] {
+        &self.orbits
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.orbits.remove(index);
+    }
+
+    pub fn clear(&mut self) {
+        self.orbits.clear();
+        self.next_color = 0;
+    }
+}
@@ -0,0 +1,87 @@
+//! Draws orbits one z value at a time, so the iteration can be watched instead of seen all at once
+
+use crate::animation::frame_delta;
+use macroquad::{
+    hash,
+    prelude::*,
+    ui::{root_ui, widgets::Window},
+};
+use num::Complex;
+
+/// Slowest and fastest playback, in z values per second
+const SPEED_MIN: f32 = 0.5;
+const SPEED_MAX: f32 = 60.0;
+
+/// How much of the orbits has been played
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitPlayback {
+    /// z values drawn per second
+    speed: f32,
+    paused: bool,
+    /// how many steps have been played. fractional so slow speeds still advance every frame
+    played: f32,
+    /// the hovered `c` being played. a new `c` starts over
+    c: Option<Complex<f32>>,
+}
+
+impl Default for OrbitPlayback {
+    fn default() -> Self {
+        Self {
+            speed: 4.0,
+            paused: false,
+            played: 0.0,
+            c: None,
+        }
+    }
+}
+
+impl OrbitPlayback {
+    /// Call once per frame while playback is shown
+    pub fn update(&mut self, c: Option<Complex<f32>>) {
+        if c != self.c {
+            self.c = c;
+            self.restart();
+        }
+        if !self.paused {
+            self.played += frame_delta() * self.speed;
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.played = 0.0;
+    }
+
+    /// The start of `z_values` that has been played. `z_0` and `c` are always shown
+    pub fn played<'a>(&self, z_values: &'a [Complex<f32>]) -> &'a [Complex<f32>] {
+        let length = (self.played as usize + 2).min(z_values.len());
+        &z_values[..length]
+    }
+
+    /// Show the playback controls. `length` is the length of the hovered orbit
+    pub fn window(&mut self, length: usize) {
+        Window::new(hash!(), vec2(260.0, 360.0), vec2(250.0, 120.0))
+            .label("orbit playback")
+            .titlebar(true)
+            .ui(&mut *root_ui(), |ui| {
+                let step = (self.played as usize + 1).min(length.saturating_sub(1));
+                ui.label(
+                    None,
+                    &format!("step {} of {}", step, length.saturating_sub(1)),
+                );
+                ui.slider(hash!(), "steps/s", SPEED_MIN..SPEED_MAX, &mut self.speed);
+                if ui.button(None, if self.paused { "Play" } else { "Pause" }) {
+                    self.paused = !self.paused;
+                }
+                ui.same_line(0.0);
+                // stepping pauses, since the next frame would move on anyway
+                if ui.button(None, "Step") {
+                    self.paused = true;
+                    self.played = self.played.floor() + 1.0;
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "Restart") {
+                    self.restart();
+                }
+            });
+    }
+}
@@ -0,0 +1,127 @@
+//! The color schemes escape times are mapped through.
+//! Colorizers turn each pixel into a number from 0 to 1, and the palette picks the color for that number
+
+use macroquad::{color::hsl_to_rgb, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// A color scheme. The gradients repeat `density` times across `0.0..=1.0`, starting `offset` of the way in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Palette {
+    /// hues that cycle while the brightness rises with the escape time
+    #[default]
+    Hsl,
+    /// dark blue through white and gold to black
+    Classic,
+    Fire,
+    Grayscale,
+    Rainbow,
+    /// perceptually uniform, and readable with most kinds of color blindness
+    Viridis,
+    /// like viridis, but looks almost the same with red-green color blindness as without
+    Cividis,
+    /// blue through green and yellow to vermillion, from the Okabe-Ito colors that stay distinct with any color blindness
+    #[serde(rename = "okabe-ito")]
+    OkabeIto,
+}
+
+const CLASSIC_STOPS: [Color; 5] = [
+    Color::new(0.0, 0.03, 0.39, 1.0),
+    Color::new(0.13, 0.42, 0.8, 1.0),
+    Color::new(0.93, 1.0, 1.0, 1.0),
+    Color::new(1.0, 0.67, 0.0, 1.0),
+    Color::new(0.0, 0.01, 0.0, 1.0),
+];
+const FIRE_STOPS: [Color; 5] = [
+    Color::new(0.0, 0.0, 0.0, 1.0),
+    Color::new(0.5, 0.0, 0.0, 1.0),
+    Color::new(0.9, 0.3, 0.0, 1.0),
+    Color::new(1.0, 0.8, 0.1, 1.0),
+    Color::new(1.0, 1.0, 0.9, 1.0),
+];
+const GRAYSCALE_STOPS: [Color; 2] = [
+    Color::new(0.0, 0.0, 0.0, 1.0),
+    Color::new(1.0, 1.0, 1.0, 1.0),
+];
+const VIRIDIS_STOPS: [Color; 5] = [
+    Color::new(0.267, 0.005, 0.329, 1.0),
+    Color::new(0.231, 0.322, 0.545, 1.0),
+    Color::new(0.129, 0.569, 0.549, 1.0),
+    Color::new(0.369, 0.788, 0.384, 1.0),
+    Color::new(0.992, 0.906, 0.145, 1.0),
+];
+const CIVIDIS_STOPS: [Color; 5] = [
+    Color::new(0.0, 0.135, 0.306, 1.0),
+    Color::new(0.208, 0.271, 0.424, 1.0),
+    Color::new(0.486, 0.482, 0.471, 1.0),
+    Color::new(0.737, 0.686, 0.435, 1.0),
+    Color::new(0.996, 0.91, 0.22, 1.0),
+];
+const OKABE_ITO_STOPS: [Color; 6] = [
+    Color::new(0.0, 0.447, 0.698, 1.0),
+    Color::new(0.337, 0.706, 0.914, 1.0),
+    Color::new(0.0, 0.62, 0.451, 1.0),
+    Color::new(0.941, 0.894, 0.259, 1.0),
+    Color::new(0.902, 0.624, 0.0, 1.0),
+    Color::new(0.835, 0.369, 0.0, 1.0),
+];
+
+impl Palette {
+    pub const ALL: [Self; 8] = [
+        Self::Hsl,
+        Self::Classic,
+        Self::Fire,
+        Self::Grayscale,
+        Self::Rainbow,
+        Self::Viridis,
+        Self::Cividis,
+        Self::OkabeIto,
+    ];
+    pub const NAMES: [&'static str; 8] = [
+        "hsl",
+        "classic",
+        "fire",
+        "grayscale",
+        "rainbow",
+        "viridis",
+        "cividis",
+        "okabe-ito",
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .position(|&palette_name| palette_name == name)
+            .map(|index| Self::ALL[index])
+    }
+
+    /// Maps `0.0..=1.0` to a color
+    pub fn color(self, normalized: f32, density: f32, offset: f32) -> Color {
+        let position = (normalized * density + offset).rem_euclid(1.0);
+        match self {
+            // density and offset only move the hues, so the darkest colors stay at the lowest escape times
+            Self::Hsl => hsl_to_rgb(position.powf(0.7), 1.0, normalized.powf(0.3) * 0.5),
+            Self::Classic => gradient(&CLASSIC_STOPS, position),
+            Self::Fire => gradient(&FIRE_STOPS, position),
+            Self::Grayscale => gradient(&GRAYSCALE_STOPS, position),
+            Self::Rainbow => hsl_to_rgb(position, 1.0, 0.5),
+            Self::Viridis => gradient(&VIRIDIS_STOPS, position),
+            Self::Cividis => gradient(&CIVIDIS_STOPS, position),
+            Self::OkabeIto => gradient(&OKABE_ITO_STOPS, position),
+        }
+    }
+}
+
+/// The color `position` of the way through evenly spaced `stops`, blending the two nearest
+fn gradient(stops: &[Color], position: f32) -> Color {
+    let scaled = position.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let index = (scaled as usize).min(stops.len() - 2);
+    let fraction = scaled - index as f32;
+    let (start, end) = (stops[index], stops[index + 1]);
+    Color::new(
+        start.r + (end.r - start.r) * fraction,
+        start.g + (end.g - start.g) * fraction,
+        start.b + (end.b - start.b) * fraction,
+        1.0,
+    )
+}
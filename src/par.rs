@@ -0,0 +1,130 @@
+//! Import the entries of Fractint `.par` parameter files, so the classic collections of locations can be explored.
+//! An entry is `name { key=value ... }`, and `;` starts a comment. Each entry's `type`, `center-mag` (with its rotation) or `corners`,
+//! and `maxiter` are read. Only the Mandelbrot set types are supported, and the coloring is left as it was.
+
+use crate::view::ViewParams;
+use num::Complex;
+
+/// The Fractint types of the Mandelbrot set, drawn with integer and floating point math
+const MANDELBROT_TYPES: [&str; 2] = ["mandel", "mandelfp"];
+
+/// An entry of a `.par` file that couldn't be imported
+#[derive(Debug)]
+pub struct SkippedEntry {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Fractint's magnification fits `2 / mag` into the height of the view, while `scale` fits `4 / scale` into its width.
+/// `aspect` is the width of the view over its height
+fn magnification_to_scale(magnification: f64, aspect: f32) -> f32 {
+    (2.0 * magnification / aspect as f64) as f32
+}
+
+/// `values` split on `/` into numbers
+fn numbers(values: &str) -> Result<Vec<f64>, String> {
+    values
+        .split('/')
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|error| format!("{value:?} isn't a number: {error}"))
+        })
+        .collect()
+}
+
+/// `view` at the location of one entry, from the `key=value` pairs in its braces
+fn entry_view(body: &str, aspect: f32, view: &ViewParams) -> Result<ViewParams, String> {
+    let value = |key: &str| {
+        body.split_whitespace()
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(pair_key, _)| pair_key.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    };
+
+    let fractal_type = value("type").unwrap_or("mandel");
+    if !MANDELBROT_TYPES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(fractal_type))
+    {
+        return Err(format!("type {fractal_type} isn't supported"));
+    }
+
+    let (center, magnification, rotation) = match (value("center-mag"), value("corners")) {
+        // after the magnification come the x magnification factor and the rotation in degrees
+        (Some(center_magnification), _) => match numbers(center_magnification)?[..] {
+            [re, im, magnification, _, rotation, ..] => {
+                (Complex::new(re, im), magnification, rotation)
+            }
+            [re, im, magnification, ..] => (Complex::new(re, im), magnification, 0.0),
+            _ => {
+                return Err(String::from(
+                    "center-mag needs a center and a magnification",
+                ));
+            }
+        },
+        (None, Some(corners)) => match numbers(corners)?[..] {
+            [re_min, re_max, im_min, im_max, ..] => (
+                Complex::new(re_min + re_max, im_min + im_max) / 2.0,
+                2.0 / (im_max - im_min).abs(),
+                0.0,
+            ),
+            _ => return Err(String::from("corners needs 4 numbers")),
+        },
+        (None, None) => return Err(String::from("no center-mag or corners")),
+    };
+    if !(magnification.is_finite() && magnification > 0.0) {
+        return Err(format!("the magnification {magnification} isn't positive"));
+    }
+    let iteration_max = match value("maxiter") {
+        Some(maxiter) => maxiter
+            .parse()
+            .map_err(|error| format!("maxiter {maxiter:?} isn't a number: {error}"))?,
+        // Fractint's default
+        None => 150,
+    };
+
+    Ok(ViewParams {
+        center: Complex::new(center.re as f32, center.im as f32),
+        scale: magnification_to_scale(magnification, aspect),
+        iteration_max,
+        formula: String::from("mandelbrot"),
+        rotation: rotation as f32,
+        ..view.clone()
+    })
+}
+
+/// The named views of every entry in a `.par` file, each based on `view`, and the entries that couldn't be imported.
+/// `aspect` is the width of the view over its height
+pub fn parse_par(
+    text: &str,
+    aspect: f32,
+    view: &ViewParams,
+) -> (Vec<(String, ViewParams)>, Vec<SkippedEntry>) {
+    let text = text
+        .lines()
+        .map(|line| line.split_once(';').map_or(line, |(code, _)| code))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+    let mut rest = text.as_str();
+    while let Some(open) = rest.find('{') {
+        let name = rest[..open].trim().to_owned();
+        let Some(close) = rest[open..].find('}') else {
+            skipped.push(SkippedEntry {
+                name,
+                reason: String::from("the entry has no closing }"),
+            });
+            break;
+        };
+        let body = &rest[open + 1..open + close];
+        match entry_view(body, aspect, view) {
+            Ok(entry) => imported.push((name, entry)),
+            Err(reason) => skipped.push(SkippedEntry { name, reason }),
+        }
+        rest = &rest[open + close + 1..];
+    }
+    (imported, skipped)
+}
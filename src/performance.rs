@@ -0,0 +1,43 @@
+//! An overlay of how fast the app runs and renders, for comparing precision modes and optimizations
+
+use crate::MandelbrotRender;
+use macroquad::prelude::*;
+
+const FONT_SIZE: f32 = 18.0;
+const LINE_HEIGHT: f32 = 20.0;
+const PANEL_WIDTH: f32 = 190.0;
+/// Pixels between the overlay and the formula window to its right
+const MARGIN: f32 = 10.0;
+
+/// Draw the frame rate, and the time, speed, memory, and threads of the last render, to the left of the formula window
+pub fn draw_performance_stats(render: &MandelbrotRender) {
+    let renders = std::iter::once(render).chain(render.comparison.as_deref());
+    let (pixel_count, memory_size) = renders.fold((0, 0), |(pixel_count, memory_size), render| {
+        (
+            pixel_count + render.data.len(),
+            memory_size + render.data.memory_size(),
+        )
+    });
+    let pixels_per_second = pixel_count as f64 / render.render_seconds.max(f64::EPSILON);
+    let lines = [
+        format!("{} fps", get_fps()),
+        format!("render {:.0} ms", render.render_seconds * 1000.0),
+        format!("{:.2} megapixels/s", pixels_per_second / 1.0e6),
+        format!("buffers {:.1} MiB", memory_size as f64 / (1024.0 * 1024.0)),
+        format!("{} threads", rayon::current_num_threads()),
+    ];
+
+    let left = screen_width() - 250.0 - MARGIN - PANEL_WIDTH;
+    let height = lines.len() as f32 * LINE_HEIGHT + 8.0;
+    draw_rectangle(
+        left,
+        0.0,
+        PANEL_WIDTH,
+        height,
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+    for (index, line) in lines.iter().enumerate() {
+        let baseline = (index + 1) as f32 * LINE_HEIGHT;
+        draw_text(line, left + 6.0, baseline, FONT_SIZE, WHITE);
+    }
+}
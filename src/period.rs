@@ -0,0 +1,74 @@
+use crate::{complex_to_screen_coordinate, view_size};
+use macroquad::prelude::*;
+use mandelbrot_core::FractalFormula;
+use num::Complex;
+
+/// The longest cycle `calculate_period` looks for
+pub const PERIOD_MAX: usize = 64;
+
+/// Nuclei (the superattracting centers) of the biggest bulbs and their periods
+pub const BULB_NUCLEI: [(Complex<f32>, usize); 12] = [
+    (Complex::new(0.0, 0.0), 1),
+    (Complex::new(-1.0, 0.0), 2),
+    (Complex::new(-0.122561, 0.744862), 3),
+    (Complex::new(-0.122561, -0.744862), 3),
+    (Complex::new(-1.754878, 0.0), 3),
+    (Complex::new(0.282271, 0.530061), 4),
+    (Complex::new(0.282271, -0.530061), 4),
+    (Complex::new(-1.310703, 0.0), 4),
+    (Complex::new(0.379513, 0.334932), 5),
+    (Complex::new(0.379513, -0.334932), 5),
+    (Complex::new(-0.504340, 0.562765), 5),
+    (Complex::new(-0.504340, -0.562765), 5),
+];
+
+/// Find the period of the attracting cycle that the orbit of `c` under `formula` has settled into.
+/// `settled_z` should be the last z value of an orbit that didn't escape.
+/// Returns `None` if the orbit hasn't settled onto a cycle of at most `period_max` points
+pub fn calculate_period(
+    c: Complex<f32>,
+    settled_z: Complex<f32>,
+    period_max: usize,
+    formula: &dyn FractalFormula<f32>,
+) -> Option<usize> {
+    // f32 can't resolve a cycle much tighter than this
+    const TOLERANCE: f32 = 1e-4;
+
+    let mut z = settled_z;
+    for period in 1..=period_max {
+        z = formula.step(z, &c);
+        if (z - settled_z).norm_sqr() < TOLERANCE * TOLERANCE {
+            return Some(period);
+        }
+    }
+
+    None
+}
+
+/// Write the period of each bulb on top of it. `bulbs` are (nucleus, period) pairs
+pub fn draw_bulb_labels(
+    center: Complex<f32>,
+    dimensions: Complex<f32>,
+    rotation: f32,
+    bulbs: &[(Complex<f32>, usize)],
+) {
+    const FONT_SIZE: u16 = 16;
+
+    for &(nucleus, period) in bulbs {
+        let position = complex_to_screen_coordinate(nucleus, center, dimensions, rotation);
+        let view_size = view_size(dimensions);
+        if !Rect::new(0.0, 0.0, view_size.x, view_size.y).contains(position) {
+            continue;
+        }
+
+        let label = period.to_string();
+        let label_dimensions = measure_text(&label, None, FONT_SIZE, 1.0);
+        draw_text(
+            &label,
+            position.x - label_dimensions.width / 2.0,
+            position.y + label_dimensions.height / 2.0,
+            FONT_SIZE as f32,
+            WHITE,
+        );
+    }
+}
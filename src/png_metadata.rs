@@ -0,0 +1,119 @@
+//! The view an image was rendered from, written into the text chunks of exported PNGs.
+//! Each part of the view gets a readable chunk of its own, so image viewers and `exiftool` show where an image is,
+//! and the whole view is also stored as JSON in one chunk, which is what's read back to restore it.
+
+use crate::{palette::Palette, view::ViewParams};
+use std::{fs::File, io::BufWriter};
+
+/// The chunk holding the whole view as JSON
+const VIEW_KEYWORD: &str = "Mandelbrot view";
+/// The scalar the app iterates orbits with
+const PRECISION: &str = "f32";
+
+/// `view` as the JSON stored in exported files
+pub fn view_json(view: &ViewParams) -> Result<String, String> {
+    serde_json::to_string(view).map_err(|error| format!("Failed to serialize the view: {error}"))
+}
+
+/// The text chunks describing `view`, as keyword and text pairs
+fn view_chunks(view: &ViewParams) -> Result<Vec<(&'static str, String)>, String> {
+    let json = view_json(view)?;
+    Ok(vec![
+        ("Software", String::from("mandelbrot_escape_path_demo")),
+        ("Center", format!("{} {}", view.center.re, view.center.im)),
+        ("Zoom", view.scale.to_string()),
+        ("Iterations", view.iteration_max.to_string()),
+        ("Fractal", view.formula.clone()),
+        ("Precision", String::from(PRECISION)),
+        (
+            "Palette",
+            Palette::NAMES[view.coloring.palette as usize].to_owned(),
+        ),
+        (VIEW_KEYWORD, json),
+    ])
+}
+
+/// Write 8 bit `bytes` of `color_type` to `path` as a PNG, with the text chunks of `view` when there is one
+pub fn write_png(
+    path: &str,
+    bytes: &[u8],
+    width: usize,
+    height: usize,
+    color_type: png::ColorType,
+    view: Option<&ViewParams>,
+) -> Result<(), String> {
+    let size = (width, height);
+    write_png_with_depth(path, bytes, size, color_type, png::BitDepth::Eight, view)
+}
+
+/// `write_png` with 16 bit samples
+pub fn write_png_16(
+    path: &str,
+    samples: &[u16],
+    width: usize,
+    height: usize,
+    color_type: png::ColorType,
+    view: Option<&ViewParams>,
+) -> Result<(), String> {
+    // PNG stores 16 bit samples big endian
+    let bytes = samples
+        .iter()
+        .flat_map(|sample| sample.to_be_bytes())
+        .collect::<Vec<_>>();
+    let size = (width, height);
+    write_png_with_depth(path, &bytes, size, color_type, png::BitDepth::Sixteen, view)
+}
+
+fn write_png_with_depth(
+    path: &str,
+    bytes: &[u8],
+    size: (usize, usize),
+    color_type: png::ColorType,
+    bit_depth: png::BitDepth,
+    view: Option<&ViewParams>,
+) -> Result<(), String> {
+    png_writer(path, size, color_type, bit_depth, view)?
+        .write_image_data(bytes)
+        .map_err(|error| format!("Failed to write {path:?}: {error}"))
+}
+
+/// Create a PNG at `path` and write its header, with the text chunks of `view` when there is one.
+/// The image data can be written all at once, or streamed a few rows at a time
+pub fn png_writer(
+    path: &str,
+    (width, height): (usize, usize),
+    color_type: png::ColorType,
+    bit_depth: png::BitDepth,
+    view: Option<&ViewParams>,
+) -> Result<png::Writer<BufWriter<File>>, String> {
+    let write_error = |error: &dyn std::fmt::Display| format!("Failed to write {path:?}: {error}");
+
+    let file = File::create(path).map_err(|error| write_error(&error))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+    if let Some(view) = view {
+        for (keyword, text) in view_chunks(view)? {
+            encoder
+                .add_text_chunk(keyword.to_owned(), text)
+                .map_err(|error| write_error(&error))?;
+        }
+    }
+    encoder.write_header().map_err(|error| write_error(&error))
+}
+
+/// The view stored in a PNG exported by `write_png`
+pub fn read_view(path: &str) -> Result<ViewParams, String> {
+    let file = File::open(path).map_err(|error| format!("Failed to open {path:?}: {error}"))?;
+    let reader = png::Decoder::new(file)
+        .read_info()
+        .map_err(|error| format!("Failed to read {path:?}: {error}"))?;
+    let chunk = reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .find(|chunk| chunk.keyword == VIEW_KEYWORD)
+        .ok_or_else(|| format!("{path:?} has no view stored in it"))?;
+    serde_json::from_str(&chunk.text)
+        .map_err(|error| format!("The view stored in {path:?} is invalid: {error}"))
+}
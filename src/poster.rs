@@ -0,0 +1,305 @@
+//! Render posters far bigger than the screen, e.g. 16384x16384, with bounded memory.
+//! The poster is calculated in bands of tiles from the top down, and each band is colored and streamed into the file
+//! before the next is calculated, so only one tile's escape data and one band's colors are in memory at once.
+//! Every tile is colored with the statistics of a small preview of the whole poster, so there are no seams between them.
+//! `.tif` and `.tiff` files are uncompressed TIFFs, and anything else is a PNG.
+
+use crate::{
+    coloring::color_with_frame,
+    colorizer::FrameStatistics,
+    png_metadata::png_writer,
+    view::{ViewJob, ViewParams, view_job},
+};
+use mandelbrot_core::PixelRect;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+/// Rows of the poster colored and written at a time
+const BAND_HEIGHT: usize = 256;
+/// The side of the square tiles each band is calculated in
+const TILE_SIZE: usize = 256;
+/// The most pixels in the preview the colors are based on
+const PREVIEW_PIXELS_MAX: usize = 512 * 512;
+
+/// The bytes of a TIFF before its pixels
+const TIFF_HEADER_SIZE: usize = 8;
+/// Entries in the TIFF directory
+const TIFF_ENTRY_COUNT: usize = 11;
+/// The directory and the bits per sample it points to
+const TIFF_DIRECTORY_SIZE: usize = 2 + TIFF_ENTRY_COUNT * 12 + 4 + 8;
+
+/// Where the rows of a poster are streamed to
+enum PosterWriter {
+    Png(png::StreamWriter<'static, BufWriter<File>>),
+    /// an uncompressed TIFF, whose directory is written after the pixels
+    Tiff(BufWriter<File>),
+}
+
+impl PosterWriter {
+    fn create(path: &str, width: usize, height: usize, view: &ViewParams) -> Result<Self, String> {
+        let write_error =
+            |error: &dyn std::fmt::Display| format!("Failed to write {path:?}: {error}");
+        let is_tiff = Path::new(path).extension().is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("tif") || extension.eq_ignore_ascii_case("tiff")
+        });
+        if !is_tiff {
+            let rgba = png::ColorType::Rgba;
+            let writer = png_writer(
+                path,
+                (width, height),
+                rgba,
+                png::BitDepth::Eight,
+                Some(view),
+            )?;
+            return writer
+                .into_stream_writer()
+                .map(Self::Png)
+                .map_err(|error| write_error(&error));
+        }
+
+        // the directory goes right after the pixels, and all offsets in a TIFF are 32 bit
+        let directory_offset = u32::try_from(TIFF_HEADER_SIZE + width * height * 4)
+            .ok()
+            .filter(|offset| offset.checked_add(TIFF_DIRECTORY_SIZE as u32).is_some())
+            .ok_or_else(|| format!("{width}x{height} is too big for a TIFF, save a PNG instead"))?;
+        let mut file = BufWriter::new(File::create(path).map_err(|error| write_error(&error))?);
+        // little endian, the TIFF magic number, and where the directory is
+        file.write_all(b"II")
+            .and_then(|_| file.write_all(&42_u16.to_le_bytes()))
+            .and_then(|_| file.write_all(&directory_offset.to_le_bytes()))
+            .map_err(|error| write_error(&error))?;
+        Ok(Self::Tiff(file))
+    }
+
+    /// Write the next rows of RGBA pixels
+    fn write_rows(&mut self, rgba: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::Png(writer) => writer.write_all(rgba),
+            Self::Tiff(file) => file.write_all(rgba),
+        }
+    }
+
+    /// Finish the file once every row has been written
+    fn finish(self, width: usize, height: usize) -> Result<(), String> {
+        match self {
+            Self::Png(writer) => writer.finish().map_err(|error| error.to_string()),
+            Self::Tiff(mut file) => {
+                let directory = tiff_directory(width as u32, height as u32);
+                file.write_all(&directory)
+                    .and_then(|_| file.flush())
+                    .map_err(|error| error.to_string())
+            }
+        }
+    }
+}
+
+/// The directory of an uncompressed RGBA TIFF with one strip of pixels right after the header
+fn tiff_directory(width: u32, height: u32) -> Vec<u8> {
+    const SHORT: u16 = 3;
+    const LONG: u16 = 4;
+
+    let directory_offset = TIFF_HEADER_SIZE as u32 + width * height * 4;
+    // the 4 bits per sample don't fit in their entry, so they go after the directory
+    let bits_per_sample_offset = directory_offset + TIFF_DIRECTORY_SIZE as u32 - 8;
+    // tag, type, count, and the value, in order of tag
+    let entries: [(u16, u16, u32, u32); TIFF_ENTRY_COUNT] = [
+        (256, LONG, 1, width),
+        (257, LONG, 1, height),
+        (258, SHORT, 4, bits_per_sample_offset),
+        // no compression
+        (259, SHORT, 1, 1),
+        // RGB
+        (262, SHORT, 1, 2),
+        // where the strip starts
+        (273, LONG, 1, TIFF_HEADER_SIZE as u32),
+        (277, SHORT, 1, 4),
+        // one strip of every row
+        (278, LONG, 1, height),
+        (279, LONG, 1, width * height * 4),
+        // the samples of each pixel together
+        (284, SHORT, 1, 1),
+        // the fourth sample is unassociated alpha
+        (338, SHORT, 1, 2),
+    ];
+
+    let mut directory = Vec::with_capacity(TIFF_DIRECTORY_SIZE);
+    directory.extend_from_slice(&(TIFF_ENTRY_COUNT as u16).to_le_bytes());
+    for (tag, kind, count, value) in entries {
+        directory.extend_from_slice(&tag.to_le_bytes());
+        directory.extend_from_slice(&kind.to_le_bytes());
+        directory.extend_from_slice(&count.to_le_bytes());
+        // a single short is stored in the first 2 bytes of the value
+        if kind == SHORT && count == 1 {
+            directory.extend_from_slice(&(value as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 2]);
+        } else {
+            directory.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    // no more directories
+    directory.extend_from_slice(&0_u32.to_le_bytes());
+    for _ in 0..4 {
+        directory.extend_from_slice(&8_u16.to_le_bytes());
+    }
+    directory
+}
+
+/// The size of a preview of a `width` by `height` image, shrunk to at most `PREVIEW_PIXELS_MAX` pixels.
+/// Very long and thin images can't keep their shape, so the long side is cut short
+fn preview_size(width: usize, height: usize) -> (usize, usize) {
+    let shrink = (PREVIEW_PIXELS_MAX as f64 / (width * height).max(1) as f64)
+        .sqrt()
+        .min(1.0);
+    let preview_width = ((width as f64 * shrink) as usize).clamp(1, PREVIEW_PIXELS_MAX);
+    let preview_height =
+        ((height as f64 * shrink) as usize).clamp(1, PREVIEW_PIXELS_MAX / preview_width);
+    (preview_width, preview_height)
+}
+
+/// The statistics of a small preview of the whole of `job`, so tiles colored with them match each other
+pub fn preview_statistics(job: &ViewJob, iteration_max: usize) -> FrameStatistics {
+    let PixelRect { width, height, .. } = job.full_rect();
+    let (preview_width, preview_height) = preview_size(width, height);
+    let preview = job
+        .clone()
+        .resolution(preview_width, preview_height)
+        .store_paths(false)
+        .run();
+    FrameStatistics::new(&preview, iteration_max)
+}
+
+/// Render `view` at `width` by `height` pixels and write it to `path`.
+/// `rows_done` counts the rows written so far, and storing `true` in `cancel` stops before the next band
+pub fn render_poster(
+    view: &ViewParams,
+    width: usize,
+    height: usize,
+    path: &str,
+    rows_done: &AtomicUsize,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    if width == 0 || height == 0 {
+        return Err(String::from(
+            "The poster must be at least 1 pixel on each side",
+        ));
+    }
+    let job = view_job(view, width, height);
+    let frame = preview_statistics(&job, view.iteration_max);
+
+    let mut writer = PosterWriter::create(path, width, height, view)?;
+    let write_error = |error: std::io::Error| format!("Failed to write {path:?}: {error}");
+    for band_row in (0..height).step_by(BAND_HEIGHT) {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(String::from("Cancelled"));
+        }
+        let band_height = BAND_HEIGHT.min(height - band_row);
+        let mut band = vec![0; width * band_height * 4];
+        for tile_rect in PixelRect::tiles(width, band_height, TILE_SIZE) {
+            let rect = PixelRect {
+                row: band_row + tile_rect.row,
+                ..tile_rect
+            };
+            let tile = job.run_tile(rect).map_err(|error| error.to_string())?;
+            let image = color_with_frame(&tile, &frame, &view.coloring)
+                .map_err(|error| error.to_string())?;
+            for row_index in 0..rect.height {
+                let start = ((tile_rect.row + row_index) * width + rect.column) * 4;
+                let tile_start = row_index * rect.width * 4;
+                band[start..start + rect.width * 4]
+                    .copy_from_slice(&image.bytes[tile_start..tile_start + rect.width * 4]);
+            }
+        }
+        writer.write_rows(&band).map_err(write_error)?;
+        rows_done.store(band_row + band_height, Ordering::Relaxed);
+    }
+    writer
+        .finish(width, height)
+        .map_err(|error| format!("Failed to write {path:?}: {error}"))
+}
+
+/// A poster (or another big export) being rendered on another thread, so the app keeps responding
+pub struct PosterJob {
+    thread: std::thread::JoinHandle<Result<(), String>>,
+    /// how many of the `total` steps are done
+    done: Arc<AtomicUsize>,
+    cancel: Arc<AtomicBool>,
+    total: usize,
+    path: String,
+}
+
+impl PosterJob {
+    /// Start rendering `view` at `width` by `height` pixels to `path`
+    pub fn start(
+        view: ViewParams,
+        width: usize,
+        height: usize,
+        path: &str,
+    ) -> Result<Self, String> {
+        let poster_path = path.to_owned();
+        Self::spawn(height, path, move |rows_done, cancel| {
+            render_poster(&view, width, height, &poster_path, rows_done, cancel)
+        })
+    }
+
+    /// Run `render` on another thread. It counts the steps it's done out of `total`, and stops when it's cancelled
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(
+        total: usize,
+        path: &str,
+        render: impl FnOnce(&AtomicUsize, &AtomicBool) -> Result<(), String> + Send + 'static,
+    ) -> Result<Self, String> {
+        let done = Arc::new(AtomicUsize::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (done, cancel) = (done.clone(), cancel.clone());
+            std::thread::spawn(move || render(&done, &cancel))
+        };
+        Ok(Self {
+            thread,
+            done,
+            cancel,
+            total,
+            path: path.to_owned(),
+        })
+    }
+
+    /// The browser has no threads to render on, or files to save to
+    #[cfg(target_arch = "wasm32")]
+    pub fn spawn(
+        _: usize,
+        _: &str,
+        _: impl FnOnce(&AtomicUsize, &AtomicBool) -> Result<(), String> + Send + 'static,
+    ) -> Result<Self, String> {
+        Err(String::from("Posters can't be saved in the browser"))
+    }
+
+    /// How much has been rendered, `0.0..=1.0`
+    pub fn progress(&self) -> f32 {
+        self.done.load(Ordering::Relaxed) as f32 / self.total.max(1) as f32
+    }
+
+    /// Stop before the next band or tile
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Wait for the render to end, and describe how it went
+    pub fn join(self) -> String {
+        match self.thread.join() {
+            Ok(Ok(())) => format!("Saved {}", self.path),
+            Ok(Err(error)) => error,
+            Err(_) => String::from("The render panicked"),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Preferences that carry over to every run, whether or not the last session is restored:
+//! how far the keyboard and mouse wheel move the view, and whether the windows use the high contrast theme.
+//! They're kept in a JSON file next to the app, like the window layout
+
+use crate::{Settings, navigation::KeyboardSteps};
+use serde::{Deserialize, Serialize};
+
+const PREFERENCES_PATH: &str = "mandelbrot_preferences.json";
+
+/// there is no file system to save to in the browser, so the preferences reset with the tab
+const PREFERENCES_SAVED: bool = cfg!(not(target_arch = "wasm32"));
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    keyboard_steps: KeyboardSteps,
+    high_contrast: bool,
+}
+
+impl Preferences {
+    /// The preferences saved by the last run, or the defaults if nothing has been saved yet
+    pub fn load() -> Self {
+        if !PREFERENCES_SAVED {
+            return Self::default();
+        }
+        let loaded = match std::fs::read_to_string(PREFERENCES_PATH) {
+            Ok(json) => serde_json::from_str(&json).map_err(|error| error.to_string()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.to_string()),
+        };
+        loaded.unwrap_or_else(|error| {
+            eprintln!("Failed to load the preferences: {error}");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if !PREFERENCES_SAVED {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        std::fs::write(PREFERENCES_PATH, json).map_err(|error| error.to_string())
+    }
+
+    /// The preferences in `settings`
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            keyboard_steps: settings.keyboard_steps,
+            high_contrast: settings.high_contrast,
+        }
+    }
+
+    /// Put the preferences into `settings`
+    pub fn apply(&self, settings: &mut Settings) {
+        settings.keyboard_steps = self.keyboard_steps;
+        settings.high_contrast = self.high_contrast;
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    coloring::rgba_to_array,
+    error::{RenderError, image_size},
+    palette::Palette,
+    serialize_index,
+};
+use macroquad::prelude::*;
+use mandelbrot_core::EscapeGrid;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+/// Total iterations spent on each square region of a render
+pub struct IterationProfile {
+    /// side length of each region in pixels
+    pub region_size: usize,
+    columns: usize,
+    rows: usize,
+    /// iterations spent in each region, in row major order
+    region_totals: Vec<u64>,
+    /// iterations spent on the whole render
+    total: u64,
+}
+
+impl IterationProfile {
+    pub fn new(mandelbrot_data: &EscapeGrid, region_size: usize, iteration_max: usize) -> Self {
+        let width = mandelbrot_data.width();
+        let height = mandelbrot_data.height();
+        let region_size = region_size.max(1);
+        let columns = width.div_ceil(region_size);
+        let rows = height.div_ceil(region_size);
+
+        let mut region_totals = vec![0; columns * rows];
+        for pixel_index in 0..mandelbrot_data.len() {
+            let escape_time = mandelbrot_data.escape_time(pixel_index);
+            let row_index = pixel_index / width;
+            let column_index = pixel_index % width;
+            let region_index =
+                serialize_index(row_index / region_size, column_index / region_size, columns);
+            // points in the set used every iteration
+            if let Some(region_total) = region_totals.get_mut(region_index) {
+                *region_total += escape_time.unwrap_or(iteration_max) as u64;
+            }
+        }
+        let total = region_totals.iter().sum();
+
+        Self {
+            region_size,
+            columns,
+            rows,
+            region_totals,
+            total,
+        }
+    }
+
+    /// Shade each region by how much of the compute it took, and label the regions big enough to fit their share
+    pub fn draw(&self) {
+        const FONT_SIZE: f32 = 14.0;
+
+        let region_max = self.region_totals.iter().copied().max().unwrap_or(0).max(1);
+        let size = self.region_size as f32;
+        for row_index in 0..self.rows {
+            for column_index in 0..self.columns {
+                let region_total =
+                    self.region_totals[serialize_index(row_index, column_index, self.columns)];
+                let x = column_index as f32 * size;
+                let y = row_index as f32 * size;
+
+                let intensity = region_total as f32 / region_max as f32;
+                draw_rectangle(x, y, size, size, RED.with_alpha(0.6 * intensity));
+                draw_rectangle_lines(x, y, size, size, 1.0, BLACK.with_alpha(0.3));
+
+                if size >= 48.0 {
+                    let share = 100.0 * region_total as f32 / self.total.max(1) as f32;
+                    draw_text(
+                        &format!("{share:.1}%"),
+                        x + 2.0,
+                        y + FONT_SIZE,
+                        FONT_SIZE,
+                        WHITE,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Create an image coloring each pixel by the iterations it took, from black for none through red and yellow to white for `iteration_max`.
+/// The scale is logarithmic, so the cheap pixels far from the set don't all look the same
+pub fn create_cost_image(
+    mandelbrot_data: &EscapeGrid,
+    iteration_max: usize,
+) -> Result<Image, RenderError> {
+    let width = mandelbrot_data.width();
+    let height = mandelbrot_data.height();
+    let (image_width, image_height) = image_size(width, height)?;
+
+    let log_iteration_max = (iteration_max as f32).ln_1p().max(f32::EPSILON);
+    let mut image = Image::gen_image_color(image_width, image_height, BLANK);
+    image
+        .get_image_data_mut()
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(pixel_index, pixel_color)| {
+            // points in the set used every iteration
+            let cost = mandelbrot_data
+                .escape_time(pixel_index)
+                .unwrap_or(iteration_max);
+            let normalized = (cost as f32).ln_1p() / log_iteration_max;
+            *pixel_color = rgba_to_array(Palette::Fire.color(normalized.min(1.0), 1.0, 0.0));
+        });
+
+    Ok(image)
+}
@@ -0,0 +1,306 @@
+//! Export the smooth escape times, and optionally the distance estimates, as numbers rather than colors,
+//! so they can be tone mapped and colored in other tools.
+//! 16 bit PNGs are grayscale with the inside of the set transparent, and OpenEXR files hold 32 bit floats.
+//! The OpenEXR writer only covers what's needed here: one uncompressed part of scanlines of float channels.
+//! The whole grid of integer escape times can also be exported as binary or CSV, for analysis in other programs.
+
+use crate::{
+    MandelbrotRender,
+    contour::smooth_iteration,
+    png_metadata::{view_json, write_png_16},
+    shading::distance_estimate,
+    view::ViewParams,
+};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::path::Path;
+
+/// Steps of a 16 bit distance per pixel. Distances of 256 pixels and more are all the brightest value
+const DISTANCE_STEPS_PER_PIXEL: f32 = 256.0;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RawFormat {
+    /// `_smooth.png`, and `_distance.png` when distances are included.
+    /// Smooth escape times are scaled so `iteration_max` is the brightest value,
+    /// and distances are in `1 / DISTANCE_STEPS_PER_PIXEL`ths of a pixel
+    #[default]
+    Png16,
+    /// one `.exr` with a `smooth` channel of escape times, and a `distance` channel on the complex plane.
+    /// Pixels inside the set have a smooth escape time of -1 and a distance of 0. Distances that couldn't be estimated are -1
+    Exr,
+    /// `_escape_times.bin`: the width, height, and iteration limit, then the escape time of each pixel in row major order,
+    /// all little endian `u32`s. Pixels inside the set are `u32::MAX`
+    Binary,
+    /// `_escape_times.csv`: a `width,height,iteration_max` header and its values,
+    /// then one line of escape times per row. Pixels inside the set are left empty
+    Csv,
+}
+
+impl RawFormat {
+    pub const ALL: [Self; 4] = [Self::Png16, Self::Exr, Self::Binary, Self::Csv];
+    pub const NAMES: [&'static str; 4] = ["16 bit png", "exr", "binary", "csv"];
+
+    /// Whether the format has room for distance estimates
+    pub fn has_distance(self) -> bool {
+        matches!(self, Self::Png16 | Self::Exr)
+    }
+}
+
+/// The smooth escape time of every pixel, or `None` inside the set
+fn smooth_values(render: &MandelbrotRender) -> Vec<Option<f32>> {
+    let data = &render.data;
+    (0..data.len())
+        .map(|pixel_index| {
+            data.escape_time(pixel_index)
+                .map(|escape_time| smooth_iteration(escape_time, data.last_z(pixel_index)))
+        })
+        .collect()
+}
+
+/// The distance estimate of every escaped pixel on the complex plane, or `None` inside the set or where it overflowed.
+/// Paths that weren't stored are calculated again
+fn distances(render: &MandelbrotRender) -> Vec<Option<f32>> {
+    let data = &render.data;
+    (0..data.len())
+        .into_par_iter()
+        .map(|pixel_index| {
+            data.escape_time(pixel_index)?;
+            match data.path(pixel_index) {
+                Some(escape_path) => distance_estimate(escape_path),
+                None => {
+                    let (row_index, column_index) =
+                        (pixel_index / data.width(), pixel_index % data.width());
+                    let pixel = render.job.pixel_coordinate(row_index, column_index);
+                    let (_, escape_path) = render.job.escape_path(pixel);
+                    distance_estimate(&escape_path)
+                }
+            }
+        })
+        .collect()
+}
+
+/// `path` with `suffix` added to the file name and its extension replaced, e.g. `render.png` becomes `render_smooth.png`
+fn suffixed_path(path: &str, suffix: &str, extension: &str) -> String {
+    let path = Path::new(path);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    path.with_file_name(format!("{stem}{suffix}.{extension}"))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Gray and alpha samples, transparent where there is no value
+fn gray_alpha(values: &[Option<f32>], to_gray: impl Fn(f32) -> f32) -> Vec<u16> {
+    values
+        .iter()
+        .flat_map(|value| match value {
+            Some(value) => [to_gray(*value).clamp(0.0, u16::MAX as f32) as u16, u16::MAX],
+            None => [0, 0],
+        })
+        .collect()
+}
+
+/// Write the escape times of `render` next to `path` in `format`, with `view` stored in the PNG and OpenEXR files.
+/// Returns the paths written
+pub fn export_raw(
+    render: &MandelbrotRender,
+    format: RawFormat,
+    include_distance: bool,
+    path: &str,
+    view: &ViewParams,
+) -> Result<Vec<String>, String> {
+    let (width, height) = (render.data.width(), render.data.height());
+    let smooth = smooth_values(render);
+    let distance = (include_distance && format.has_distance()).then(|| distances(render));
+
+    match format {
+        RawFormat::Png16 => {
+            let smooth_path = suffixed_path(path, "_smooth", "png");
+            let scale = u16::MAX as f32 / render.iteration_max.max(1) as f32;
+            let samples = gray_alpha(&smooth, |smooth| smooth * scale);
+            let gray_alpha_type = png::ColorType::GrayscaleAlpha;
+            write_png_16(
+                &smooth_path,
+                &samples,
+                width,
+                height,
+                gray_alpha_type,
+                Some(view),
+            )?;
+            let mut written = vec![smooth_path];
+
+            if let Some(distance) = distance {
+                let distance_path = suffixed_path(path, "_distance", "png");
+                let pixel_size = render.dimensions.re / width as f32;
+                let samples = gray_alpha(&distance, |distance| {
+                    distance / pixel_size * DISTANCE_STEPS_PER_PIXEL
+                });
+                write_png_16(
+                    &distance_path,
+                    &samples,
+                    width,
+                    height,
+                    gray_alpha_type,
+                    Some(view),
+                )?;
+                written.push(distance_path);
+            }
+            Ok(written)
+        }
+        RawFormat::Exr => {
+            let exr_path = suffixed_path(path, "", "exr");
+            let mut channels = vec![(
+                "smooth",
+                smooth.iter().map(|smooth| smooth.unwrap_or(-1.0)).collect(),
+            )];
+            if let Some(distance) = distance {
+                let distance = distance
+                    .iter()
+                    .zip(&smooth)
+                    .map(|(distance, smooth)| match (distance, smooth) {
+                        // inside the set
+                        (_, None) => 0.0,
+                        (Some(distance), Some(_)) => *distance,
+                        (None, Some(_)) => -1.0,
+                    })
+                    .collect();
+                channels.push(("distance", distance));
+            }
+            let exr = encode_exr(width, height, &mut channels, &view_json(view)?);
+            std::fs::write(&exr_path, exr)
+                .map_err(|error| format!("Failed to write {exr_path:?}: {error}"))?;
+            Ok(vec![exr_path])
+        }
+        RawFormat::Binary => {
+            let binary_path = suffixed_path(path, "_escape_times", "bin");
+            let escape_times = (0..render.data.len()).map(|pixel_index| {
+                render
+                    .data
+                    .escape_time(pixel_index)
+                    .map_or(u32::MAX, |escape_time| escape_time as u32)
+            });
+            let binary = [width as u32, height as u32, render.iteration_max as u32]
+                .into_iter()
+                .chain(escape_times)
+                .flat_map(u32::to_le_bytes)
+                .collect::<Vec<_>>();
+            std::fs::write(&binary_path, binary)
+                .map_err(|error| format!("Failed to write {binary_path:?}: {error}"))?;
+            Ok(vec![binary_path])
+        }
+        RawFormat::Csv => {
+            let csv_path = suffixed_path(path, "_escape_times", "csv");
+            let mut csv = format!(
+                "width,height,iteration_max\n{width},{height},{}\n",
+                render.iteration_max
+            );
+            for row_index in 0..height {
+                let row = (0..width)
+                    .map(|column_index| {
+                        let pixel_index = render.data.pixel_index(row_index, column_index);
+                        render
+                            .data
+                            .escape_time(pixel_index)
+                            .map(|escape_time| escape_time.to_string())
+                            .unwrap_or_default()
+                    })
+                    .collect::<Vec<_>>();
+                csv.push_str(&row.join(","));
+                csv.push('\n');
+            }
+            std::fs::write(&csv_path, csv)
+                .map_err(|error| format!("Failed to write {csv_path:?}: {error}"))?;
+            Ok(vec![csv_path])
+        }
+    }
+}
+
+/// A single part, uncompressed, scanline OpenEXR file of 32 bit float `channels`, one value per pixel in row major order.
+/// `view` is stored in a string attribute
+fn encode_exr(
+    width: usize,
+    height: usize,
+    channels: &mut [(&str, Vec<f32>)],
+    view: &str,
+) -> Vec<u8> {
+    const MAGIC: u32 = 20000630;
+    /// version 2, with no flags for tiles, long names, or multiple parts
+    const VERSION: u32 = 2;
+    const PIXEL_TYPE_FLOAT: i32 = 2;
+
+    fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+        for text in [name, kind] {
+            header.extend_from_slice(text.as_bytes());
+            header.push(0);
+        }
+        header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+        header.extend_from_slice(value);
+    }
+
+    // readers expect the channels in alphabetical order, in the list and in each scanline
+    channels.sort_by_key(|&(name, _)| name);
+    let (width_i32, height_i32) = (width as i32, height as i32);
+
+    let mut channel_list = Vec::new();
+    for (name, _) in channels.iter() {
+        channel_list.extend_from_slice(name.as_bytes());
+        channel_list.push(0);
+        channel_list.extend_from_slice(&PIXEL_TYPE_FLOAT.to_le_bytes());
+        // not perceptually linear, and 3 reserved bytes
+        channel_list.extend_from_slice(&[0; 4]);
+        // no subsampling in x or y
+        channel_list.extend_from_slice(&1_i32.to_le_bytes());
+        channel_list.extend_from_slice(&1_i32.to_le_bytes());
+    }
+    channel_list.push(0);
+    let window = [0, 0, width_i32 - 1, height_i32 - 1]
+        .iter()
+        .flat_map(|coordinate: &i32| coordinate.to_le_bytes())
+        .collect::<Vec<_>>();
+
+    let mut file = Vec::new();
+    file.extend_from_slice(&MAGIC.to_le_bytes());
+    file.extend_from_slice(&VERSION.to_le_bytes());
+    attribute(&mut file, "channels", "chlist", &channel_list);
+    // no compression
+    attribute(&mut file, "compression", "compression", &[0]);
+    attribute(&mut file, "dataWindow", "box2i", &window);
+    attribute(&mut file, "displayWindow", "box2i", &window);
+    // increasing y
+    attribute(&mut file, "lineOrder", "lineOrder", &[0]);
+    attribute(
+        &mut file,
+        "pixelAspectRatio",
+        "float",
+        &1.0_f32.to_le_bytes(),
+    );
+    attribute(&mut file, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(
+        &mut file,
+        "screenWindowWidth",
+        "float",
+        &1.0_f32.to_le_bytes(),
+    );
+    attribute(&mut file, "mandelbrotView", "string", view.as_bytes());
+    file.push(0);
+
+    // uncompressed files have one scanline per chunk, each starting with its y and its size in bytes
+    let scanline_size = width * channels.len() * size_of::<f32>();
+    let chunk_size = 2 * size_of::<i32>() + scanline_size;
+    let chunks_start = file.len() + height * size_of::<u64>();
+    for row_index in 0..height {
+        let offset = chunks_start + row_index * chunk_size;
+        file.extend_from_slice(&(offset as u64).to_le_bytes());
+    }
+    for row_index in 0..height {
+        file.extend_from_slice(&(row_index as i32).to_le_bytes());
+        file.extend_from_slice(&(scanline_size as i32).to_le_bytes());
+        for (_, values) in channels.iter() {
+            for value in &values[row_index * width..(row_index + 1) * width] {
+                file.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+    file
+}
@@ -0,0 +1,442 @@
+//! Views calculated without holding up the window, so it keeps responding during long renders and can show how far along they are.
+//! Natively the view is calculated on a worker thread. The browser has no threads, so there it's calculated
+//! a band of rows at a time over several frames.
+//! Before the full view, the main formula is calculated at a few coarser resolutions to show something right away.
+//! Views that reuse the one before them, after panning or raising the iteration limit, are calculated the same way but without previews
+
+use crate::{
+    MandelbrotRender, Settings,
+    coloring::{Coloring, create_mandelbrot_image},
+    formula::{FormulaEntry, find_formula},
+};
+use macroquad::prelude::{FilterMode, Texture2D, get_time};
+use mandelbrot_core::{EscapeGrid, FractalFormula, RenderJob};
+use num::Complex;
+
+use std::sync::atomic::AtomicBool;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+    mpsc::{Receiver, TryRecvError},
+};
+
+#[cfg(target_arch = "wasm32")]
+use mandelbrot_core::PixelRect;
+
+/// Rows calculated at a time. Each band is calculated in one go, so deep zooms with high iteration limits need small bands
+#[cfg(target_arch = "wasm32")]
+const BAND_HEIGHT: usize = 8;
+
+/// Seconds of each frame spent calculating bands. At least one band is calculated every frame
+#[cfg(target_arch = "wasm32")]
+const FRAME_BUDGET: f64 = 1.0 / 60.0;
+
+/// The previews are the size of the view divided by each of these, coarsest first.
+/// Together they cost about a third of the full view
+const PREVIEW_DIVISORS: [usize; 3] = [8, 4, 2];
+
+type FormulaJob = RenderJob<f32, &'static (dyn FractalFormula<f32> + Send)>;
+
+/// What a view keeps of the view before it, so only what changed is calculated
+pub enum Reuse {
+    /// the view before, with its center `row_shift` rows and `column_shift` columns away, see `RenderJob::run_shifted`
+    Shifted {
+        grid: EscapeGrid,
+        row_shift: isize,
+        column_shift: isize,
+    },
+    /// the view before, with a lower iteration limit, see `RenderJob::continue_iterations`
+    Continued(EscapeGrid),
+}
+
+/// Calculate `job`, from what `reuse` kept of the view before if there is one
+fn calculate(
+    job: &FormulaJob,
+    reuse: Option<&Reuse>,
+    progress: impl Fn(usize, usize) + Sync,
+    cancel: &AtomicBool,
+) -> Result<EscapeGrid, mandelbrot_core::Error> {
+    match reuse {
+        None => job.run_with_progress(progress, cancel),
+        Some(Reuse::Shifted {
+            grid,
+            row_shift,
+            column_shift,
+        }) => job.run_shifted_with_progress(grid, *row_shift, *column_shift, progress, cancel),
+        Some(Reuse::Continued(grid)) => {
+            job.continue_iterations_with_progress(grid, progress, cancel)
+        }
+    }
+}
+
+/// A view that's being calculated
+pub struct RenderTask {
+    pub center: Complex<f32>,
+    pub dimensions: Complex<f32>,
+    /// degrees counterclockwise, see `Settings::rotation`
+    pub rotation: f32,
+    pub iteration_max: usize,
+    /// when the task was created, from `get_time`
+    started_at: f64,
+    /// the view of the main formula first, then the comparison
+    formulas: Vec<FormulaTask>,
+    /// the finest preview of the main formula so far, to be stretched over the view
+    preview: Option<Texture2D>,
+    /// the previews still to calculate in the browser, coarsest first
+    #[cfg(target_arch = "wasm32")]
+    preview_jobs: std::vec::IntoIter<FormulaJob>,
+    #[cfg(not(target_arch = "wasm32"))]
+    worker: Worker,
+}
+
+/// What the worker sends back
+#[cfg(not(target_arch = "wasm32"))]
+enum WorkerMessage {
+    Preview(EscapeGrid),
+    /// the grid of each formula
+    Finished(Vec<EscapeGrid>),
+}
+
+/// A thread calculating the previews, then the view of every formula one after another
+#[cfg(not(target_arch = "wasm32"))]
+struct Worker {
+    /// rows of the full view finished so far, over every formula
+    rows_done: Arc<AtomicUsize>,
+    /// the cancellation token of the job being calculated. Set when the task is dropped
+    cancel: Arc<AtomicBool>,
+    messages: Receiver<WorkerMessage>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Worker {
+    fn spawn(preview_jobs: Vec<FormulaJob>, jobs: Vec<(FormulaJob, Option<Reuse>)>) -> Self {
+        let rows_done = Arc::new(AtomicUsize::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, messages) = std::sync::mpsc::channel();
+        {
+            let rows_done = Arc::clone(&rows_done);
+            let cancel = Arc::clone(&cancel);
+            // sending fails once the task is dropped, and then nobody wants the rest either
+            std::thread::spawn(
+                move || -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+                    for job in preview_jobs {
+                        let preview = job.run_with_progress(|_, _| {}, &cancel)?;
+                        sender.send(WorkerMessage::Preview(preview))?;
+                    }
+
+                    let count_row = |_: usize, _: usize| {
+                        rows_done.fetch_add(1, Ordering::Relaxed);
+                    };
+                    let grids = jobs
+                        .iter()
+                        .map(|(job, reuse)| calculate(job, reuse.as_ref(), &count_row, &cancel))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    sender.send(WorkerMessage::Finished(grids))?;
+                    Ok(())
+                },
+            );
+        }
+        Self {
+            rows_done,
+            cancel,
+            messages,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The view of one formula
+struct FormulaTask {
+    job: FormulaJob,
+    formula: &'static FormulaEntry,
+    /// the rows calculated so far, from the first row on. `None` until the first band is done,
+    /// or natively until the worker is done
+    data: Option<EscapeGrid>,
+    /// what's kept of the view before, until the view is calculated from it. Natively the worker has it
+    #[cfg(target_arch = "wasm32")]
+    reuse: Option<Reuse>,
+}
+
+impl FormulaTask {
+    fn rows_total(&self) -> usize {
+        self.job.full_rect().height
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn rows_done(&self) -> usize {
+        self.data.as_ref().map_or(0, EscapeGrid::height)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn calculate_band(&mut self) {
+        // only what changed is calculated, so it's done in one go
+        if let Some(reuse) = self.reuse.take() {
+            let data = calculate(&self.job, Some(&reuse), |_, _| {}, &AtomicBool::new(false))
+                .expect(
+                    "the view before came from this job with another center or iteration limit",
+                );
+            self.data = Some(data);
+            return;
+        }
+
+        let full_rect = self.job.full_rect();
+        let row = self.rows_done();
+        let band = self
+            .job
+            .run_tile(PixelRect {
+                row,
+                column: 0,
+                width: full_rect.width,
+                height: BAND_HEIGHT.min(full_rect.height - row),
+            })
+            .expect("bands are inside the image");
+        match &mut self.data {
+            Some(data) => data
+                .append_rows(band)
+                .expect("every band is calculated by the same job"),
+            None => self.data = Some(band),
+        }
+    }
+}
+
+impl RenderTask {
+    /// Get ready to calculate the view of the formula (and comparison formula) in `settings`, at the size of the window
+    pub fn new(
+        center: Complex<f32>,
+        dimensions: Complex<f32>,
+        iteration_max: usize,
+        settings: &Settings,
+    ) -> Self {
+        let formulas: Vec<_> = std::iter::once(settings.formula.as_str())
+            .chain(settings.compare_formula.as_deref())
+            .map(|name| {
+                let formula = find_formula(name);
+                let job = MandelbrotRender::formula_job(
+                    center,
+                    dimensions,
+                    iteration_max,
+                    formula,
+                    settings,
+                );
+                (job, formula, None)
+            })
+            .collect();
+
+        // previews are only looked at, so they don't need paths
+        let full_rect = formulas[0].0.full_rect();
+        let preview_jobs = PREVIEW_DIVISORS
+            .iter()
+            .map(|divisor| {
+                formulas[0]
+                    .0
+                    .clone()
+                    .resolution(
+                        (full_rect.width / divisor).max(1),
+                        (full_rect.height / divisor).max(1),
+                    )
+                    .store_paths(false)
+                    .orbit_statistics(true)
+            })
+            .collect::<Vec<_>>();
+
+        Self::start(
+            center,
+            dimensions,
+            settings.rotation,
+            iteration_max,
+            formulas,
+            preview_jobs,
+        )
+    }
+
+    /// Get ready to calculate a view from what `Reuse` kept of the view before it.
+    /// `formulas` are the job, formula, and reused view of the main formula, then of the comparison.
+    /// The view before stays on screen meanwhile, so there are no previews
+    pub fn reuse(
+        center: Complex<f32>,
+        dimensions: Complex<f32>,
+        rotation: f32,
+        iteration_max: usize,
+        formulas: Vec<(FormulaJob, &'static FormulaEntry, Reuse)>,
+    ) -> Self {
+        let formulas = formulas
+            .into_iter()
+            .map(|(job, formula, reuse)| (job, formula, Some(reuse)))
+            .collect();
+        Self::start(
+            center,
+            dimensions,
+            rotation,
+            iteration_max,
+            formulas,
+            Vec::new(),
+        )
+    }
+
+    fn start(
+        center: Complex<f32>,
+        dimensions: Complex<f32>,
+        rotation: f32,
+        iteration_max: usize,
+        formulas: Vec<(FormulaJob, &'static FormulaEntry, Option<Reuse>)>,
+        preview_jobs: Vec<FormulaJob>,
+    ) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let (formulas, jobs): (Vec<_>, Vec<_>) = formulas
+            .into_iter()
+            .map(|(job, formula, reuse)| {
+                let task = FormulaTask {
+                    job: job.clone(),
+                    formula,
+                    data: None,
+                };
+                (task, (job, reuse))
+            })
+            .unzip();
+        #[cfg(target_arch = "wasm32")]
+        let formulas = formulas
+            .into_iter()
+            .map(|(job, formula, reuse)| FormulaTask {
+                job,
+                formula,
+                data: None,
+                reuse,
+            })
+            .collect();
+
+        Self {
+            center,
+            dimensions,
+            rotation,
+            iteration_max,
+            started_at: get_time(),
+            preview: None,
+            #[cfg(target_arch = "wasm32")]
+            preview_jobs: preview_jobs.into_iter(),
+            #[cfg(not(target_arch = "wasm32"))]
+            worker: Worker::spawn(preview_jobs, jobs),
+            formulas,
+        }
+    }
+
+    /// The width and height of the view in pixels
+    pub fn size(&self) -> (usize, usize) {
+        let full_rect = self.formulas[0].job.full_rect();
+        (full_rect.width, full_rect.height)
+    }
+
+    /// The finest preview of the main formula so far, which has to be stretched to `size`
+    pub fn preview(&self) -> Option<&Texture2D> {
+        self.preview.as_ref()
+    }
+
+    /// How much of the full view has been calculated, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        #[cfg(not(target_arch = "wasm32"))]
+        let rows_done = self.worker.rows_done.load(Ordering::Relaxed);
+        #[cfg(target_arch = "wasm32")]
+        let rows_done = self
+            .formulas
+            .iter()
+            .map(FormulaTask::rows_done)
+            .sum::<usize>();
+        let rows_total = self
+            .formulas
+            .iter()
+            .map(FormulaTask::rows_total)
+            .sum::<usize>();
+        if rows_total == 0 {
+            1.0
+        } else {
+            rows_done as f32 / rows_total as f32
+        }
+    }
+
+    /// Show `preview` until something finer is ready
+    fn set_preview(&mut self, preview: &EscapeGrid, coloring: &Coloring) {
+        let image = create_mandelbrot_image(preview, self.iteration_max, coloring)
+            .expect("previews are smaller than the view");
+        let texture = Texture2D::from_image(&image);
+        // blocky, so it doesn't look like a blurry version of the finished view
+        texture.set_filter(FilterMode::Nearest);
+        self.preview = Some(texture);
+    }
+
+    /// Collect what the worker has finished. Returns whether the whole view is done
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn step(&mut self, coloring: &Coloring) -> bool {
+        loop {
+            match self.worker.messages.try_recv() {
+                Ok(WorkerMessage::Preview(preview)) => self.set_preview(&preview, coloring),
+                Ok(WorkerMessage::Finished(grids)) => {
+                    for (task, grid) in self.formulas.iter_mut().zip(grids) {
+                        task.data = Some(grid);
+                    }
+                    return true;
+                }
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => {
+                    panic!("the render thread stopped without a view")
+                }
+            }
+        }
+    }
+
+    /// Calculate the next preview, or bands for this frame's share of time. Returns whether the whole view is done
+    #[cfg(target_arch = "wasm32")]
+    pub fn step(&mut self, coloring: &Coloring) -> bool {
+        if let Some(preview_job) = self.preview_jobs.next() {
+            self.set_preview(&preview_job.run(), coloring);
+            return false;
+        }
+
+        let start_time = get_time();
+        loop {
+            let Some(task) = self
+                .formulas
+                .iter_mut()
+                .find(|task| task.rows_done() < task.rows_total())
+            else {
+                return true;
+            };
+            task.calculate_band();
+            if get_time() - start_time >= FRAME_BUDGET {
+                return false;
+            }
+        }
+    }
+
+    /// Color the finished view
+    pub fn finish(self, coloring: &Coloring) -> MandelbrotRender {
+        let center = self.center;
+        let dimensions = self.dimensions;
+        let rotation = self.rotation;
+        let render_seconds = get_time() - self.started_at;
+        // natively this drops the worker, which has already finished
+        let mut renders = self.formulas.into_iter().map(|task| {
+            // a window with no height has no bands
+            let data = task.data.unwrap_or_else(|| task.job.run());
+            MandelbrotRender::from_data(
+                task.job,
+                data,
+                center,
+                dimensions,
+                rotation,
+                task.formula,
+                coloring,
+            )
+        });
+        let mut render = renders.next().expect("there is always the main formula");
+        render.comparison = renders.next().map(Box::new);
+        render.render_seconds = render_seconds;
+        render
+    }
+}
@@ -0,0 +1,380 @@
+//! Restoring earlier sessions.
+//! While the app runs the session is written to a recovery file every few seconds, and the file is removed on a clean exit.
+//! A clean exit also leaves a marker file, which is removed on launch. If the recovery file is there without the marker
+//! then the last run didn't exit cleanly, so we offer to restore it.
+//! A clean exit saves the session to its own file instead, which is offered on the next launch too unless it's where the app starts.
+//! Discarding the offered session deletes its file
+
+use crate::{
+    Settings,
+    orbit::{OrbitColors, OrbitSource},
+    palette::Palette,
+};
+use macroquad::{
+    prelude::*,
+    ui::{hash, root_ui, widgets::Window},
+};
+use num::Complex;
+use std::str::FromStr;
+
+const RECOVERY_PATH: &str = "mandelbrot_recovery.txt";
+const LAST_SESSION_PATH: &str = "mandelbrot_last_session.txt";
+/// exists from a clean exit until the next launch
+const CLEAN_EXIT_PATH: &str = "mandelbrot_clean_exit";
+
+/// seconds between autosaves
+const AUTOSAVE_INTERVAL: f64 = 5.0;
+
+/// there is no file system to save to in the browser
+const AUTOSAVE_ENABLED: bool = cfg!(not(target_arch = "wasm32"));
+
+/// Everything needed to get back to where the user was
+#[derive(Clone, PartialEq)]
+pub struct Session {
+    pub center: Complex<f32>,
+    pub scale: f32,
+    pub iteration_max: usize,
+    pub settings: Settings,
+}
+
+impl Session {
+    /// One `key=value` pair per line
+    pub fn encode(&self) -> String {
+        let Self {
+            center,
+            scale,
+            iteration_max,
+            settings,
+        } = self;
+
+        let entries = [
+            ("center_re", center.re.to_string()),
+            ("center_im", center.im.to_string()),
+            ("scale", scale.to_string()),
+            ("iteration_max", iteration_max.to_string()),
+            (
+                "orbit_source",
+                OrbitSource::NAMES[settings.orbit_source as usize].to_owned(),
+            ),
+            ("show_orbit", settings.show_orbit.to_string()),
+            ("orbit_playback", settings.orbit_playback.to_string()),
+            (
+                "orbit_line_thickness",
+                settings.orbit_style.line_thickness.to_string(),
+            ),
+            ("orbit_dot_size", settings.orbit_style.dot_size.to_string()),
+            (
+                "orbit_colors",
+                OrbitColors::NAMES[settings.orbit_style.colors as usize].to_owned(),
+            ),
+            (
+                "orbit_point_max",
+                settings.orbit_style.point_max.to_string(),
+            ),
+            ("steps_past_escape", settings.steps_past_escape.to_string()),
+            ("show_bailout", settings.show_bailout.to_string()),
+            ("show_contours", settings.show_contours.to_string()),
+            ("contour_spacing", settings.contour_spacing.to_string()),
+            ("label_bulbs", settings.label_bulbs.to_string()),
+            (
+                "show_iteration_profile",
+                settings.show_iteration_profile.to_string(),
+            ),
+            ("show_cost_heatmap", settings.show_cost_heatmap.to_string()),
+            ("show_histogram", settings.show_histogram.to_string()),
+            (
+                "profile_region_size",
+                settings.profile_region_size.to_string(),
+            ),
+            ("auto_iterations", settings.auto_iterations.to_string()),
+            ("auto_render", settings.auto_render.to_string()),
+            ("escape_times_only", settings.escape_times_only.to_string()),
+            ("show_minimap", settings.show_minimap.to_string()),
+            (
+                "show_julia_preview",
+                settings.show_julia_preview.to_string(),
+            ),
+            (
+                "julia_side_by_side",
+                settings.julia_side_by_side.to_string(),
+            ),
+            ("rotation", settings.rotation.to_string()),
+            ("fullscreen", settings.fullscreen.to_string()),
+            ("show_performance", settings.show_performance.to_string()),
+            ("show_tooltip", settings.show_tooltip.to_string()),
+            ("color_cycling", settings.color_cycling.to_string()),
+            ("color_cycle_speed", settings.color_cycle_speed.to_string()),
+            ("auto_ui_scale", settings.auto_ui_scale.to_string()),
+            ("ui_scale", settings.ui_scale.to_string()),
+            ("formula", settings.formula.clone()),
+            (
+                "compare_formula",
+                settings.compare_formula.clone().unwrap_or_default(),
+            ),
+            ("colorizer", settings.coloring.colorizer.clone()),
+            ("coloring_exponent", settings.coloring.exponent.to_string()),
+            (
+                "palette",
+                Palette::NAMES[settings.coloring.palette as usize].to_owned(),
+            ),
+            (
+                "palette_density",
+                settings.coloring.palette_density.to_string(),
+            ),
+            (
+                "palette_offset",
+                settings.coloring.palette_offset.to_string(),
+            ),
+            ("slope_shading", settings.coloring.slope_shading.to_string()),
+            ("light_azimuth", settings.coloring.light_azimuth.to_string()),
+            (
+                "light_elevation",
+                settings.coloring.light_elevation.to_string(),
+            ),
+        ];
+
+        entries
+            .iter()
+            .map(|(key, value)| format!("{key}={value}\n"))
+            .collect()
+    }
+
+    /// Unknown keys and malformed values are skipped, so an old or damaged file still restores as much as it can
+    pub fn decode(text: &str) -> Self {
+        fn parse_into<T: FromStr>(value: &str, field: &mut T) {
+            if let Ok(value) = value.trim().parse() {
+                *field = value;
+            }
+        }
+
+        let mut session = Self {
+            center: Complex::new(-0.4, 0.0),
+            scale: 1.0,
+            iteration_max: 500,
+            settings: Settings::default(),
+        };
+
+        for (key, value) in text.lines().filter_map(|line| line.split_once('=')) {
+            match key.trim() {
+                "center_re" => parse_into(value, &mut session.center.re),
+                "center_im" => parse_into(value, &mut session.center.im),
+                "scale" => parse_into(value, &mut session.scale),
+                "iteration_max" => parse_into(value, &mut session.iteration_max),
+                "orbit_source" => {
+                    if let Some(index) = OrbitSource::NAMES.iter().position(|&name| name == value) {
+                        session.settings.orbit_source = OrbitSource::ALL[index];
+                    }
+                }
+                "show_orbit" => parse_into(value, &mut session.settings.show_orbit),
+                "orbit_playback" => parse_into(value, &mut session.settings.orbit_playback),
+                "orbit_line_thickness" => {
+                    parse_into(value, &mut session.settings.orbit_style.line_thickness)
+                }
+                "orbit_dot_size" => parse_into(value, &mut session.settings.orbit_style.dot_size),
+                "orbit_colors" => {
+                    if let Some(index) = OrbitColors::NAMES.iter().position(|&name| name == value) {
+                        session.settings.orbit_style.colors = OrbitColors::ALL[index];
+                    }
+                }
+                "orbit_point_max" => parse_into(value, &mut session.settings.orbit_style.point_max),
+                "steps_past_escape" => parse_into(value, &mut session.settings.steps_past_escape),
+                "show_bailout" => parse_into(value, &mut session.settings.show_bailout),
+                "show_contours" => parse_into(value, &mut session.settings.show_contours),
+                "contour_spacing" => parse_into(value, &mut session.settings.contour_spacing),
+                "label_bulbs" => parse_into(value, &mut session.settings.label_bulbs),
+                "show_iteration_profile" => {
+                    parse_into(value, &mut session.settings.show_iteration_profile)
+                }
+                "show_cost_heatmap" => parse_into(value, &mut session.settings.show_cost_heatmap),
+                "show_histogram" => parse_into(value, &mut session.settings.show_histogram),
+                "profile_region_size" => {
+                    parse_into(value, &mut session.settings.profile_region_size)
+                }
+                "auto_iterations" => parse_into(value, &mut session.settings.auto_iterations),
+                "auto_render" => parse_into(value, &mut session.settings.auto_render),
+                "escape_times_only" => parse_into(value, &mut session.settings.escape_times_only),
+                "show_minimap" => parse_into(value, &mut session.settings.show_minimap),
+                "show_julia_preview" => parse_into(value, &mut session.settings.show_julia_preview),
+                "julia_side_by_side" => parse_into(value, &mut session.settings.julia_side_by_side),
+                "rotation" => parse_into(value, &mut session.settings.rotation),
+                "fullscreen" => parse_into(value, &mut session.settings.fullscreen),
+                "show_performance" => parse_into(value, &mut session.settings.show_performance),
+                "show_tooltip" => parse_into(value, &mut session.settings.show_tooltip),
+                "color_cycling" => parse_into(value, &mut session.settings.color_cycling),
+                "color_cycle_speed" => parse_into(value, &mut session.settings.color_cycle_speed),
+                "auto_ui_scale" => parse_into(value, &mut session.settings.auto_ui_scale),
+                "ui_scale" => parse_into(value, &mut session.settings.ui_scale),
+                "formula" => session.settings.formula = value.to_owned(),
+                "compare_formula" => {
+                    session.settings.compare_formula = (!value.is_empty()).then(|| value.to_owned())
+                }
+                "colorizer" => session.settings.coloring.colorizer = value.to_owned(),
+                "coloring_exponent" => parse_into(value, &mut session.settings.coloring.exponent),
+                "palette" => {
+                    if let Some(palette) = Palette::from_name(value) {
+                        session.settings.coloring.palette = palette;
+                    }
+                }
+                "palette_density" => {
+                    parse_into(value, &mut session.settings.coloring.palette_density)
+                }
+                "palette_offset" => {
+                    parse_into(value, &mut session.settings.coloring.palette_offset)
+                }
+                "slope_shading" => parse_into(value, &mut session.settings.coloring.slope_shading),
+                "light_azimuth" => parse_into(value, &mut session.settings.coloring.light_azimuth),
+                "light_elevation" => {
+                    parse_into(value, &mut session.settings.coloring.light_elevation)
+                }
+                _ => {}
+            }
+        }
+
+        session
+    }
+
+    /// Call on a clean exit. Saves the session for the next launch, removes the recovery file, and leaves the clean exit marker
+    pub fn save_on_exit(&self) {
+        if !AUTOSAVE_ENABLED {
+            return;
+        }
+        if let Err(error) = std::fs::write(LAST_SESSION_PATH, self.encode()) {
+            eprintln!("Failed to save session: {error}");
+        }
+        // the file won't exist if nothing was ever autosaved
+        let _ = std::fs::remove_file(RECOVERY_PATH);
+        if let Err(error) = std::fs::write(CLEAN_EXIT_PATH, "") {
+            eprintln!("Failed to mark the exit as clean: {error}");
+        }
+    }
+}
+
+/// A session from an earlier run, offered on launch
+pub struct SavedSession {
+    session: Session,
+    /// whether the run it came from crashed or was killed
+    crashed: bool,
+}
+
+impl SavedSession {
+    /// The session left behind by a run that didn't exit cleanly, or else the session of the last clean exit
+    /// if it went anywhere. Call once on launch, it removes the clean exit marker
+    pub fn load() -> Option<Self> {
+        if !AUTOSAVE_ENABLED {
+            return None;
+        }
+        // put back by a clean exit, so it's missing after a crash
+        let exited_cleanly = std::fs::remove_file(CLEAN_EXIT_PATH).is_ok();
+        let load = |path| {
+            std::fs::read_to_string(path)
+                .ok()
+                .map(|text| Session::decode(&text))
+        };
+        let crashed_session = (!exited_cleanly)
+            .then(|| load(RECOVERY_PATH))
+            .flatten()
+            .map(|session| Self {
+                session,
+                crashed: true,
+            });
+        crashed_session.or_else(|| {
+            load(LAST_SESSION_PATH)
+                // a session that's where the app starts has nothing to restore
+                .filter(|session| *session != Session::decode(""))
+                .map(|session| Self {
+                    session,
+                    crashed: false,
+                })
+        })
+    }
+
+    /// Delete the files the session was loaded from, so it isn't offered again
+    fn discard(&self) {
+        let path = if self.crashed {
+            RECOVERY_PATH
+        } else {
+            LAST_SESSION_PATH
+        };
+        if let Err(error) = std::fs::remove_file(path) {
+            eprintln!("Failed to discard the session: {error}");
+        }
+    }
+}
+
+/// Writes the session to the recovery file when it has changed
+pub struct Autosave {
+    last_save_time: f64,
+    last_saved: Option<Session>,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            last_save_time: get_time(),
+            last_saved: None,
+        }
+    }
+}
+
+impl Autosave {
+    /// Call every frame. Only touches the file system every `AUTOSAVE_INTERVAL` seconds
+    pub fn update(&mut self, session: impl FnOnce() -> Session) {
+        if !AUTOSAVE_ENABLED || get_time() - self.last_save_time < AUTOSAVE_INTERVAL {
+            return;
+        }
+        self.last_save_time = get_time();
+
+        let session = session();
+        if self.last_saved.as_ref() == Some(&session) {
+            return;
+        }
+        if let Err(error) = std::fs::write(RECOVERY_PATH, session.encode()) {
+            eprintln!("Failed to autosave session: {error}");
+        }
+        self.last_saved = Some(session);
+    }
+}
+
+/// Ask whether to restore the `recovered` session.
+/// Returns the session when the user chooses to restore it, and clears `recovered` once they choose either way
+pub fn recovery_window(recovered: &mut Option<SavedSession>) -> Option<Session> {
+    let SavedSession { session, crashed } = recovered.as_ref()?;
+
+    let window_size = vec2(300.0, 170.0);
+    let window_position = (vec2(screen_width(), screen_height()) - window_size) / 2.0;
+    let mut restore = false;
+    let mut discard = false;
+    Window::new(hash!(), window_position, window_size)
+        .label("restore session")
+        .titlebar(true)
+        .ui(&mut *root_ui(), |ui| {
+            if *crashed {
+                ui.label(None, "The last session didn't exit cleanly.");
+            } else {
+                ui.label(None, "Continue where the last session left off?");
+            }
+            ui.label(None, &format!("center: {}", session.center));
+            ui.label(None, &format!("scale: {}", session.scale));
+            ui.label(None, &format!("iterations: {}", session.iteration_max));
+            ui.label(None, &format!("formula: {}", session.settings.formula));
+            ui.label(
+                None,
+                &format!(
+                    "palette: {}",
+                    Palette::NAMES[session.settings.coloring.palette as usize]
+                ),
+            );
+            restore = ui.button(None, "Restore");
+            ui.same_line(0.0);
+            discard = ui.button(None, "Discard");
+        });
+
+    if restore {
+        recovered.take().map(|recovered| recovered.session)
+    } else {
+        if discard && let Some(recovered) = recovered.take() {
+            recovered.discard();
+        }
+        None
+    }
+}
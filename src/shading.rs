@@ -0,0 +1,69 @@
+use macroquad::prelude::*;
+use num::Complex;
+
+// how the surface reflects light
+const AMBIENT: f32 = 0.2;
+const SPECULAR: f32 = 0.4;
+const SHININESS: f32 = 20.0;
+
+/// Unit vector pointing towards a light at `azimuth` degrees around and `elevation` degrees above the complex plane
+pub fn light_direction(azimuth: f32, elevation: f32) -> Vec3 {
+    let (azimuth, elevation) = (azimuth.to_radians(), elevation.to_radians());
+    vec3(
+        elevation.cos() * azimuth.cos(),
+        elevation.cos() * azimuth.sin(),
+        elevation.sin(),
+    )
+}
+
+/// The derivative `dzₙ/dc` of the last z value of an escape path.
+/// Uses `dzₙ₊₁/dc = 2·zₙ·dzₙ/dc + 1` starting with `dz₀/dc = 0`
+pub fn escape_derivative(escape_path: &[Complex<f32>]) -> Complex<f32> {
+    escape_path
+        .iter()
+        .take(escape_path.len().saturating_sub(1))
+        .fold(Complex::new(0.0, 0.0), |derivative, &z| {
+            2.0 * z * derivative + 1.0
+        })
+}
+
+/// How far the end of an escape path is from the set, `2·|z|·ln|z| / |dz/dc|`.
+/// Returns `None` if the derivative overflowed
+pub fn distance_estimate(escape_path: &[Complex<f32>]) -> Option<f32> {
+    let last_z = escape_path.last()?.norm();
+    let distance = 2.0 * last_z * last_z.ln() / escape_derivative(escape_path).norm();
+    distance.is_finite().then_some(distance)
+}
+
+/// Treat the potential around the set as a height map and find its normal at the end of an escape path.
+/// Returns `None` if the derivative overflowed
+fn surface_normal(escape_path: &[Complex<f32>]) -> Option<Vec3> {
+    let &last_z = escape_path.last()?;
+    let direction = last_z / escape_derivative(escape_path);
+    if !direction.is_finite() || direction.norm_sqr() == 0.0 {
+        return None;
+    }
+    let direction = direction / direction.norm();
+
+    Some(vec3(direction.re, direction.im, 1.0).normalize())
+}
+
+/// Apply Blinn-Phong lighting to the color of an escaped point
+pub fn shade(color: Color, escape_path: &[Complex<f32>], light: Vec3) -> Color {
+    let Some(normal) = surface_normal(escape_path) else {
+        return color;
+    };
+
+    // the viewer looks straight down at the plane
+    let halfway = (light + Vec3::Z).normalize();
+    let diffuse = normal.dot(light).max(0.0);
+    let specular = normal.dot(halfway).max(0.0).powf(SHININESS) * SPECULAR;
+    let brightness = AMBIENT + (1.0 - AMBIENT) * diffuse;
+
+    Color::new(
+        (color.r * brightness + specular).min(1.0),
+        (color.g * brightness + specular).min(1.0),
+        (color.b * brightness + specular).min(1.0),
+        color.a,
+    )
+}
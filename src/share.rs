@@ -0,0 +1,204 @@
+//! Share views through a simple paste service.
+//! `Share` POSTs the encoded session to the configured endpoint, which responds with a link to the paste.
+//! `Open Shared` GETs a link and restores the location and coloring it contains.
+//! Only plain `http://` links are supported.
+//! Requests run on another thread, so a slow or unreachable server doesn't freeze the window.
+
+use crate::session::Session;
+use macroquad::{
+    prelude::*,
+    ui::{hash, root_ui, widgets::Window},
+};
+use std::{
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::mpsc::{Receiver, TryRecvError},
+    time::Duration,
+};
+
+type CoreError = Box<dyn core::error::Error>;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The endpoint can be set with this environment variable, and edited in the share window
+const ENDPOINT_VARIABLE: &str = "MANDELBROT_PASTE_ENDPOINT";
+const DEFAULT_ENDPOINT: &str = "http://localhost:8080/";
+
+/// The keys a response has to have to be a shared view
+const REQUIRED_KEYS: [&str; 3] = ["center_re", "center_im", "scale"];
+
+/// What a request in flight was for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestKind {
+    Share,
+    Open,
+}
+
+/// What the share window shows between frames
+pub struct ShareState {
+    /// where shared views are POSTed
+    endpoint: String,
+    /// the link returned by the paste service, or pasted in by the user
+    link: String,
+    /// the result of the last share or open
+    status: String,
+    /// the request in flight and where its response will arrive
+    request: Option<(RequestKind, Receiver<Result<String, String>>)>,
+}
+
+impl Default for ShareState {
+    fn default() -> Self {
+        Self {
+            endpoint: std::env::var(ENDPOINT_VARIABLE)
+                .unwrap_or_else(|_| String::from(DEFAULT_ENDPOINT)),
+            link: String::new(),
+            status: String::new(),
+            request: None,
+        }
+    }
+}
+
+/// Split `http://host[:port]/path` into a socket address, the host header, and the path
+fn parse_http_url(url: &str) -> Result<(String, &str, &str), CoreError> {
+    let rest = url
+        .trim()
+        .strip_prefix("http://")
+        .ok_or("Only http:// links are supported")?;
+    let (host, path) = match rest.find('/') {
+        Some(path_start) => rest.split_at(path_start),
+        None => (rest, "/"),
+    };
+    let address = if host.contains(':') {
+        host.to_owned()
+    } else {
+        format!("{host}:80")
+    };
+
+    Ok((address, host, path))
+}
+
+/// Send a request and return the body of a successful response
+fn http_request(method: &str, url: &str, body: &str) -> Result<String, CoreError> {
+    let (address, host, path) = parse_http_url(url)?;
+    let address = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| format!("Failed to resolve {host}"))?;
+
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    // HTTP/1.0 so the server won't use a chunked response
+    let request = format!(
+        "{method} {path} HTTP/1.0\r\nHost: {host}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes())?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("Malformed response")?;
+    let status_line = head.lines().next().unwrap_or_default();
+    let status_code = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if !status_code.starts_with('2') {
+        return Err(format!("Server responded with {status_line}").into());
+    }
+
+    Ok(body.to_owned())
+}
+
+/// Send a request on another thread. Its result arrives on the returned channel
+fn spawn_request(
+    method: &'static str,
+    url: String,
+    body: String,
+) -> Receiver<Result<String, String>> {
+    let (sender, response) = std::sync::mpsc::channel();
+    let request = move || {
+        // nobody is waiting for the response if the window was closed
+        let _ = sender.send(http_request(method, &url, &body).map_err(|error| error.to_string()));
+    };
+    // the browser has no threads, and fails to connect straight away
+    #[cfg(not(target_arch = "wasm32"))]
+    std::thread::spawn(request);
+    #[cfg(target_arch = "wasm32")]
+    request();
+    response
+}
+
+/// The session in a response, if it is one. Any other page, like an error page
+/// that was served with a success status, would otherwise open as the default view
+fn shared_session(text: &str) -> Result<Session, String> {
+    for required_key in REQUIRED_KEYS {
+        let has_number = text
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .any(|(key, value)| key.trim() == required_key && value.trim().parse::<f32>().is_ok());
+        if !has_number {
+            return Err(format!(
+                "the response isn't a shared view, it has no {required_key}"
+            ));
+        }
+    }
+    Ok(Session::decode(text))
+}
+
+/// Returns the session from `link` when the user opens one
+pub fn share_window(state: &mut ShareState, session: impl FnOnce() -> Session) -> Option<Session> {
+    let mut opened = None;
+    if let Some((kind, response)) = &state.request {
+        let kind = *kind;
+        let response = match response.try_recv() {
+            Ok(response) => Some(response),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(String::from("the request panicked"))),
+        };
+        if let Some(response) = response {
+            state.status = match (kind, response) {
+                (RequestKind::Share, Ok(link)) => {
+                    state.link = link.trim().to_owned();
+                    String::from("Shared! Send the link below")
+                }
+                (RequestKind::Share, Err(error)) => format!("Failed to share: {error}"),
+                (RequestKind::Open, Ok(text)) => match shared_session(&text) {
+                    Ok(session) => {
+                        opened = Some(session);
+                        String::from("Opened shared view")
+                    }
+                    Err(error) => format!("Failed to open: {error}"),
+                },
+                (RequestKind::Open, Err(error)) => format!("Failed to open: {error}"),
+            };
+            state.request = None;
+        }
+    }
+
+    Window::new(hash!(), vec2(0.0, 310.0), vec2(250.0, 140.0))
+        .label("share")
+        .titlebar(true)
+        .ui(&mut *root_ui(), |ui| {
+            // one request at a time
+            let idle = state.request.is_none();
+            ui.input_text(hash!(), "endpoint", &mut state.endpoint);
+            if ui.button(None, "Share") && idle {
+                let response = spawn_request("POST", state.endpoint.clone(), session().encode());
+                state.request = Some((RequestKind::Share, response));
+                state.status = String::from("sharing…");
+            }
+
+            ui.input_text(hash!(), "link", &mut state.link);
+            if ui.button(None, "Open Shared") && idle {
+                let response = spawn_request("GET", state.link.clone(), String::new());
+                state.request = Some((RequestKind::Open, response));
+                state.status = String::from("opening…");
+            }
+
+            ui.label(None, &state.status);
+        });
+
+    opened
+}
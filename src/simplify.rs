@@ -0,0 +1,76 @@
+//! Polyline simplification, so orbits with thousands of points draw quickly and cleanly
+
+use macroquad::prelude::*;
+
+/// The distance from `point` to the line segment from `start` to `end`
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared == 0.0 {
+        return point.distance(start);
+    }
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    point.distance(start + t * segment)
+}
+
+/// Points per independently simplified span. Orbits that settle into a cycle barely simplify,
+/// which makes Ramer–Douglas–Peucker quadratic, so long polylines are split to bound the work
+const SPAN_LENGTH_MAX: usize = 256;
+
+/// Ramer–Douglas–Peucker simplification.
+/// Returns the indices of the points to keep, in order. The first and last points are always kept,
+/// and no dropped point is further than `epsilon` from the simplified line
+pub fn simplify_polyline(points: &[Vec2], epsilon: f32) -> Vec<usize> {
+    if points.len() < 3 {
+        return (0..points.len()).collect();
+    }
+
+    let last_index = points.len() - 1;
+    let mut keep = vec![false; points.len()];
+    // an explicit stack instead of recursion, because orbits can be thousands of points long
+    let mut spans = Vec::new();
+    for start_index in (0..last_index).step_by(SPAN_LENGTH_MAX) {
+        let end_index = (start_index + SPAN_LENGTH_MAX).min(last_index);
+        keep[start_index] = true;
+        keep[end_index] = true;
+        spans.push((start_index, end_index));
+    }
+
+    while let Some((start_index, end_index)) = spans.pop() {
+        let farthest = (start_index + 1..end_index)
+            .map(|index| {
+                let distance =
+                    distance_to_segment(points[index], points[start_index], points[end_index]);
+                (index, distance)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((farthest_index, distance)) = farthest
+            && distance > epsilon
+        {
+            keep[farthest_index] = true;
+            spans.push((start_index, farthest_index));
+            spans.push((farthest_index, end_index));
+        }
+    }
+
+    keep.iter()
+        .enumerate()
+        .filter_map(|(index, &keep)| keep.then_some(index))
+        .collect()
+}
+
+/// Keep at most `vertex_max` of `indices` by taking every nth one. The first and last are always kept
+pub fn limit_vertices(indices: Vec<usize>, vertex_max: usize) -> Vec<usize> {
+    if indices.len() <= vertex_max.max(2) {
+        return indices;
+    }
+
+    let stride = indices.len().div_ceil(vertex_max.max(2) - 1);
+    let last_index = indices[indices.len() - 1];
+    let mut limited = indices.into_iter().step_by(stride).collect::<Vec<_>>();
+    if limited.last() != Some(&last_index) {
+        limited.push(last_index);
+    }
+    limited
+}
@@ -0,0 +1,132 @@
+//! Automatic palette suggestion.
+//! Tries a range of palette densities and offsets on a sample of the current render,
+//! and keeps the one that spreads brightness over the most levels (high entropy)
+//! without cycling so fast that neighboring pixels flicker between colors (low noise energy).
+
+use crate::{
+    coloring::Coloring,
+    colorizer::{COLORIZERS, Colorizer, FrameStatistics, PixelResult},
+};
+use macroquad::prelude::*;
+use mandelbrot_core::EscapeGrid;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+/// roughly how many pixels are sampled
+const SAMPLE_COUNT: usize = 10_000;
+
+/// brightness levels counted for the entropy
+const BRIGHTNESS_LEVELS: usize = 32;
+
+/// how much a unit of noise (root mean square brightness difference between neighboring pixels) costs against a bit of entropy
+const NOISE_WEIGHT: f32 = 8.0;
+
+/// The palette densities tried, from a quarter to 16 hue cycles
+fn candidate_densities() -> impl Iterator<Item = f32> {
+    (-4..=8).map(|exponent| 2_f32.powf(exponent as f32 / 2.0))
+}
+
+const OFFSET_STEPS: usize = 16;
+
+/// A copy of `coloring` with the palette density and offset that give the most visible contrast for this render
+pub fn suggest_palette(
+    mandelbrot_data: &EscapeGrid,
+    iteration_max: usize,
+    coloring: &Coloring,
+) -> Coloring {
+    let frame = FrameStatistics::new(mandelbrot_data, iteration_max);
+    let colorizers = COLORIZERS
+        .read()
+        .expect("a colorizer panicked while being registered");
+    let colorizer = colorizers.get(&coloring.colorizer);
+
+    // pairs of horizontally neighboring escaped pixels, spread evenly over the image
+    let width = mandelbrot_data.width();
+    let height = mandelbrot_data.height();
+    let stride = (mandelbrot_data.len() as f32 / SAMPLE_COUNT as f32)
+        .sqrt()
+        .ceil()
+        .max(1.0) as usize;
+    let sample_pairs = (0..height)
+        .step_by(stride)
+        .flat_map(|row_index| {
+            (0..width.saturating_sub(1))
+                .step_by(stride)
+                .map(move |column_index| mandelbrot_data.pixel_index(row_index, column_index))
+        })
+        .filter(|&pixel_index| {
+            mandelbrot_data.escape_time(pixel_index).is_some()
+                && mandelbrot_data.escape_time(pixel_index + 1).is_some()
+        })
+        .collect::<Vec<_>>();
+    if sample_pairs.is_empty() {
+        return coloring.clone();
+    }
+
+    let candidates = candidate_densities()
+        .flat_map(|palette_density| {
+            (0..OFFSET_STEPS).map(move |step| Coloring {
+                palette_density,
+                palette_offset: step as f32 / OFFSET_STEPS as f32,
+                ..coloring.clone()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    candidates
+        .into_par_iter()
+        .map(|candidate| {
+            let score = score(
+                mandelbrot_data,
+                &sample_pairs,
+                colorizer,
+                &frame,
+                &candidate,
+            );
+            (candidate, score)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _)| candidate)
+        .unwrap_or_else(|| coloring.clone())
+}
+
+/// Entropy of the brightness histogram in bits, minus the weighted noise between neighbors
+fn score(
+    mandelbrot_data: &EscapeGrid,
+    sample_pairs: &[usize],
+    colorizer: &dyn Colorizer,
+    frame: &FrameStatistics,
+    coloring: &Coloring,
+) -> f32 {
+    let brightness = |pixel_index: usize| {
+        let pixel = PixelResult {
+            escape_time: mandelbrot_data.escape_time(pixel_index),
+            last_z: mandelbrot_data.last_z(pixel_index),
+            escape_path: None,
+            statistics: mandelbrot_data.statistics(pixel_index),
+        };
+        let color = colorizer.color(pixel, frame, coloring);
+        0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b
+    };
+
+    let mut histogram = [0_usize; BRIGHTNESS_LEVELS];
+    let mut noise_energy = 0.0;
+    for &pixel_index in sample_pairs {
+        let left = brightness(pixel_index);
+        let right = brightness(pixel_index + 1);
+        let level = (left * BRIGHTNESS_LEVELS as f32) as usize;
+        histogram[level.min(BRIGHTNESS_LEVELS - 1)] += 1;
+        noise_energy += (left - right).powi(2);
+    }
+
+    let sample_count = sample_pairs.len() as f32;
+    let entropy = histogram
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let probability = count as f32 / sample_count;
+            -probability * probability.log2()
+        })
+        .sum::<f32>();
+
+    entropy - NOISE_WEIGHT * (noise_energy / sample_count).sqrt()
+}
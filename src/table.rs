@@ -0,0 +1,162 @@
+//! Export the escape times of a rectangle of the render as a table, for analysis in a spreadsheet or script.
+//! Rows and columns are labelled with the imaginary and real parts of the pixels they sample.
+//! Tables are limited to `TABLE_SIDE_MAX` pixels on each side, so select a region rather than dumping the whole frame.
+
+use crate::contour::smooth_iteration;
+use mandelbrot_core::{EscapeGrid, FractalFormula, PixelRect, RenderJob};
+use serde::Serialize;
+
+/// The most rows or columns a table can have
+pub const TABLE_SIDE_MAX: usize = 1000;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    /// one table of escape times, followed by a blank line and a table of smooth values when they're included.
+    /// The top left cell names the table, and pixels that never escaped are left empty
+    #[default]
+    Csv,
+    /// `{"re": [...], "im": [...], "escape_times": [[...]], "smooth": [[...]]}`, one inner array per row,
+    /// with `null` for pixels that never escaped
+    Json,
+}
+
+impl TableFormat {
+    pub const ALL: [Self; 2] = [Self::Csv, Self::Json];
+    pub const NAMES: [&'static str; 2] = ["csv", "json"];
+
+    /// The usual file extension
+    pub fn extension(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Table {
+    /// the real part of each column
+    re: Vec<f32>,
+    /// the imaginary part of each row
+    im: Vec<f32>,
+    escape_times: Vec<Vec<Option<usize>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    smooth: Option<Vec<Vec<Option<f32>>>>,
+}
+
+impl Table {
+    fn new<F: FractalFormula<f32>>(
+        mandelbrot_data: &EscapeGrid,
+        job: &RenderJob<f32, F>,
+        rect: PixelRect,
+        include_smooth: bool,
+    ) -> Self {
+        Self {
+            re: (rect.column..rect.column + rect.width)
+                .map(|column_index| job.pixel_coordinate(rect.row, column_index).re)
+                .collect(),
+            im: (rect.row..rect.row + rect.height)
+                .map(|row_index| job.pixel_coordinate(row_index, rect.column).im)
+                .collect(),
+            escape_times: cells(mandelbrot_data, rect, |pixel_index| {
+                mandelbrot_data.escape_time(pixel_index)
+            }),
+            smooth: include_smooth.then(|| {
+                cells(mandelbrot_data, rect, |pixel_index| {
+                    mandelbrot_data.escape_time(pixel_index).map(|escape_time| {
+                        smooth_iteration(escape_time, mandelbrot_data.last_z(pixel_index))
+                    })
+                })
+            }),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        fn write_grid<T: ToString>(
+            csv: &mut String,
+            name: &str,
+            re: &[f32],
+            im: &[f32],
+            grid: &[Vec<Option<T>>],
+        ) {
+            csv.push_str(name);
+            for re in re {
+                csv.push_str(&format!(",{re}"));
+            }
+            csv.push('\n');
+            for (im, row) in im.iter().zip(grid) {
+                csv.push_str(&im.to_string());
+                for cell in row {
+                    csv.push(',');
+                    if let Some(cell) = cell {
+                        csv.push_str(&cell.to_string());
+                    }
+                }
+                csv.push('\n');
+            }
+        }
+
+        let mut csv = String::new();
+        write_grid(
+            &mut csv,
+            "escape time",
+            &self.re,
+            &self.im,
+            &self.escape_times,
+        );
+        if let Some(smooth) = &self.smooth {
+            csv.push('\n');
+            write_grid(&mut csv, "smooth", &self.re, &self.im, smooth);
+        }
+        csv
+    }
+}
+
+/// `value` of each pixel in `rect`, one `Vec` per row
+fn cells<T>(
+    mandelbrot_data: &EscapeGrid,
+    rect: PixelRect,
+    value: impl Fn(usize) -> Option<T>,
+) -> Vec<Vec<Option<T>>> {
+    (rect.row..rect.row + rect.height)
+        .map(|row_index| {
+            (rect.column..rect.column + rect.width)
+                .map(|column_index| value(mandelbrot_data.pixel_index(row_index, column_index)))
+                .collect()
+        })
+        .collect()
+}
+
+/// The escape times (and smooth values with `include_smooth`) of the pixels in `rect`, written in `format`.
+/// `job` is the job that calculated `mandelbrot_data`, used to label the rows and columns
+pub fn region_table<F: FractalFormula<f32>>(
+    mandelbrot_data: &EscapeGrid,
+    job: &RenderJob<f32, F>,
+    rect: PixelRect,
+    include_smooth: bool,
+    format: TableFormat,
+) -> Result<String, String> {
+    if rect.width == 0 || rect.height == 0 {
+        return Err(String::from("The region is empty"));
+    }
+    if rect.row + rect.height > mandelbrot_data.height()
+        || rect.column + rect.width > mandelbrot_data.width()
+    {
+        return Err(format!(
+            "The {}x{} region isn't inside the {}x{} render",
+            rect.width,
+            rect.height,
+            mandelbrot_data.width(),
+            mandelbrot_data.height()
+        ));
+    }
+    if rect.width > TABLE_SIDE_MAX || rect.height > TABLE_SIDE_MAX {
+        return Err(format!(
+            "The {}x{} region is too large, tables can be at most {TABLE_SIDE_MAX} pixels on each side",
+            rect.width, rect.height
+        ));
+    }
+
+    let table = Table::new(mandelbrot_data, job, rect, include_smooth);
+    match format {
+        TableFormat::Csv => Ok(table.to_csv()),
+        TableFormat::Json => serde_json::to_string(&table).map_err(|error| error.to_string()),
+    }
+}
@@ -0,0 +1,311 @@
+//! A guided walk through the escape time algorithm.
+//! One pixel is highlighted, its orbit is animated, the bailout circle is shown,
+//! and the numbers that chose its color are spelled out.
+
+use crate::{
+    ESCAPE_PATH_COLOR, MandelbrotRender,
+    animation::AnimationClock,
+    coloring::Coloring,
+    colorizer::{COLORIZERS, FrameStatistics, PixelResult},
+    complex_to_screen_coordinate,
+    contour::smooth_iteration,
+    draw_bailout, draw_escape_path,
+    orbit::OrbitStyle,
+};
+use macroquad::{
+    prelude::*,
+    ui::{hash, root_ui, widgets::Window},
+};
+use num::Complex;
+
+/// Seconds between each new `z` of the animated orbit
+const ORBIT_STEP_SECONDS: f32 = 0.4;
+
+/// Escape times that make an orbit long enough to be interesting and short enough to watch
+const INTERESTING_ESCAPE_TIMES: std::ops::RangeInclusive<usize> = 8..=40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TutorialStep {
+    Pixel,
+    Orbit,
+    Bailout,
+    Color,
+}
+
+impl TutorialStep {
+    const ALL: [Self; 4] = [Self::Pixel, Self::Orbit, Self::Bailout, Self::Color];
+}
+
+/// Everything the tutorial explains about the highlighted pixel
+struct TutorialPixel {
+    screen_position: Vec2,
+    escape_time: Option<usize>,
+    /// every z value visited, starting with `z₀ = 0`
+    escape_path: Vec<Complex<f32>>,
+    /// the color before slope shading
+    color: Color,
+}
+
+#[derive(Default)]
+pub struct Tutorial {
+    /// index into `TutorialStep::ALL`. `None` while the tutorial isn't running
+    step_index: Option<usize>,
+    /// time since the current step was entered, used to animate the orbit
+    step_clock: AnimationClock,
+    pixel: Option<TutorialPixel>,
+}
+
+impl Tutorial {
+    pub fn is_running(&self) -> bool {
+        self.step_index.is_some()
+    }
+
+    fn step(&self) -> Option<TutorialStep> {
+        self.step_index
+            .map(|step_index| TutorialStep::ALL[step_index])
+    }
+
+    fn go_to_step(&mut self, step_index: Option<usize>) {
+        self.step_index = step_index;
+        self.step_clock.restart();
+    }
+
+    /// Start explaining the pixel at `screen_position`
+    fn pick(&mut self, screen_position: Vec2, render: &MandelbrotRender, coloring: &Coloring) {
+        let Some((escape_time, escape_path)) = render.pixel_escape_data(screen_position) else {
+            return;
+        };
+
+        let frame = FrameStatistics::new(&render.data, render.iteration_max);
+        let pixel = PixelResult {
+            escape_time,
+            last_z: *escape_path.last().expect("every orbit has a z₀"),
+            escape_path: Some(&*escape_path),
+            statistics: None,
+        };
+        let color = COLORIZERS
+            .read()
+            .expect("a colorizer panicked while being registered")
+            .get(&coloring.colorizer)
+            .color(pixel, &frame, coloring);
+
+        self.pixel = Some(TutorialPixel {
+            screen_position,
+            escape_time,
+            escape_path: escape_path.into_owned(),
+            color,
+        });
+        // replay the animation for the new orbit
+        self.step_clock.restart();
+    }
+
+    /// The pixel nearest the middle of the screen whose orbit is worth watching
+    fn interesting_pixel(render: &MandelbrotRender) -> Vec2 {
+        let width = render.data.width();
+        let middle = vec2(width as f32, render.data.height() as f32) / 2.0;
+
+        (0..render.data.len())
+            .filter(|&pixel_index| {
+                render
+                    .data
+                    .escape_time(pixel_index)
+                    .is_some_and(|escape_time| INTERESTING_ESCAPE_TIMES.contains(&escape_time))
+            })
+            .map(|pixel_index| vec2((pixel_index % width) as f32, (pixel_index / width) as f32))
+            .min_by(|a, b| {
+                a.distance_squared(middle)
+                    .total_cmp(&b.distance_squared(middle))
+            })
+            .unwrap_or(middle)
+    }
+
+    /// How many z values of the orbit have been revealed so far
+    fn revealed_length(&self, escape_path: &[Complex<f32>]) -> usize {
+        let steps = (self.step_clock.elapsed() / ORBIT_STEP_SECONDS) as usize;
+        (steps + 2).min(escape_path.len())
+    }
+
+    /// Draw the overlays for the current step
+    pub fn draw(
+        &self,
+        center: Complex<f32>,
+        dimensions: Complex<f32>,
+        rotation: f32,
+        iteration_max: usize,
+        coloring: &Coloring,
+    ) {
+        let (Some(step), Some(pixel)) = (self.step(), &self.pixel) else {
+            return;
+        };
+
+        // always show which pixel is being explained
+        let position = pixel.screen_position;
+        draw_rectangle_lines(position.x - 6.0, position.y - 6.0, 12.0, 12.0, 2.0, YELLOW);
+
+        match step {
+            TutorialStep::Pixel => {
+                draw_circle_lines(position.x, position.y, 20.0, 2.0, YELLOW);
+            }
+            TutorialStep::Orbit => {
+                let revealed = self.revealed_length(&pixel.escape_path);
+                draw_escape_path(
+                    &pixel.escape_path[..revealed],
+                    |z| complex_to_screen_coordinate(z, center, dimensions, rotation),
+                    ESCAPE_PATH_COLOR,
+                    iteration_max,
+                    coloring,
+                    &OrbitStyle::default(),
+                );
+            }
+            TutorialStep::Bailout | TutorialStep::Color => {
+                draw_bailout(
+                    pixel.escape_time,
+                    &pixel.escape_path,
+                    center,
+                    dimensions,
+                    rotation,
+                );
+                draw_escape_path(
+                    &pixel.escape_path,
+                    |z| complex_to_screen_coordinate(z, center, dimensions, rotation),
+                    ESCAPE_PATH_COLOR,
+                    iteration_max,
+                    coloring,
+                    &OrbitStyle::default(),
+                );
+
+                if step == TutorialStep::Color {
+                    draw_rectangle(
+                        position.x + 12.0,
+                        position.y - 20.0,
+                        40.0,
+                        40.0,
+                        pixel.color,
+                    );
+                    draw_rectangle_lines(
+                        position.x + 12.0,
+                        position.y - 20.0,
+                        40.0,
+                        40.0,
+                        2.0,
+                        YELLOW,
+                    );
+                }
+            }
+        }
+    }
+
+    /// The explanation for the current step, one short line per entry
+    fn explanation(&self, coloring: &Coloring, iteration_max: usize) -> Vec<String> {
+        let (Some(step), Some(pixel)) = (self.step(), &self.pixel) else {
+            return Vec::new();
+        };
+        let c = pixel.escape_path.get(1).copied().unwrap_or_default();
+
+        match step {
+            TutorialStep::Pixel => vec![
+                String::from("Every pixel is a complex number c."),
+                format!("This one is c = {:.4} + {:.4}i", c.re, c.im),
+                String::from("Click the image to pick another."),
+            ],
+            TutorialStep::Orbit => {
+                let revealed = self.revealed_length(&pixel.escape_path);
+                let z = pixel.escape_path[revealed - 1];
+                vec![
+                    String::from("Start at z0 = 0 and repeat"),
+                    String::from("z = z^2 + c. The dots are the orbit."),
+                    format!("z{} = {:.3} + {:.3}i", revealed - 1, z.re, z.im),
+                ]
+            }
+            TutorialStep::Bailout => match pixel.escape_time {
+                Some(escape_time) => vec![
+                    String::from("Once |z| > 2 (outside the circle)"),
+                    String::from("the orbit always runs off to infinity."),
+                    format!("This orbit escaped at step {escape_time}."),
+                ],
+                None => vec![
+                    String::from("Once |z| > 2 (outside the circle)"),
+                    String::from("the orbit always runs off to infinity."),
+                    format!("This orbit stayed inside for {iteration_max}"),
+                    String::from("steps, so c counts as in the set."),
+                ],
+            },
+            TutorialStep::Color => match pixel.escape_time {
+                Some(escape_time) => {
+                    let smoothed = smooth_iteration(escape_time, pixel.escape_path[escape_time]);
+                    let mut lines = vec![
+                        String::from("The escape time picks the color."),
+                        format!("escape time {escape_time}, smoothed {smoothed:.2}"),
+                        format!("The {} coloring made the swatch.", coloring.colorizer),
+                    ];
+                    if coloring.slope_shading {
+                        lines.push(String::from("Slope shading then lights it."));
+                    }
+                    lines
+                }
+                None => vec![
+                    String::from("Points in the set never escape,"),
+                    String::from("so they have no escape time to"),
+                    String::from("color by and are drawn black."),
+                ],
+            },
+        }
+    }
+}
+
+pub fn tutorial_window(tutorial: &mut Tutorial, render: &MandelbrotRender, coloring: &Coloring) {
+    if tutorial.is_running() {
+        tutorial.step_clock.advance();
+    }
+
+    // clicking the image picks a new pixel, clicking a window doesn't
+    let mouse_position = Vec2::from(mouse_position());
+    if tutorial.is_running()
+        && is_mouse_button_pressed(MouseButton::Left)
+        && !root_ui().is_mouse_over(mouse_position)
+    {
+        tutorial.pick(mouse_position, render, coloring);
+    }
+
+    let mut next_step_index = tutorial.step_index;
+    let explanation = tutorial.explanation(coloring, render.iteration_max);
+    Window::new(hash!(), vec2(0.0, 460.0), vec2(250.0, 180.0))
+        .label("tutorial")
+        .titlebar(true)
+        .ui(&mut *root_ui(), |ui| match tutorial.step_index {
+            None => {
+                ui.label(None, "Learn how each pixel is colored.");
+                if ui.button(None, "Start Tutorial") {
+                    next_step_index = Some(0);
+                }
+            }
+            Some(step_index) => {
+                ui.label(
+                    None,
+                    &format!("step {} of {}", step_index + 1, TutorialStep::ALL.len()),
+                );
+                for line in &explanation {
+                    ui.label(None, line);
+                }
+                if ui.button(None, "Back") {
+                    next_step_index = Some(step_index.saturating_sub(1));
+                }
+                ui.same_line(0.0);
+                let is_last_step = step_index + 1 == TutorialStep::ALL.len();
+                if ui.button(None, if is_last_step { "Done" } else { "Next" }) {
+                    next_step_index = (!is_last_step).then_some(step_index + 1);
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "Close") {
+                    next_step_index = None;
+                }
+            }
+        });
+
+    if next_step_index != tutorial.step_index {
+        if tutorial.step_index.is_none() {
+            tutorial.pick(Tutorial::interesting_pixel(render), render, coloring);
+        }
+        tutorial.go_to_step(next_step_index);
+    }
+}
@@ -0,0 +1,133 @@
+//! Scaling the UI for high DPI screens, where the default font sizes and hit targets are tiny,
+//! and a high contrast skin for when the default grays are hard to tell apart
+
+use macroquad::{
+    prelude::*,
+    ui::{Skin, root_ui},
+};
+
+/// The default skin's font size
+const FONT_SIZE: f32 = 16.0;
+/// Space around the text of buttons, text boxes, and checkboxes
+const WIDGET_MARGIN: f32 = 4.0;
+/// Styles can't be copied out of the default skin, so the widget colors are set again
+const WIDGET_COLOR: Color = Color::new(0.8, 0.8, 0.8, 0.92);
+const WIDGET_HOVERED_COLOR: Color = Color::new(0.67, 0.67, 0.67, 0.92);
+const WIDGET_CLICKED_COLOR: Color = Color::new(0.73, 0.73, 0.73, 1.0);
+/// The color of a checked checkbox
+const WIDGET_SELECTED_COLOR: Color = Color::new(0.47, 0.47, 0.47, 1.0);
+/// The high contrast skin has black text on white, with yellow under the mouse and black for checked checkboxes
+const HIGH_CONTRAST_WIDGET_COLOR: Color = WHITE;
+const HIGH_CONTRAST_HOVERED_COLOR: Color = YELLOW;
+const HIGH_CONTRAST_CLICKED_COLOR: Color = GOLD;
+const HIGH_CONTRAST_SELECTED_COLOR: Color = BLACK;
+
+pub const UI_SCALE_MIN: f32 = 0.5;
+pub const UI_SCALE_MAX: f32 = 4.0;
+
+/// The scale of a screen's pixels compared to a standard DPI screen, e.g. `2.0` on most 4K screens
+pub fn detected_ui_scale() -> f32 {
+    screen_dpi_scale().clamp(UI_SCALE_MIN, UI_SCALE_MAX)
+}
+
+/// Keeps the root UI's skin matching the UI scale and contrast
+#[derive(Debug, Default)]
+pub struct UiScale {
+    /// the scale and contrast of the pushed skin. `None` until the first skin is pushed
+    applied: Option<(f32, bool)>,
+    /// the height of the windows' title bars in the pushed skin
+    title_height: f32,
+}
+
+impl UiScale {
+    /// Call once per frame before any window is shown
+    pub fn apply(&mut self, scale: f32, high_contrast: bool) {
+        if self.applied == Some((scale, high_contrast)) {
+            return;
+        }
+        if self.applied.is_some() {
+            root_ui().pop_skin();
+        }
+        let skin = scaled_skin(scale, high_contrast);
+        root_ui().push_skin(&skin);
+        self.applied = Some((scale, high_contrast));
+        self.title_height = skin.title_height;
+    }
+
+    pub fn title_height(&self) -> f32 {
+        self.title_height
+    }
+}
+
+/// The default skin with its fonts and margins scaled, and optionally its grays replaced by high contrast colors
+fn scaled_skin(scale: f32, high_contrast: bool) -> Skin {
+    let mut ui = root_ui();
+    let font_size = (FONT_SIZE * scale).round() as u16;
+    let margin = WIDGET_MARGIN * scale;
+    let widget_margin = RectOffset::new(margin * 2.0, margin * 2.0, margin, margin);
+    let default_skin = ui.default_skin();
+    let (widget_color, hovered_color, clicked_color, selected_color) = if high_contrast {
+        (
+            HIGH_CONTRAST_WIDGET_COLOR,
+            HIGH_CONTRAST_HOVERED_COLOR,
+            HIGH_CONTRAST_CLICKED_COLOR,
+            HIGH_CONTRAST_SELECTED_COLOR,
+        )
+    } else {
+        (
+            WIDGET_COLOR,
+            WIDGET_HOVERED_COLOR,
+            WIDGET_CLICKED_COLOR,
+            WIDGET_SELECTED_COLOR,
+        )
+    };
+    // the default window background is a light gray
+    let window_style = if high_contrast {
+        ui.style_builder().color(WHITE).text_color(BLACK).build()
+    } else {
+        default_skin.window_style.clone()
+    };
+
+    Skin {
+        label_style: ui.style_builder().font_size(font_size).build(),
+        button_style: ui
+            .style_builder()
+            .font_size(font_size)
+            .margin(widget_margin)
+            .color(widget_color)
+            .color_hovered(hovered_color)
+            .color_clicked(clicked_color)
+            .build(),
+        editbox_style: ui
+            .style_builder()
+            .font_size(font_size)
+            .margin(widget_margin)
+            .color(widget_color)
+            .color_hovered(hovered_color)
+            .color_clicked(clicked_color)
+            .build(),
+        checkbox_style: ui
+            .style_builder()
+            .font_size(font_size)
+            .margin(widget_margin)
+            .color(widget_color)
+            .color_hovered(hovered_color)
+            .color_clicked(clicked_color)
+            .color_selected(selected_color)
+            .build(),
+        combobox_style: ui
+            .style_builder()
+            .font_size(font_size)
+            .margin(widget_margin)
+            .color(widget_color)
+            .color_hovered(hovered_color)
+            .color_clicked(clicked_color)
+            .build(),
+        window_style,
+        window_titlebar_style: ui.style_builder().font_size(font_size).build(),
+        title_height: default_skin.title_height * scale,
+        scroll_width: default_skin.scroll_width * scale,
+        margin: default_skin.margin * scale,
+        ..default_skin
+    }
+}
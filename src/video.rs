@@ -0,0 +1,80 @@
+//! Encode frames straight to a video by piping them to an `ffmpeg` process, which has to be installed and on the path.
+//! Frames are written to its stdin as raw RGBA. The container comes from the file extension: `.webm` is VP9, and anything else is H.264.
+
+use std::{
+    io::Write,
+    path::Path,
+    process::{Child, Command, Stdio},
+};
+
+/// The codec arguments for the container `path` ends in
+fn codec_args(path: &str) -> &'static [&'static str] {
+    let is_webm = Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("webm"));
+    if is_webm {
+        &["-c:v", "libvpx-vp9"]
+    } else {
+        // most players only play H.264 in 4:2:0
+        &["-c:v", "libx264", "-pix_fmt", "yuv420p"]
+    }
+}
+
+/// An `ffmpeg` process frames are being written to
+pub struct VideoEncoder {
+    ffmpeg: Child,
+    path: String,
+}
+
+impl VideoEncoder {
+    /// Start encoding `width` by `height` frames at `fps` frames per second and `bitrate` kilobits per second to `path`
+    pub fn start(
+        path: &str,
+        width: usize,
+        height: usize,
+        fps: usize,
+        bitrate: usize,
+    ) -> Result<Self, String> {
+        let ffmpeg = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pixel_format", "rgba"])
+            .args(["-video_size", &format!("{width}x{height}")])
+            .args(["-framerate", &fps.to_string()])
+            .args(["-i", "-"])
+            .args(codec_args(path))
+            .args(["-b:v", &format!("{bitrate}k"), path])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("Failed to start ffmpeg. Is it installed?: {error}"))?;
+        Ok(Self {
+            ffmpeg,
+            path: path.to_owned(),
+        })
+    }
+
+    /// Encode the next frame, `width * height` RGBA pixels from the top row down
+    pub fn write_frame(&mut self, rgba: &[u8]) -> Result<(), String> {
+        self.ffmpeg
+            .stdin
+            .as_mut()
+            .ok_or("ffmpeg's input was already closed")?
+            .write_all(rgba)
+            .map_err(|error| format!("Failed to send a frame to ffmpeg: {error}"))
+    }
+
+    /// Close the input and wait for `ffmpeg` to finish writing the video. Returns its path
+    pub fn finish(mut self) -> Result<String, String> {
+        // ffmpeg finishes once its input ends
+        drop(self.ffmpeg.stdin.take());
+        let status = self
+            .ffmpeg
+            .wait()
+            .map_err(|error| format!("Failed to wait for ffmpeg: {error}"))?;
+        if status.success() {
+            Ok(self.path)
+        } else {
+            Err(format!("ffmpeg failed with {status}"))
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Everything that decides what a render looks like, independent of the window it's shown in.
+//! Serializable so views can be saved, shared, and sent to the headless renderer.
+//! View files are TOML when their extension is `.toml`, Kalles Fraktaler locations when it's `.kfr` (see `kfr`), and JSON otherwise.
+
+use crate::{
+    coloring::Coloring,
+    complex_dimensions,
+    formula::{FORMULAS, find_formula},
+    kfr::{from_kfr, to_kfr},
+};
+use mandelbrot_core::{FractalFormula, RenderJob};
+use num::Complex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A place on the complex plane and how to draw it. Missing fields are filled with the defaults when deserializing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewParams {
+    /// the complex number in the middle of the view, serialized as `[re, im]`
+    pub center: Complex<f32>,
+    /// zoom level, where 1 shows the whole set
+    pub scale: f32,
+    pub iteration_max: usize,
+    /// name of the `FormulaEntry` to render
+    pub formula: String,
+    pub coloring: Coloring,
+    /// how far the view is turned around `center`, in degrees counterclockwise
+    pub rotation: f32,
+}
+
+impl Default for ViewParams {
+    fn default() -> Self {
+        Self {
+            center: Complex::new(-0.4, 0.0),
+            scale: 1.0,
+            iteration_max: 500,
+            formula: FORMULAS[0].name.to_owned(),
+            coloring: Coloring::default(),
+            rotation: 0.0,
+        }
+    }
+}
+
+/// How a view is calculated off screen
+pub type ViewJob = RenderJob<f32, &'static (dyn FractalFormula<f32> + Send)>;
+
+/// A job for `view` at `width` by `height` pixels, for rendering it off screen whole or a tile at a time
+pub fn view_job(view: &ViewParams, width: usize, height: usize) -> ViewJob {
+    let formula = find_formula(&view.formula);
+    RenderJob::new()
+        .resolution(width, height)
+        .center(view.center)
+        .dimensions(complex_dimensions(view.scale, width as f32, height as f32))
+        .rotation(view.rotation.to_radians() as f64)
+        .iteration_max(view.iteration_max)
+        .formula(&*formula.formula)
+        // slope shading needs the whole path, and the other colorings only need its statistics
+        .store_paths(view.coloring.slope_shading)
+        .orbit_statistics(!view.coloring.slope_shading)
+}
+
+/// Whether a view file at `path` has the extension `expected`
+fn has_extension(path: &str, expected: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(expected))
+}
+
+impl ViewParams {
+    /// Write the view to a JSON, TOML, or `.kfr` file. `aspect` is the width of the view over its height
+    pub fn save(&self, path: &str, aspect: f32) -> Result<(), String> {
+        let text = if has_extension(path, "toml") {
+            toml::to_string_pretty(self).map_err(|error| error.to_string())?
+        } else if has_extension(path, "kfr") {
+            to_kfr(self, aspect)?
+        } else {
+            serde_json::to_string_pretty(self).map_err(|error| error.to_string())?
+        };
+        std::fs::write(path, text).map_err(|error| format!("Failed to write {path:?}: {error}"))
+    }
+
+    /// Read a view written by `save`, or by hand. A `.kfr` file only has a location,
+    /// so the rest is taken from `current`. `aspect` is the width of the view over its height
+    pub fn load(path: &str, aspect: f32, current: Self) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| format!("Failed to read {path:?}: {error}"))?;
+        let view = if has_extension(path, "toml") {
+            toml::from_str(&text).map_err(|error| error.to_string())
+        } else if has_extension(path, "kfr") {
+            from_kfr(&text, aspect, current)
+        } else {
+            serde_json::from_str(&text).map_err(|error| error.to_string())
+        };
+        view.map_err(|error| format!("{path:?} isn't a valid view: {error}"))
+    }
+}